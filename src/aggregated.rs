@@ -0,0 +1,414 @@
+// SPDX-License-Identifier: MIT
+
+use std::ops::RangeInclusive;
+
+use crate::secondary_map::SlotMap;
+use crate::{GenerationalTokenList, ItemToken, Iter, IterWithTokens};
+
+/// A node of the tree that mirrors the list order, keyed by the token of its item.
+#[derive(Clone, Debug)]
+struct Node<A> {
+    left: Option<ItemToken>,
+    right: Option<ItemToken>,
+    parent: Option<ItemToken>,
+    priority: u64,
+    /// Number of nodes in the subtree rooted here.
+    size: usize,
+    /// The measure of this node's item.
+    value: A,
+    /// The combined measures of the subtree rooted here, in list order.
+    total: A,
+}
+
+/// A treap with implicit keys: the in-order sequence of its nodes is the list order.
+#[derive(Clone, Debug)]
+struct Tree<A> {
+    nodes: SlotMap<Node<A>>,
+    root: Option<ItemToken>,
+    identity: A,
+    combine: fn(&A, &A) -> A,
+    /// State of the generator for node priorities.
+    seed: u64,
+}
+
+impl<A: Clone> Tree<A> {
+    fn size(&self, node: Option<ItemToken>) -> usize {
+        node.map_or(0, |node| self.nodes[node].size)
+    }
+
+    fn set_parent(&mut self, node: Option<ItemToken>, parent: Option<ItemToken>) {
+        if let Some(node) = node {
+            self.nodes[node].parent = parent;
+        }
+    }
+
+    /// Recompute the size and total of `node` from its children.
+    fn pull(&mut self, node: ItemToken) {
+        let Node {
+            left, right, value, ..
+        } = &self.nodes[node];
+        let (left, right) = (*left, *right);
+        let mut total = value.clone();
+        if let Some(left) = left {
+            total = (self.combine)(&self.nodes[left].total, &total);
+        }
+        if let Some(right) = right {
+            total = (self.combine)(&total, &self.nodes[right].total);
+        }
+
+        let size = 1 + self.size(left) + self.size(right);
+        let node = &mut self.nodes[node];
+        node.size = size;
+        node.total = total;
+    }
+
+    /// Split the subtree rooted at `node` into its first `k` nodes and the rest.
+    fn split(
+        &mut self,
+        node: Option<ItemToken>,
+        k: usize,
+    ) -> (Option<ItemToken>, Option<ItemToken>) {
+        let node = match node {
+            Some(node) => node,
+            None => return (None, None),
+        };
+
+        let left_size = self.size(self.nodes[node].left);
+        let (first, rest) = if k <= left_size {
+            let (first, rest) = self.split(self.nodes[node].left, k);
+            self.nodes[node].left = rest;
+            self.set_parent(rest, Some(node));
+            (first, Some(node))
+        } else {
+            let (first, rest) = self.split(self.nodes[node].right, k - left_size - 1);
+            self.nodes[node].right = first;
+            self.set_parent(first, Some(node));
+            (Some(node), rest)
+        };
+        self.pull(node);
+        self.set_parent(first, None);
+        self.set_parent(rest, None);
+        (first, rest)
+    }
+
+    /// Join two subtrees, with all nodes of `first` coming before those of `rest`.
+    fn merge(&mut self, first: Option<ItemToken>, rest: Option<ItemToken>) -> Option<ItemToken> {
+        let (first, rest) = match (first, rest) {
+            (None, node) | (node, None) => return node,
+            (Some(first), Some(rest)) => (first, rest),
+        };
+
+        if self.nodes[first].priority > self.nodes[rest].priority {
+            let right = self.merge(self.nodes[first].right, Some(rest));
+            self.nodes[first].right = right;
+            self.set_parent(right, Some(first));
+            self.pull(first);
+            Some(first)
+        } else {
+            let left = self.merge(Some(first), self.nodes[rest].left);
+            self.nodes[rest].left = left;
+            self.set_parent(left, Some(rest));
+            self.pull(rest);
+            Some(rest)
+        }
+    }
+
+    /// Returns the position of `node` in list order.
+    fn rank(&self, node: ItemToken) -> usize {
+        let mut rank = self.size(self.nodes[node].left);
+        let mut current = node;
+        while let Some(parent) = self.nodes[current].parent {
+            if self.nodes[parent].right == Some(current) {
+                rank += self.size(self.nodes[parent].left) + 1;
+            }
+            current = parent;
+        }
+        rank
+    }
+
+    fn insert_at(&mut self, token: ItemToken, value: A, position: usize) {
+        // splitmix64
+        self.seed = self.seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut priority = self.seed;
+        priority = (priority ^ (priority >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        priority = (priority ^ (priority >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        priority ^= priority >> 31;
+
+        self.nodes.insert(
+            token,
+            Node {
+                left: None,
+                right: None,
+                parent: None,
+                priority,
+                size: 1,
+                total: value.clone(),
+                value,
+            },
+        );
+        let (first, rest) = self.split(self.root, position);
+        let first = self.merge(first, Some(token));
+        self.root = self.merge(first, rest);
+    }
+
+    fn remove(&mut self, token: ItemToken) {
+        let (first, rest) = self.split(self.root, self.rank(token));
+        let (_, rest) = self.split(rest, 1);
+        self.nodes.remove(token);
+        self.root = self.merge(first, rest);
+    }
+
+    fn set_value(&mut self, token: ItemToken, value: A) {
+        self.nodes[token].value = value;
+        let mut current = Some(token);
+        while let Some(node) = current {
+            self.pull(node);
+            current = self.nodes[node].parent;
+        }
+    }
+
+    /// Combine the values at positions `start..end` of the subtree rooted at `node`, or return
+    /// `None` if the range is empty.
+    fn fold(&self, node: Option<ItemToken>, start: usize, end: usize) -> Option<A> {
+        let node = &self.nodes[node?];
+        if start == 0 && end == node.size {
+            return Some(node.total.clone());
+        }
+
+        let left_size = self.size(node.left);
+        let mut result = None;
+        if start < left_size {
+            result = self.fold(node.left, start, end.min(left_size));
+        }
+        if start <= left_size && left_size < end {
+            result = Some(match result {
+                Some(left) => (self.combine)(&left, &node.value),
+                None => node.value.clone(),
+            });
+        }
+        if end > left_size + 1 {
+            let right = self.fold(
+                node.right,
+                start.max(left_size + 1) - left_size - 1,
+                end - left_size - 1,
+            );
+            result = match (result, right) {
+                (Some(left), Some(right)) => Some((self.combine)(&left, &right)),
+                (left, right) => left.or(right),
+            };
+        }
+        result
+    }
+}
+
+/// A wrapper around [`GenerationalTokenList`] that keeps track of an aggregate of its items, like
+/// the sum of a field or the maximum priority.
+///
+/// The aggregate is described by a monoid: `measure` maps each item to a value, and `combine`
+/// joins two values (it must be associative, with `identity` as its neutral element, but needn't
+/// be commutative). Alongside the list, the wrapper keeps a balanced tree of the values in list
+/// order, so [`aggregate`](Self::aggregate) takes O(1) time, and
+/// [`aggregate_range`](Self::aggregate_range) as well as every insertion, removal and
+/// [`update`](Self::update) take O(log n) time.
+///
+/// Items can't be mutated in place except through `update`, which keeps the tree up to date.
+///
+/// # Examples
+/// ```
+/// # use generational_token_list::AggregatedTokenList;
+/// let mut tasks = AggregatedTokenList::new(0, |task: &(&str, u32)| task.1, |a, b| *a.max(b));
+/// let build = tasks.push_back(("build", 3));
+/// let test = tasks.push_back(("test", 7));
+/// let deploy = tasks.push_back(("deploy", 5));
+/// assert_eq!(tasks.aggregate(), 7);
+/// assert_eq!(tasks.aggregate_range(build..=test), 7);
+///
+/// tasks.remove(test);
+/// assert_eq!(tasks.aggregate(), 5);
+/// tasks.update(build, |task| task.1 = 9);
+/// assert_eq!(tasks.aggregate_range(build..=deploy), 9);
+/// ```
+#[derive(Clone, Debug)]
+pub struct AggregatedTokenList<T, A> {
+    list: GenerationalTokenList<T>,
+    tree: Tree<A>,
+    measure: fn(&T) -> A,
+}
+
+impl<T, A: Clone> AggregatedTokenList<T, A> {
+    /// Creates a new `AggregatedTokenList`, aggregating the values returned by `measure` with
+    /// `combine`, starting from `identity`.
+    pub fn new(identity: A, measure: fn(&T) -> A, combine: fn(&A, &A) -> A) -> Self {
+        AggregatedTokenList {
+            list: GenerationalTokenList::new(),
+            tree: Tree {
+                nodes: SlotMap::new(),
+                root: None,
+                identity,
+                combine,
+                seed: 0,
+            },
+            measure,
+        }
+    }
+
+    /// Returns the combined values of all items, in list order, or the identity if the list is
+    /// empty.
+    pub fn aggregate(&self) -> A {
+        match self.tree.root {
+            Some(root) => self.tree.nodes[root].total.clone(),
+            None => self.tree.identity.clone(),
+        }
+    }
+
+    /// Returns the combined values of the items from `range.start()` to `range.end()`
+    /// (inclusive), in list order.
+    ///
+    /// # Panics
+    /// Panics if either token is invalid or if `range.end()` does not come at or after
+    /// `range.start()` in the list.
+    pub fn aggregate_range(&self, range: RangeInclusive<ItemToken>) -> A {
+        let (start, end) = range.into_inner();
+        assert!(
+            self.tree.nodes.contains_key(start) && self.tree.nodes.contains_key(end),
+            "invalid token"
+        );
+        let (start, end) = (self.tree.rank(start), self.tree.rank(end));
+        assert!(start <= end, "end of range comes before its start");
+        self.tree.fold(self.tree.root, start, end + 1).unwrap()
+    }
+
+    /// Append an item to the end of the list. Returns a token which corresponds to the new item.
+    pub fn push_back(&mut self, data: T) -> ItemToken {
+        let value = (self.measure)(&data);
+        let token = self.list.push_back(data);
+        self.tree.insert_at(token, value, self.list.len() - 1);
+        token
+    }
+
+    /// Prepend an item to the beginning of the list. Returns a token which corresponds to the new
+    /// item.
+    pub fn push_front(&mut self, data: T) -> ItemToken {
+        let value = (self.measure)(&data);
+        let token = self.list.push_front(data);
+        self.tree.insert_at(token, value, 0);
+        token
+    }
+
+    /// Insert an item after the item identified by given token. Returns a token which corresponds
+    /// to the new item.
+    ///
+    /// # Panics
+    /// Panics if `after` is invalid.
+    pub fn insert_after(&mut self, after: ItemToken, data: T) -> ItemToken {
+        let value = (self.measure)(&data);
+        let token = self.list.insert_after(after, data);
+        self.tree.insert_at(token, value, self.tree.rank(after) + 1);
+        token
+    }
+
+    /// Insert an item before the item identified by given token. Returns a token which corresponds
+    /// to the new item.
+    ///
+    /// # Panics
+    /// Panics if `before` is invalid.
+    pub fn insert_before(&mut self, before: ItemToken, data: T) -> ItemToken {
+        let value = (self.measure)(&data);
+        let token = self.list.insert_before(before, data);
+        self.tree.insert_at(token, value, self.tree.rank(before));
+        token
+    }
+
+    /// Remove the item identified by given token and return it, or `None` if the token is invalid
+    /// (or the item is protected by a [`RemovalGuard`](crate::RemovalGuard)).
+    pub fn remove(&mut self, token: ItemToken) -> Option<T> {
+        let data = self.list.remove(token)?;
+        self.tree.remove(token);
+        Some(data)
+    }
+
+    /// Remove and return the first item, if any.
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.remove(self.list.head_token()?)
+    }
+
+    /// Remove and return the last item, if any.
+    pub fn pop_back(&mut self) -> Option<T> {
+        self.remove(self.list.tail_token()?)
+    }
+
+    /// Modify the item identified by given token with `f`, and update the aggregates. Returns
+    /// `false` if the token is invalid.
+    pub fn update(&mut self, token: ItemToken, f: impl FnOnce(&mut T)) -> bool {
+        let data = match self.list.get_mut(token) {
+            Some(data) => data,
+            None => return false,
+        };
+        f(data);
+        let value = (self.measure)(data);
+        self.tree.set_value(token, value);
+        true
+    }
+
+    /// Remove all items. Invalidates all tokens, except for items protected by a
+    /// [`RemovalGuard`](crate::RemovalGuard), which are kept.
+    pub fn clear(&mut self) {
+        self.list.clear();
+        self.tree.nodes.clear();
+        self.tree.root = None;
+        let kept: Vec<_> = self
+            .list
+            .iter_with_tokens()
+            .map(|(token, data)| (token, (self.measure)(data)))
+            .collect();
+        for (position, (token, value)) in kept.into_iter().enumerate() {
+            self.tree.insert_at(token, value, position);
+        }
+    }
+}
+
+impl<T, A> AggregatedTokenList<T, A> {
+    /// Returns a reference to the item identified by given token, or `None` if the token is
+    /// invalid.
+    pub fn get(&self, token: ItemToken) -> Option<&T> {
+        self.list.get(token)
+    }
+
+    /// Returns the number of items in the list.
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    /// Returns whether the list is empty.
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// Returns an iterator of references to the items, in list order.
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.list.iter()
+    }
+
+    /// Returns an iterator of pairs of (tokens, references to items), in list order.
+    pub fn iter_with_tokens(&self) -> IterWithTokens<'_, T> {
+        self.list.iter_with_tokens()
+    }
+
+    /// Returns a reference to the underlying list.
+    pub fn as_list(&self) -> &GenerationalTokenList<T> {
+        &self.list
+    }
+
+    /// Consume the wrapper and return the underlying list. Tokens remain valid.
+    pub fn into_list(self) -> GenerationalTokenList<T> {
+        self.list
+    }
+}
+
+impl<T, A> std::ops::Index<ItemToken> for AggregatedTokenList<T, A> {
+    type Output = T;
+
+    fn index(&self, token: ItemToken) -> &Self::Output {
+        &self.list[token]
+    }
+}
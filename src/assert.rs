@@ -0,0 +1,78 @@
+// SPDX-License-Identifier: MIT
+
+use std::fmt::{Debug, Write};
+
+/// Assert that the items of a list are equal to the given values, in order.
+///
+/// The first argument can be anything with an `iter` method yielding references to the items,
+/// like a [`GenerationalTokenList`](crate::GenerationalTokenList) or one of its wrappers. The
+/// second one is an array, slice or `Vec` of the expected values. An optional format string and
+/// arguments are added to the panic message.
+///
+/// On failure, the panic message lists the items position by position, marking every position
+/// where the list (`+`) differs from the expected values (`-`).
+///
+/// # Examples
+/// ```
+/// # use generational_token_list::{assert_list_eq, GenerationalTokenList};
+/// let mut list = GenerationalTokenList::new();
+/// let b = list.push_back("b");
+/// list.insert_before(b, "a");
+/// assert_list_eq!(list, ["a", "b"]);
+/// assert_list_eq!(list, vec!["a", "b"], "after inserting {:?}", "a");
+/// ```
+#[macro_export]
+macro_rules! assert_list_eq {
+    ($list:expr, $expected:expr $(,)?) => {
+        $crate::assert_list_eq_impl($list.iter(), &$expected, ::std::option::Option::None)
+    };
+    ($list:expr, $expected:expr, $($arg:tt)+) => {
+        $crate::assert_list_eq_impl(
+            $list.iter(),
+            &$expected,
+            ::std::option::Option::Some(::std::format_args!($($arg)+)),
+        )
+    };
+}
+
+#[doc(hidden)]
+#[track_caller]
+pub fn assert_list_eq_impl<'a, T, E>(
+    actual: impl Iterator<Item = &'a T>,
+    expected: &E,
+    message: Option<std::fmt::Arguments<'_>>,
+) where
+    T: PartialEq + Debug + 'a,
+    E: AsRef<[T]> + ?Sized,
+{
+    let actual: Vec<_> = actual.collect();
+    let expected = expected.as_ref();
+    if actual.len() == expected.len() && actual.iter().zip(expected).all(|(a, e)| *a == e) {
+        return;
+    }
+
+    let mut diff = String::new();
+    for i in 0..actual.len().max(expected.len()) {
+        match (actual.get(i), expected.get(i)) {
+            (Some(a), Some(e)) if *a == e => writeln!(diff, "  {:>3}: {:?}", i, a),
+            (a, e) => {
+                if let Some(e) = e {
+                    writeln!(diff, "- {:>3}: {:?}", i, e).unwrap();
+                }
+                match a {
+                    Some(a) => writeln!(diff, "+ {:>3}: {:?}", i, a),
+                    None => Ok(()),
+                }
+            }
+        }
+        .unwrap();
+    }
+
+    match message {
+        Some(message) => panic!(
+            "list contents differ (-expected +list): {}\n{}",
+            message, diff
+        ),
+        None => panic!("list contents differ (-expected +list):\n{}", diff),
+    }
+}
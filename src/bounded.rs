@@ -0,0 +1,139 @@
+// SPDX-License-Identifier: MIT
+
+use crate::{GenerationalTokenList, ItemToken};
+
+impl<T> GenerationalTokenList<T> {
+    /// Limit the number of items the fallible insertion methods will add to the list to `max_len`,
+    /// or lift the limit with `None`. The limit applies to [`len`](Self::len), so unlinked items
+    /// don't count towards it. It is carried over to clones.
+    ///
+    /// Once the list is full, [`try_push_back`](Self::try_push_back),
+    /// [`try_push_front`](Self::try_push_front), [`try_insert_after`](Self::try_insert_after) and
+    /// [`try_insert_before`](Self::try_insert_before) hand the value back instead of inserting it.
+    /// [`try_push_back_many`](Self::try_push_back_many) and
+    /// [`try_push_front_many`](Self::try_push_front_many) hand the whole batch back unless all of
+    /// it fits. All other insertion methods ignore the limit.
+    ///
+    /// Nothing is ever evicted: lowering the limit below the current number of items only prevents
+    /// insertions until enough items have been removed.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut queue = GenerationalTokenList::new();
+    /// queue.set_max_len(Some(2));
+    /// queue.try_push_back("a").unwrap();
+    /// queue.try_push_back("b").unwrap();
+    /// assert_eq!(queue.try_push_back("c"), Err("c"));
+    ///
+    /// queue.pop_front();
+    /// assert!(queue.try_push_back("c").is_ok());
+    /// ```
+    pub fn set_max_len(&mut self, max_len: Option<usize>) {
+        self.max_len = max_len;
+    }
+
+    /// Returns the limit set by [`set_max_len`](Self::set_max_len), if any.
+    pub fn max_len(&self) -> Option<usize> {
+        self.max_len
+    }
+
+    /// Returns whether the list has reached the limit set by [`set_max_len`](Self::set_max_len).
+    /// Always `false` if there is no limit.
+    pub fn is_full(&self) -> bool {
+        !self.fits(1)
+    }
+
+    /// Append an item to the end of the list. Returns a token which corresponds to the new item, or
+    /// gives `data` back if the list [`is_full`](Self::is_full).
+    pub fn try_push_back(&mut self, data: T) -> Result<ItemToken, T> {
+        if self.is_full() {
+            return Err(data);
+        }
+        Ok(self.push_back(data))
+    }
+
+    /// Prepend an item to the beginning of the list. Returns a token which corresponds to the new
+    /// item, or gives `data` back if the list [`is_full`](Self::is_full).
+    pub fn try_push_front(&mut self, data: T) -> Result<ItemToken, T> {
+        if self.is_full() {
+            return Err(data);
+        }
+        Ok(self.push_front(data))
+    }
+
+    /// Insert an item after the item identified by given token. Returns a token which corresponds
+    /// to the new item, or gives `data` back if the list [`is_full`](Self::is_full).
+    ///
+    /// # Panics
+    /// Panics if `after` is an invalid token or an unlinked item.
+    pub fn try_insert_after(&mut self, after: ItemToken, data: T) -> Result<ItemToken, T> {
+        self.assert_linked(after);
+        if self.is_full() {
+            return Err(data);
+        }
+        Ok(self.insert_after(after, data))
+    }
+
+    /// Insert an item before the item identified by given token. Returns a token which corresponds
+    /// to the new item, or gives `data` back if the list [`is_full`](Self::is_full).
+    ///
+    /// # Panics
+    /// Panics if `before` is an invalid token or an unlinked item.
+    pub fn try_insert_before(&mut self, before: ItemToken, data: T) -> Result<ItemToken, T> {
+        self.assert_linked(before);
+        if self.is_full() {
+            return Err(data);
+        }
+        Ok(self.insert_before(before, data))
+    }
+
+    /// Insert all items from `iter` at the end of the list, like
+    /// [`push_back_many`](Self::push_back_many), if there is room for all of them. Otherwise
+    /// nothing is inserted and the items are handed back, in order.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::new();
+    /// list.set_max_len(Some(3));
+    /// list.push_back(0);
+    /// assert_eq!(list.try_push_back_many(vec![1, 2, 3]), Err(vec![1, 2, 3]));
+    /// assert_eq!(list.len(), 1);
+    ///
+    /// assert!(list.try_push_back_many(vec![1, 2]).is_ok());
+    /// assert!(list.is_full());
+    /// ```
+    pub fn try_push_back_many(
+        &mut self,
+        iter: impl IntoIterator<Item = T>,
+    ) -> Result<Vec<ItemToken>, Vec<T>> {
+        let items: Vec<T> = iter.into_iter().collect();
+        if !self.fits(items.len()) {
+            return Err(items);
+        }
+        Ok(self.push_back_many(items))
+    }
+
+    /// Insert all items from `iter` at the beginning of the list, like
+    /// [`push_front_many`](Self::push_front_many), if there is room for all of them. Otherwise
+    /// nothing is inserted and the items are handed back, in order.
+    pub fn try_push_front_many(
+        &mut self,
+        iter: impl IntoIterator<Item = T>,
+    ) -> Result<Vec<ItemToken>, Vec<T>> {
+        let items: Vec<T> = iter.into_iter().collect();
+        if !self.fits(items.len()) {
+            return Err(items);
+        }
+        Ok(self.push_front_many(items))
+    }
+
+    /// Returns whether `extra` more items fit within the limit.
+    fn fits(&self, extra: usize) -> bool {
+        match self.max_len {
+            Some(max_len) => self.len() + extra <= max_len,
+            None => true,
+        }
+    }
+}
@@ -0,0 +1,77 @@
+// SPDX-License-Identifier: MIT
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::{GenerationalTokenList, ItemToken};
+
+/// Builds a [`GenerationalTokenList`] item by item, attaching labels to items along the way, and
+/// hands out the list together with the tokens of the labeled items.
+///
+/// # Examples
+/// ```
+/// # use generational_token_list::ListBuilder;
+/// let (list, labels) = ListBuilder::new()
+///     .push("fn main() {")
+///     .push("}")
+///     .label("cursor")
+///     .build();
+///
+/// assert_eq!(list.iter().collect::<Vec<_>>(), vec![&"fn main() {", &"}"]);
+/// assert_eq!(list[labels["cursor"]], "}");
+/// ```
+#[derive(Debug)]
+#[must_use = "the list is only available after calling `build`"]
+pub struct ListBuilder<T, L = &'static str> {
+    list: GenerationalTokenList<T>,
+    labels: HashMap<L, ItemToken>,
+}
+
+impl<T, L> Default for ListBuilder<T, L> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, L> ListBuilder<T, L> {
+    /// Creates a new, empty `ListBuilder<T, L>`.
+    pub fn new() -> Self {
+        ListBuilder {
+            list: GenerationalTokenList::new(),
+            labels: HashMap::new(),
+        }
+    }
+
+    /// Append an item.
+    pub fn push(mut self, data: T) -> Self {
+        self.list.push_back(data);
+        self
+    }
+
+    /// Append all items of an iterator.
+    pub fn extend(mut self, iter: impl IntoIterator<Item = T>) -> Self {
+        self.list.push_back_many(iter);
+        self
+    }
+
+    /// Attach `label` to the most recently appended item.
+    ///
+    /// # Panics
+    /// Panics if no item has been appended yet, or if `label` is already in use.
+    pub fn label(mut self, label: L) -> Self
+    where
+        L: Eq + Hash,
+    {
+        let token = self.list.tail_token().expect("no item to label");
+        assert!(
+            self.labels.insert(label, token).is_none(),
+            "label is already in use"
+        );
+        self
+    }
+
+    /// Finish building, and return the list along with the tokens of the labeled items.
+    pub fn build(self) -> (GenerationalTokenList<T>, HashMap<L, ItemToken>) {
+        (self.list, self.labels)
+    }
+}
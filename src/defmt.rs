@@ -0,0 +1,33 @@
+// SPDX-License-Identifier: MIT
+
+use ::defmt::{write, Format, Formatter};
+
+use crate::{GenerationalTokenList, ItemToken};
+
+impl Format for ItemToken {
+    fn format(&self, f: Formatter<'_>) {
+        write!(
+            f,
+            "ItemToken {{ slot: {=usize}, generation: {=u64} }}",
+            self.slot(),
+            self.generation()
+        );
+    }
+}
+
+/// Formats the length followed by the items in list order, e.g. `len=2 [1, 2]`.
+impl<T> Format for GenerationalTokenList<T>
+where
+    T: Format,
+{
+    fn format(&self, f: Formatter<'_>) {
+        write!(f, "len={=usize} [", self.len());
+        for (i, item) in self.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ");
+            }
+            write!(f, "{}", item);
+        }
+        write!(f, "]");
+    }
+}
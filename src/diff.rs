@@ -0,0 +1,175 @@
+// SPDX-License-Identifier: MIT
+
+use crate::{GenerationalTokenList, ItemToken};
+
+/// One step of an edit script produced by [`GenerationalTokenList::diff`].
+///
+/// Applying the steps in order, starting at the front of the old list, produces the new list:
+/// kept items stay where they are, removed items are dropped and inserted values are placed after
+/// whatever came before them in the script.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Edit<V> {
+    /// Keep the item identified by the token.
+    Keep(ItemToken),
+    /// Remove the item identified by the token.
+    Remove(ItemToken),
+    /// Insert a new item.
+    Insert(V),
+}
+
+impl<V> Edit<&V>
+where
+    V: Clone,
+{
+    /// Clone the value of an `Insert`, e.g. to keep the script around after the list it was
+    /// computed from is gone.
+    pub fn cloned(self) -> Edit<V> {
+        match self {
+            Edit::Keep(token) => Edit::Keep(token),
+            Edit::Remove(token) => Edit::Remove(token),
+            Edit::Insert(value) => Edit::Insert(value.clone()),
+        }
+    }
+}
+
+impl<T> GenerationalTokenList<T>
+where
+    T: PartialEq,
+{
+    /// Compute a minimal edit script that turns `self` into `other`. `Keep` and `Remove` steps
+    /// refer to items of `self` by token, `Insert` steps refer to items of `other`.
+    ///
+    /// The script is based on a longest common subsequence, so items that only moved show up as a
+    /// `Remove` and an `Insert`. Runs time and memory proportional to the product of the lengths,
+    /// after skipping any common prefix and suffix.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::{Edit, GenerationalTokenList};
+    /// let mut old = GenerationalTokenList::new();
+    /// let a = old.push_back("a");
+    /// let b = old.push_back("b");
+    /// let c = old.push_back("c");
+    ///
+    /// let new = vec!["a", "x", "c"].into_iter().collect::<GenerationalTokenList<_>>();
+    ///
+    /// assert_eq!(
+    ///     old.diff(&new),
+    ///     vec![Edit::Keep(a), Edit::Remove(b), Edit::Insert(&"x"), Edit::Keep(c)]
+    /// );
+    /// ```
+    pub fn diff<'a>(&self, other: &'a Self) -> Vec<Edit<&'a T>> {
+        let old: Vec<_> = self.iter_with_tokens().collect();
+        let new: Vec<_> = other.iter().collect();
+
+        let prefix = old
+            .iter()
+            .zip(&new)
+            .take_while(|((_, a), b)| a == *b)
+            .count();
+        let suffix = old[prefix..]
+            .iter()
+            .rev()
+            .zip(new[prefix..].iter().rev())
+            .take_while(|((_, a), b)| a == *b)
+            .count();
+        let old_middle = &old[prefix..old.len() - suffix];
+        let new_middle = &new[prefix..new.len() - suffix];
+
+        // lcs[i][j] is the length of the longest common subsequence of old_middle[i..] and
+        // new_middle[j..]
+        let width = new_middle.len() + 1;
+        let mut lcs = vec![0usize; (old_middle.len() + 1) * width];
+        for i in (0..old_middle.len()).rev() {
+            for j in (0..new_middle.len()).rev() {
+                lcs[i * width + j] = if old_middle[i].1 == new_middle[j] {
+                    lcs[(i + 1) * width + j + 1] + 1
+                } else {
+                    lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+                };
+            }
+        }
+
+        let mut edits = Vec::with_capacity(old.len().max(new.len()));
+        edits.extend(old[..prefix].iter().map(|(token, _)| Edit::Keep(*token)));
+
+        let (mut i, mut j) = (0, 0);
+        while i < old_middle.len() || j < new_middle.len() {
+            if i < old_middle.len() && j < new_middle.len() && old_middle[i].1 == new_middle[j] {
+                edits.push(Edit::Keep(old_middle[i].0));
+                i += 1;
+                j += 1;
+            } else if i < old_middle.len()
+                && (j == new_middle.len() || lcs[(i + 1) * width + j] >= lcs[i * width + j + 1])
+            {
+                edits.push(Edit::Remove(old_middle[i].0));
+                i += 1;
+            } else {
+                edits.push(Edit::Insert(new_middle[j]));
+                j += 1;
+            }
+        }
+
+        edits.extend(
+            old[old.len() - suffix..]
+                .iter()
+                .map(|(token, _)| Edit::Keep(*token)),
+        );
+        edits
+    }
+}
+
+impl<T> GenerationalTokenList<T> {
+    /// Apply an edit script, e.g. one produced by [`diff`](Self::diff) and made owned with
+    /// [`Edit::cloned`]. Returns the tokens of the inserted items, in order.
+    ///
+    /// Each inserted value is placed right after the item of the preceding `Keep` or `Insert` step,
+    /// or at the front of the list if there is none. Items not mentioned by the script are left
+    /// alone.
+    ///
+    /// # Panics
+    /// Panics if a `Keep` or `Remove` step has an invalid token.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::{Edit, GenerationalTokenList};
+    /// let mut server = GenerationalTokenList::new();
+    /// server.push_back(1);
+    /// server.push_back(2);
+    /// let mut client = server.clone();
+    ///
+    /// server.pop_front();
+    /// server.push_back(3);
+    ///
+    /// let script = client.diff(&server).into_iter().map(Edit::cloned).collect::<Vec<_>>();
+    /// let inserted = client.apply_patch(script);
+    /// assert_eq!(client.iter().collect::<Vec<_>>(), vec![&2, &3]);
+    /// assert_eq!(client.get(inserted[0]), Some(&3));
+    /// ```
+    pub fn apply_patch(&mut self, edits: impl IntoIterator<Item = Edit<T>>) -> Vec<ItemToken> {
+        let mut inserted = Vec::new();
+        let mut last = None;
+
+        for edit in edits {
+            match edit {
+                Edit::Keep(token) => {
+                    assert!(self.get(token).is_some(), "invalid token");
+                    last = Some(token);
+                }
+                Edit::Remove(token) => {
+                    self.remove(token).expect("invalid token");
+                }
+                Edit::Insert(value) => {
+                    let token = match last {
+                        Some(after) => self.insert_after(after, value),
+                        None => self.push_front(value),
+                    };
+                    inserted.push(token);
+                    last = Some(token);
+                }
+            }
+        }
+
+        inserted
+    }
+}
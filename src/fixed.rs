@@ -0,0 +1,347 @@
+// SPDX-License-Identifier: MIT
+
+use crate::ItemToken;
+
+/// A doubly linked list with room for at most `N` items, stored inline.
+///
+/// This works like [`GenerationalTokenList`](crate::GenerationalTokenList), but never touches the
+/// heap: all storage is part of the value itself, and insertions return the value back in `Err`
+/// instead of growing when the list is full. Tokens behave the same way as for the growable list,
+/// i.e. a token is invalidated when its item is removed, and is never valid for an item inserted
+/// later into the same slot.
+///
+/// # Examples
+/// ```
+/// # use generational_token_list::FixedTokenList;
+/// let mut list = FixedTokenList::<_, 2>::new();
+/// let a = list.try_push_back("a").unwrap();
+/// list.try_push_front("b").unwrap();
+/// assert_eq!(list.try_push_back("c"), Err("c"));
+///
+/// assert_eq!(list.remove(a), Some("a"));
+/// assert!(list.try_push_back("c").is_ok());
+/// assert_eq!(list.iter().collect::<Vec<_>>(), vec![&"b", &"c"]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct FixedTokenList<T, const N: usize> {
+    entries: [Entry<T>; N],
+    free_head: Option<usize>,
+    head: Option<usize>,
+    tail: Option<usize>,
+    len: usize,
+    /// Generation given to newly inserted items, incremented on every removal.
+    generation: u64,
+}
+
+#[derive(Clone, Debug)]
+enum Entry<T> {
+    Free {
+        next_free: Option<usize>,
+    },
+    Occupied {
+        generation: u64,
+        data: T,
+        previous: Option<usize>,
+        next: Option<usize>,
+    },
+}
+
+impl<T, const N: usize> Default for FixedTokenList<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> FixedTokenList<T, N> {
+    /// Creates a new, empty `FixedTokenList<T, N>`.
+    pub fn new() -> Self {
+        FixedTokenList {
+            entries: std::array::from_fn(|slot| Entry::Free {
+                next_free: Some(slot + 1).filter(|&next| next < N),
+            }),
+            free_head: Some(0).filter(|_| N > 0),
+            head: None,
+            tail: None,
+            len: 0,
+            generation: 0,
+        }
+    }
+
+    /// Returns the maximum number of items the list can hold, i.e. `N`.
+    pub fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Returns the number of items in the list.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether the list is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns whether the list holds `N` items, so that insertions will fail.
+    pub fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    /// Returns a reference to the first item in the list, or `None` if list is empty.
+    pub fn head(&self) -> Option<&T> {
+        self.head.map(|slot| self.data(slot))
+    }
+
+    /// Returns a reference to the last item in the list, or `None` if list is empty.
+    pub fn tail(&self) -> Option<&T> {
+        self.tail.map(|slot| self.data(slot))
+    }
+
+    /// Returns the token of the first item in the list, or `None` if list is empty.
+    pub fn head_token(&self) -> Option<ItemToken> {
+        self.head.map(|slot| self.token(slot))
+    }
+
+    /// Returns the token of the last item in the list, or `None` if list is empty.
+    pub fn tail_token(&self) -> Option<ItemToken> {
+        self.tail.map(|slot| self.token(slot))
+    }
+
+    /// Returns a reference to the item identified by given token, or `None` if the token is invalid.
+    pub fn get(&self, token: ItemToken) -> Option<&T> {
+        self.slot_of(token).map(|slot| self.data(slot))
+    }
+
+    /// Returns a mutable reference to the item identified by given token, or `None` if the token is
+    /// invalid.
+    pub fn get_mut(&mut self, token: ItemToken) -> Option<&mut T> {
+        let slot = self.slot_of(token)?;
+        match &mut self.entries[slot] {
+            Entry::Occupied { data, .. } => Some(data),
+            Entry::Free { .. } => unreachable!(),
+        }
+    }
+
+    /// Returns the token of the item after the one identified by given token, or `None` if the
+    /// token is invalid or identifies the last item.
+    pub fn next_token(&self, token: ItemToken) -> Option<ItemToken> {
+        let next = self.links(self.slot_of(token)?).1?;
+        Some(self.token(next))
+    }
+
+    /// Returns the token of the item before the one identified by given token, or `None` if the
+    /// token is invalid or identifies the first item.
+    pub fn prev_token(&self, token: ItemToken) -> Option<ItemToken> {
+        let previous = self.links(self.slot_of(token)?).0?;
+        Some(self.token(previous))
+    }
+
+    /// Append an item to the end of the list. Returns a token which corresponds to the new item, or
+    /// gives `data` back if the list is full.
+    pub fn try_push_back(&mut self, data: T) -> Result<ItemToken, T> {
+        let slot = self.allocate(data)?;
+        self.link(slot, self.tail, None);
+        Ok(self.token(slot))
+    }
+
+    /// Prepend an item to the beginning of the list. Returns a token which corresponds to the new
+    /// item, or gives `data` back if the list is full.
+    pub fn try_push_front(&mut self, data: T) -> Result<ItemToken, T> {
+        let slot = self.allocate(data)?;
+        self.link(slot, None, self.head);
+        Ok(self.token(slot))
+    }
+
+    /// Insert an item after the item identified by given token. Returns a token which corresponds to
+    /// the new item, or gives `data` back if the list is full.
+    ///
+    /// # Panics
+    /// Panics if `after` is an invalid token.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::FixedTokenList;
+    /// let mut list = FixedTokenList::<_, 3>::new();
+    /// let one = list.try_push_back(1).unwrap();
+    /// list.try_push_back(3).unwrap();
+    /// list.try_insert_after(one, 2).unwrap();
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), vec![&1, &2, &3]);
+    /// assert_eq!(list.try_insert_after(one, 4), Err(4));
+    /// ```
+    pub fn try_insert_after(&mut self, after: ItemToken, data: T) -> Result<ItemToken, T> {
+        let previous = self.slot_of(after).expect("invalid token");
+        let slot = self.allocate(data)?;
+        self.link(slot, Some(previous), self.links(previous).1);
+        Ok(self.token(slot))
+    }
+
+    /// Insert an item before the item identified by given token. Returns a token which corresponds
+    /// to the new item, or gives `data` back if the list is full.
+    ///
+    /// # Panics
+    /// Panics if `before` is an invalid token.
+    pub fn try_insert_before(&mut self, before: ItemToken, data: T) -> Result<ItemToken, T> {
+        let next = self.slot_of(before).expect("invalid token");
+        let slot = self.allocate(data)?;
+        self.link(slot, self.links(next).0, Some(next));
+        Ok(self.token(slot))
+    }
+
+    /// Remove the item identified by given token from the list and return the item. Invalidates the
+    /// token. Returns `None` if token is invalid.
+    pub fn remove(&mut self, token: ItemToken) -> Option<T> {
+        let slot = self.slot_of(token)?;
+        let entry = std::mem::replace(
+            &mut self.entries[slot],
+            Entry::Free {
+                next_free: self.free_head,
+            },
+        );
+        let (data, previous, next) = match entry {
+            Entry::Occupied {
+                data,
+                previous,
+                next,
+                ..
+            } => (data, previous, next),
+            Entry::Free { .. } => unreachable!(),
+        };
+
+        match previous {
+            Some(previous) => self.set_next(previous, next),
+            None => self.head = next,
+        }
+        match next {
+            Some(next) => self.set_previous(next, previous),
+            None => self.tail = previous,
+        }
+
+        self.free_head = Some(slot);
+        self.generation += 1;
+        self.len -= 1;
+        Some(data)
+    }
+
+    /// Remove the first item from the list and return it, or `None` if list is empty.
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.remove(self.head_token()?)
+    }
+
+    /// Remove the last item from the list and return it, or `None` if list is empty.
+    pub fn pop_back(&mut self) -> Option<T> {
+        self.remove(self.tail_token()?)
+    }
+
+    /// Remove all items from the list. Invalidates all tokens.
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+
+    /// Returns an iterator of references to the items, in list order.
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        self.iter_with_tokens().map(|(_, data)| data)
+    }
+
+    /// Returns an iterator of pairs of (tokens, references to items), in list order.
+    pub fn iter_with_tokens(&self) -> impl Iterator<Item = (ItemToken, &T)> + '_ {
+        let mut current = self.head;
+        std::iter::from_fn(move || {
+            let slot = current?;
+            current = self.links(slot).1;
+            Some((self.token(slot), self.data(slot)))
+        })
+    }
+
+    fn allocate(&mut self, data: T) -> Result<usize, T> {
+        let slot = match self.free_head {
+            Some(slot) => slot,
+            None => return Err(data),
+        };
+
+        self.free_head = match self.entries[slot] {
+            Entry::Free { next_free } => next_free,
+            Entry::Occupied { .. } => unreachable!(),
+        };
+        self.entries[slot] = Entry::Occupied {
+            generation: self.generation,
+            data,
+            previous: None,
+            next: None,
+        };
+        self.len += 1;
+        Ok(slot)
+    }
+
+    /// Link the item at `slot` in between `previous` and `next`, which must be adjacent.
+    fn link(&mut self, slot: usize, previous: Option<usize>, next: Option<usize>) {
+        self.set_previous(slot, previous);
+        self.set_next(slot, next);
+
+        match previous {
+            Some(previous) => self.set_next(previous, Some(slot)),
+            None => self.head = Some(slot),
+        }
+        match next {
+            Some(next) => self.set_previous(next, Some(slot)),
+            None => self.tail = Some(slot),
+        }
+    }
+
+    fn slot_of(&self, token: ItemToken) -> Option<usize> {
+        match self.entries.get(token.slot()) {
+            Some(Entry::Occupied { generation, .. }) if *generation == token.generation() => {
+                Some(token.slot())
+            }
+            _ => None,
+        }
+    }
+
+    fn token(&self, slot: usize) -> ItemToken {
+        match &self.entries[slot] {
+            Entry::Occupied { generation, .. } => {
+                ItemToken::from_slot_and_generation(slot, *generation)
+            }
+            Entry::Free { .. } => unreachable!(),
+        }
+    }
+
+    fn data(&self, slot: usize) -> &T {
+        match &self.entries[slot] {
+            Entry::Occupied { data, .. } => data,
+            Entry::Free { .. } => unreachable!(),
+        }
+    }
+
+    fn links(&self, slot: usize) -> (Option<usize>, Option<usize>) {
+        match &self.entries[slot] {
+            Entry::Occupied { previous, next, .. } => (*previous, *next),
+            Entry::Free { .. } => unreachable!(),
+        }
+    }
+
+    fn set_previous(&mut self, slot: usize, value: Option<usize>) {
+        if let Entry::Occupied { previous, .. } = &mut self.entries[slot] {
+            *previous = value;
+        }
+    }
+
+    fn set_next(&mut self, slot: usize, value: Option<usize>) {
+        if let Entry::Occupied { next, .. } = &mut self.entries[slot] {
+            *next = value;
+        }
+    }
+}
+
+impl<T, const N: usize> std::ops::Index<ItemToken> for FixedTokenList<T, N> {
+    type Output = T;
+
+    fn index(&self, token: ItemToken) -> &Self::Output {
+        self.get(token).unwrap()
+    }
+}
+
+impl<T, const N: usize> std::ops::IndexMut<ItemToken> for FixedTokenList<T, N> {
+    fn index_mut(&mut self, token: ItemToken) -> &mut Self::Output {
+        self.get_mut(token).unwrap()
+    }
+}
@@ -0,0 +1,84 @@
+// SPDX-License-Identifier: MIT
+
+use std::collections::BTreeMap;
+
+use crate::GenerationalTokenList;
+
+/// How the items of a [`GenerationalTokenList`] are laid out in its storage, as returned by
+/// [`GenerationalTokenList::fragmentation`].
+///
+/// Removing an item leaves a hole in the storage, which is filled again by a later insertion. A
+/// list that shrank a lot without refilling has its items scattered over a large range of slots,
+/// which wastes memory and makes traversals touch more cache lines than necessary.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Fragmentation {
+    /// Total number of slots, i.e. the [`capacity`](GenerationalTokenList::capacity).
+    pub capacity: usize,
+    /// Number of occupied slots, including unlinked items.
+    pub occupied: usize,
+    /// Number of free slots after the last occupied one. These are spare capacity rather than
+    /// holes, and are not counted in `gaps`.
+    pub trailing_free: usize,
+    /// Length of the longest run of free slots in `gaps`, or 0 if there are no holes.
+    pub longest_gap: usize,
+    /// Histogram of the holes: maps the length of a run of free slots before the last occupied
+    /// slot to the number of such runs.
+    pub gaps: BTreeMap<usize, usize>,
+}
+
+impl Fragmentation {
+    /// Returns the fraction of slots up to the last occupied one that are occupied, between 0
+    /// (exclusive) and 1. A list without holes has a density of 1, and so does an empty list.
+    pub fn density(&self) -> f64 {
+        let span = self.capacity - self.trailing_free;
+        if span == 0 {
+            return 1.0;
+        }
+        self.occupied as f64 / span as f64
+    }
+}
+
+impl<T> GenerationalTokenList<T> {
+    /// Returns how the items are laid out in the list's storage: how many slots are free between
+    /// them and how long those runs of free slots are. Use this to decide whether rebuilding the
+    /// list (e.g. by collecting its items into a new one) is worth it.
+    ///
+    /// This takes time proportional to the capacity of the list.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::with_capacity(8);
+    /// let tokens = list.push_back_many(0..6);
+    /// list.remove(tokens[1]);
+    /// list.remove(tokens[3]);
+    /// list.remove(tokens[4]);
+    ///
+    /// let report = list.fragmentation();
+    /// assert_eq!(report.occupied, 3);
+    /// assert_eq!(report.trailing_free, 2);
+    /// assert_eq!(report.longest_gap, 2);
+    /// assert_eq!(report.gaps.into_iter().collect::<Vec<_>>(), vec![(1, 1), (2, 1)]);
+    /// assert_eq!(list.fragmentation().density(), 0.5);
+    /// ```
+    pub fn fragmentation(&self) -> Fragmentation {
+        let mut report = Fragmentation {
+            capacity: self.arena.capacity(),
+            occupied: self.arena.len(),
+            ..Default::default()
+        };
+
+        let mut next_slot = 0;
+        for (index, _) in self.arena.iter() {
+            let slot = index.into_raw_parts().0;
+            let gap = slot - next_slot;
+            if gap > 0 {
+                *report.gaps.entry(gap).or_insert(0) += 1;
+                report.longest_gap = report.longest_gap.max(gap);
+            }
+            next_slot = slot + 1;
+        }
+        report.trailing_free = report.capacity - next_slot;
+        report
+    }
+}
@@ -0,0 +1,143 @@
+// SPDX-License-Identifier: MIT
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::{GenerationalTokenList, ItemToken};
+
+/// The guards of a list, shared between the list and its guards.
+#[derive(Debug, Default)]
+pub(crate) struct GuardTable {
+    /// Number of guarded tokens, so that checks can skip the lock while nothing is guarded.
+    guarded: AtomicUsize,
+    /// Number of live guards per token.
+    counts: Mutex<HashMap<ItemToken, usize>>,
+}
+
+impl GuardTable {
+    fn lock(&self) -> MutexGuard<'_, HashMap<ItemToken, usize>> {
+        self.counts
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn add(&self, token: ItemToken) {
+        let mut counts = self.lock();
+        let count = counts.entry(token).or_insert(0);
+        if *count == 0 {
+            self.guarded.fetch_add(1, Ordering::Release);
+        }
+        *count += 1;
+    }
+
+    fn release(&self, token: ItemToken) {
+        let mut counts = self.lock();
+        let count = counts.get_mut(&token).unwrap();
+        *count -= 1;
+        if *count == 0 {
+            counts.remove(&token);
+            self.guarded.fetch_sub(1, Ordering::Release);
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.guarded.load(Ordering::Acquire) == 0
+    }
+
+    fn contains(&self, token: ItemToken) -> bool {
+        !self.is_empty() && self.lock().contains_key(&token)
+    }
+}
+
+/// Keeps an item from being removed from its list for as long as the guard is alive. Created by
+/// [`GenerationalTokenList::guard`].
+///
+/// The guard does not borrow the list, so it can be handed to other threads or tasks together with
+/// the token while the list is still mutated elsewhere.
+#[derive(Debug)]
+#[must_use]
+pub struct RemovalGuard {
+    guards: Arc<GuardTable>,
+    token: ItemToken,
+}
+
+impl RemovalGuard {
+    /// Returns the token of the guarded item.
+    pub fn token(&self) -> ItemToken {
+        self.token
+    }
+}
+
+impl Clone for RemovalGuard {
+    fn clone(&self) -> Self {
+        self.guards.add(self.token);
+        RemovalGuard {
+            guards: self.guards.clone(),
+            token: self.token,
+        }
+    }
+}
+
+impl Drop for RemovalGuard {
+    fn drop(&mut self) {
+        self.guards.release(self.token);
+    }
+}
+
+impl<T> GenerationalTokenList<T> {
+    /// Protect the item identified by given token from removal until the returned guard (and all
+    /// its clones) are dropped.
+    ///
+    /// While an item is guarded, [`remove`](Self::remove) leaves it in place and returns `None`.
+    /// Removal from the ends stops at it rather than reach past it: [`pop_front`](Self::pop_front)
+    /// returns `None` while the head is guarded, and [`pop_front_n`](Self::pop_front_n) or
+    /// [`drain_while_front`](Self::drain_while_front) stop there. Operations that remove items
+    /// wherever they are, like [`clear`](Self::clear) or [`retain_range`](Self::retain_range),
+    /// keep it and remove the others. Splitting the list with [`split_into`](Self::split_into) or
+    /// [`split_when`](Self::split_when) panics.
+    /// Guards are not carried over to clones of the list.
+    ///
+    /// # Panics
+    /// Panics if `token` is invalid.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::new();
+    /// let busy = list.push_back("busy");
+    /// list.push_back("idle");
+    ///
+    /// let guard = list.guard(busy);
+    /// assert_eq!(list.remove(busy), None);
+    /// list.clear();
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), vec![&"busy"]);
+    ///
+    /// drop(guard);
+    /// assert_eq!(list.remove(busy), Some("busy"));
+    /// ```
+    pub fn guard(&mut self, token: ItemToken) -> RemovalGuard {
+        assert!(self.arena.contains(token.index), "invalid token");
+
+        let guards = self.guards.get_or_insert_with(Default::default).clone();
+        guards.add(token);
+        RemovalGuard { guards, token }
+    }
+
+    /// Returns `true` if the item identified by given token is protected by a
+    /// [`RemovalGuard`].
+    pub fn is_guarded(&self, token: ItemToken) -> bool {
+        match &self.guards {
+            Some(guards) => guards.contains(token),
+            None => false,
+        }
+    }
+
+    /// Returns `true` if any item is currently protected by a [`RemovalGuard`].
+    pub(crate) fn has_guards(&self) -> bool {
+        match &self.guards {
+            Some(guards) => !guards.is_empty(),
+            None => false,
+        }
+    }
+}
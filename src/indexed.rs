@@ -0,0 +1,267 @@
+// SPDX-License-Identifier: MIT
+
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hash};
+use std::iter::FromIterator;
+
+use crate::{GenerationalTokenList, ItemToken, Iter, IterWithTokens};
+
+/// A wrapper around [`GenerationalTokenList`] that keeps an index of its items by value, so that
+/// [`find_token`](Self::find_token), [`contains`](Self::contains) and
+/// [`count_of`](Self::count_of) take O(1) amortized time instead of a linear scan.
+///
+/// Items can't be mutated in place, as that would get them out of sync with the index; use
+/// [`replace`](Self::replace) instead. The index only stores hashes and tokens, so `T` doesn't need
+/// to be `Clone`.
+///
+/// # Examples
+/// ```
+/// # use generational_token_list::IndexedTokenList;
+/// let mut list = IndexedTokenList::new();
+/// list.push_back("a");
+/// let b = list.push_back("b");
+/// assert_eq!(list.find_token(&"b"), Some(b));
+///
+/// list.remove(b);
+/// assert!(!list.contains(&"b"));
+/// ```
+#[derive(Clone, Debug)]
+pub struct IndexedTokenList<T> {
+    list: GenerationalTokenList<T>,
+    /// Tokens of the items with a given hash, in insertion order.
+    index: HashMap<u64, Vec<ItemToken>>,
+    hasher: RandomState,
+}
+
+impl<T> Default for IndexedTokenList<T>
+where
+    T: Hash + Eq,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> IndexedTokenList<T>
+where
+    T: Hash + Eq,
+{
+    /// Creates a new, empty `IndexedTokenList<T>`.
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Creates a new `IndexedTokenList<T>` with given capacity. The first `n` insertions are
+    /// guaranteed not to allocate in the list; the index may still allocate.
+    pub fn with_capacity(n: usize) -> Self {
+        IndexedTokenList {
+            list: GenerationalTokenList::with_capacity(n),
+            index: HashMap::with_capacity(n),
+            hasher: RandomState::new(),
+        }
+    }
+
+    /// Append an item to the end of the list. Returns a token which corresponds to the new item.
+    pub fn push_back(&mut self, value: T) -> ItemToken {
+        let hash = self.hasher.hash_one(&value);
+        let token = self.list.push_back(value);
+        self.index.entry(hash).or_default().push(token);
+        token
+    }
+
+    /// Prepend an item to the beginning of the list. Returns a token which corresponds to the new
+    /// item.
+    pub fn push_front(&mut self, value: T) -> ItemToken {
+        let hash = self.hasher.hash_one(&value);
+        let token = self.list.push_front(value);
+        self.index.entry(hash).or_default().push(token);
+        token
+    }
+
+    /// Insert an item after the item identified by given token. Returns a token which corresponds
+    /// to the new item.
+    ///
+    /// # Panics
+    /// Panics if `after` is invalid.
+    pub fn insert_after(&mut self, after: ItemToken, value: T) -> ItemToken {
+        let hash = self.hasher.hash_one(&value);
+        let token = self.list.insert_after(after, value);
+        self.index.entry(hash).or_default().push(token);
+        token
+    }
+
+    /// Insert an item before the item identified by given token. Returns a token which corresponds
+    /// to the new item.
+    ///
+    /// # Panics
+    /// Panics if `before` is invalid.
+    pub fn insert_before(&mut self, before: ItemToken, value: T) -> ItemToken {
+        let hash = self.hasher.hash_one(&value);
+        let token = self.list.insert_before(before, value);
+        self.index.entry(hash).or_default().push(token);
+        token
+    }
+
+    /// Remove the item identified by given token and return it, or `None` if the token is invalid
+    /// (or the item is protected by a [`RemovalGuard`](crate::RemovalGuard)).
+    pub fn remove(&mut self, token: ItemToken) -> Option<T> {
+        let value = self.list.remove(token)?;
+        self.forget(self.hasher.hash_one(&value), token);
+        Some(value)
+    }
+
+    /// Remove and return the first item, if any.
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.remove(self.list.head_token()?)
+    }
+
+    /// Remove and return the last item, if any.
+    pub fn pop_back(&mut self) -> Option<T> {
+        self.remove(self.list.tail_token()?)
+    }
+
+    /// Replace the item identified by given token with `value`, keeping its token and position.
+    /// Returns the old value, or gives `value` back if the token is invalid.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::IndexedTokenList;
+    /// let mut list = IndexedTokenList::new();
+    /// let token = list.push_back(1);
+    /// assert_eq!(list.replace(token, 2), Ok(1));
+    /// assert_eq!(list.find_token(&2), Some(token));
+    /// assert!(!list.contains(&1));
+    /// ```
+    pub fn replace(&mut self, token: ItemToken, value: T) -> Result<T, T> {
+        let hash = self.hasher.hash_one(&value);
+        let old = match self.list.get_mut(token) {
+            Some(data) => std::mem::replace(data, value),
+            None => return Err(value),
+        };
+        self.forget(self.hasher.hash_one(&old), token);
+        self.index.entry(hash).or_default().push(token);
+        Ok(old)
+    }
+
+    /// Returns the token of an item equal to `value`, or `None` if there is none. If there are
+    /// several, this is the one that was inserted first.
+    pub fn find_token(&self, value: &T) -> Option<ItemToken> {
+        self.tokens_of(value).next()
+    }
+
+    /// Returns `true` if the list contains an item equal to `value`.
+    pub fn contains(&self, value: &T) -> bool {
+        self.find_token(value).is_some()
+    }
+
+    /// Returns the number of items equal to `value`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::IndexedTokenList;
+    /// let list = vec!["a", "b", "a"].into_iter().collect::<IndexedTokenList<_>>();
+    /// assert_eq!(list.count_of(&"a"), 2);
+    /// assert_eq!(list.count_of(&"c"), 0);
+    /// ```
+    pub fn count_of(&self, value: &T) -> usize {
+        self.tokens_of(value).count()
+    }
+
+    /// Remove all items. Invalidates all tokens, except for items protected by a
+    /// [`RemovalGuard`](crate::RemovalGuard), which are kept.
+    pub fn clear(&mut self) {
+        self.list.clear();
+        let list = &self.list;
+        self.index.retain(|_, tokens| {
+            tokens.retain(|&token| list.get(token).is_some());
+            !tokens.is_empty()
+        });
+    }
+
+    fn tokens_of<'a>(&'a self, value: &'a T) -> impl Iterator<Item = ItemToken> + 'a {
+        let hash = self.hasher.hash_one(value);
+        self.index
+            .get(&hash)
+            .into_iter()
+            .flatten()
+            .copied()
+            .filter(move |&token| &self.list[token] == value)
+    }
+
+    fn forget(&mut self, hash: u64, token: ItemToken) {
+        let tokens = self.index.get_mut(&hash).unwrap();
+        tokens.retain(|&other| other != token);
+        if tokens.is_empty() {
+            self.index.remove(&hash);
+        }
+    }
+}
+
+impl<T> IndexedTokenList<T> {
+    /// Returns a reference to the item identified by given token, or `None` if the token is
+    /// invalid.
+    pub fn get(&self, token: ItemToken) -> Option<&T> {
+        self.list.get(token)
+    }
+
+    /// Returns the number of items in the list.
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    /// Returns whether the list is empty.
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// Returns an iterator of references to the items, in list order.
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.list.iter()
+    }
+
+    /// Returns an iterator of pairs of (tokens, references to items), in list order.
+    pub fn iter_with_tokens(&self) -> IterWithTokens<'_, T> {
+        self.list.iter_with_tokens()
+    }
+
+    /// Returns a reference to the underlying list.
+    pub fn as_list(&self) -> &GenerationalTokenList<T> {
+        &self.list
+    }
+
+    /// Consume the wrapper and return the underlying list. Tokens remain valid.
+    pub fn into_list(self) -> GenerationalTokenList<T> {
+        self.list
+    }
+}
+
+impl<T> FromIterator<T> for IndexedTokenList<T>
+where
+    T: Hash + Eq,
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T> Extend<T> for IndexedTokenList<T>
+where
+    T: Hash + Eq,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push_back(value);
+        }
+    }
+}
+
+impl<T> std::ops::Index<ItemToken> for IndexedTokenList<T> {
+    type Output = T;
+
+    fn index(&self, token: ItemToken) -> &Self::Output {
+        &self.list[token]
+    }
+}
@@ -3,9 +3,120 @@
 #![cfg_attr(not(feature = "iter-mut"), forbid(unsafe_code))]
 #![cfg_attr(feature = "iter-mut", deny(unsafe_code))]
 
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashSet, LinkedList, VecDeque};
+use std::fmt::{Display, Write};
+use std::hash::{Hash, Hasher};
+use std::iter::FromIterator;
+use std::ops::{ControlFlow, RangeInclusive};
+use std::pin::Pin;
+use std::sync::Arc;
+
 use generational_arena::{Arena, Index};
+use guard::GuardTable;
+use notify::Notifier;
 
-#[derive(Debug)]
+/// Emit a `tracing` event at trace level, if the `tracing` feature is enabled.
+macro_rules! trace_event {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::trace!($($arg)*);
+    };
+}
+
+/// Check the list's internal consistency after a structural change, if the `debug-checks` feature
+/// is enabled.
+macro_rules! debug_check {
+    ($list:expr) => {
+        #[cfg(feature = "debug-checks")]
+        $list.assert_invariants();
+    };
+}
+
+/// Enter a `tracing` span at trace level for the rest of the enclosing block, if the `tracing`
+/// feature is enabled.
+macro_rules! trace_span {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!($($arg)*).entered();
+    };
+}
+
+mod aggregated;
+mod assert;
+mod bounded;
+mod builder;
+#[cfg(feature = "defmt")]
+mod defmt;
+mod diff;
+mod fixed;
+mod fragmentation;
+mod guard;
+mod indexed;
+mod left_right;
+#[cfg(feature = "metrics")]
+mod metrics;
+mod notify;
+mod playback;
+#[cfg(feature = "quickcheck")]
+mod quickcheck;
+#[cfg(feature = "rand")]
+mod random;
+mod raw;
+mod rc_token;
+mod recycle;
+mod replicated;
+mod saved_position;
+mod sealed;
+mod secondary_map;
+mod sorted;
+#[cfg(feature = "futures")]
+mod stream;
+mod timer;
+mod token_set;
+mod token_string;
+#[cfg(feature = "debug-checks")]
+mod tombstones;
+mod typed;
+mod walk;
+#[cfg(feature = "wasm")]
+mod wasm;
+
+pub use aggregated::AggregatedTokenList;
+#[doc(hidden)]
+pub use assert::assert_list_eq_impl;
+pub use builder::ListBuilder;
+pub use diff::Edit;
+pub use fixed::FixedTokenList;
+pub use fragmentation::Fragmentation;
+pub use guard::RemovalGuard;
+pub use indexed::IndexedTokenList;
+pub use left_right::{ReadHandle, WriteHandle};
+#[cfg(feature = "metrics")]
+pub use metrics::ListMetrics;
+pub use notify::{ChangeSubscription, Changed};
+pub use playback::{PlaybackCursor, PlaybackMode};
+pub use rc_token::{ArcToken, RcToken};
+pub use replicated::{OpId, ReplicatedList, ReplicatedOp};
+pub use saved_position::{Bias, SavedPosition};
+pub use sealed::SealedTokenList;
+pub use secondary_map::SecondaryMap;
+pub use sorted::SortedTokenList;
+#[cfg(feature = "futures")]
+pub use stream::{IntoStream, PopFrontStream};
+pub use timer::{PopExpired, TimerList};
+pub use token_set::TokenSet;
+pub use token_string::ParseTokenError;
+#[cfg(feature = "debug-checks")]
+pub use tombstones::TokenStatus;
+pub use typed::TypedTokenList;
+pub use walk::TokenWalk;
+#[cfg(feature = "wasm")]
+pub use wasm::JsTokenList;
+
+#[derive(Clone, Debug)]
 #[cfg_attr(test, derive(PartialEq, Eq))]
 struct Item<T> {
     data: T,
@@ -78,19 +189,127 @@ struct Item<T> {
 /// // You can confirm that item1 != item1_new
 /// assert_ne!(item1, item1_new);
 /// ```
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+///
+/// # Ordering
+/// Tokens don't implement `PartialOrd`/`Ord` by default, since the only ordering available without
+/// the list (by arena slot) has nothing to do with the order of the items in the list. Use
+/// [`GenerationalTokenList::compare_tokens`] or
+/// [`GenerationalTokenList::sort_tokens_by_position`] to order tokens by position. If you need
+/// tokens as keys of ordered collections like `BTreeMap`, enable the `token-ord` feature to get the
+/// slot-based ordering.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "token-ord", derive(PartialOrd, Ord))]
 pub struct ItemToken {
     index: Index,
 }
 
+impl ItemToken {
+    fn from_slot_and_generation(slot: usize, generation: u64) -> Self {
+        ItemToken {
+            index: Index::from_raw_parts(slot, generation),
+        }
+    }
+
+    /// Returns the arena slot this token points to, ignoring the generation.
+    fn slot(self) -> usize {
+        self.index.into_raw_parts().0
+    }
+
+    fn generation(self) -> u64 {
+        self.index.into_raw_parts().1
+    }
+}
+
+/// Where an item is in its list, as yielded by [`GenerationalTokenList::with_position`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Position {
+    /// The first of several items.
+    First,
+    /// Neither the first nor the last item.
+    Middle,
+    /// The last of several items.
+    Last,
+    /// The only item.
+    Only,
+}
+
+/// A run of consecutive items that has been detached from a list with
+/// [`GenerationalTokenList::detach_range`].
+///
+/// The items themselves stay in the list's storage (so their tokens remain valid); the segment only
+/// remembers their order so they can be reattached in one piece.
+#[derive(Debug, PartialEq, Eq)]
+#[must_use = "the detached items stay unlinked unless the segment is attached again"]
+pub struct DetachedSegment {
+    tokens: Vec<ItemToken>,
+}
+
+impl DetachedSegment {
+    /// Returns the tokens of the items in the segment, in order.
+    pub fn tokens(&self) -> &[ItemToken] {
+        &self.tokens
+    }
+
+    /// Returns the number of items in the segment.
+    pub fn len(&self) -> usize {
+        self.tokens.len()
+    }
+
+    /// Returns whether the segment is empty. Segments returned by `detach_range` never are.
+    pub fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+}
+
 /// A doubly linked list, backed by [generational-arena](https://github.com/fitzgen/generational-arena).
 ///
 /// See the crate documentation for more.
+///
+/// Items are stored inline in the arena, which moves them when it grows, so a `&T` obtained from
+/// the list must not be assumed to point to the same address later on. For data that has to stay
+/// put (e.g. `!Unpin` futures), store it as `Pin<Box<U>>`; see
+/// [`push_back_pinned`](GenerationalTokenList::push_back_pinned).
+///
+/// # Determinism
+/// Token allocation is fully deterministic: freed slots are reused in a fixed (last freed, first
+/// reused) order and the generation counter only depends on the number of removals. Two lists that
+/// are created the same way (`new` or `with_capacity` with the same capacity) and then go through
+/// the same sequence of operations hand out identical tokens, on any machine. A clone continues
+/// exactly like the original.
+///
+/// # Drop order
+/// When the list is dropped, or [`clear`](GenerationalTokenList::clear)ed, its items are dropped in
+/// the order of their arena slots, which generally differs from list order. To tear a list down
+/// in list order, use [`clear_with`](GenerationalTokenList::clear_with).
 #[derive(Debug)]
 pub struct GenerationalTokenList<T> {
     arena: Arena<Item<T>>,
     head: Option<ItemToken>,
     tail: Option<ItemToken>,
+    /// Number of items that are in the arena but currently not linked into the list.
+    unlinked_len: usize,
+    /// Marks set by [`mark`](Self::mark), consumed by [`sweep_unmarked`](Self::sweep_unmarked).
+    marks: TokenSet,
+    /// Mirror of the arena's generation counter, which the arena doesn't expose.
+    generation: u64,
+    /// Live [`RemovalGuard`]s, created on first use.
+    guards: Option<Arc<GuardTable>>,
+    /// Shared state of [`ChangeSubscription`]s, created on first use.
+    notifier: Option<Notifier>,
+    /// Values parked by [`remove_recycled`](Self::remove_recycled).
+    recycled: Vec<T>,
+    /// Limit set by [`set_max_len`](Self::set_max_len).
+    max_len: Option<usize>,
+    /// Live [`SavedPosition`]s.
+    positions: saved_position::PositionTable,
+    /// Number of structural changes so far, see [`version`](Self::version).
+    version: u64,
+    /// Name set by [`set_label`](Self::set_label), for panic messages.
+    label: Option<String>,
+    #[cfg(feature = "metrics")]
+    metrics: metrics::Counters,
+    #[cfg(feature = "debug-checks")]
+    tombstones: tombstones::Tombstones,
 }
 
 impl<T> Default for GenerationalTokenList<T> {
@@ -99,6 +318,100 @@ impl<T> Default for GenerationalTokenList<T> {
     }
 }
 
+/// Cloning a list preserves tokens: a token that is valid for the original list refers to the
+/// corresponding item in the clone.
+///
+/// # Examples
+/// ```
+/// # use generational_token_list::GenerationalTokenList;
+/// let mut list = GenerationalTokenList::new();
+/// let item1 = list.push_back(1);
+/// let copy = list.clone();
+/// assert_eq!(copy.get(item1), Some(&1));
+/// ```
+impl<T> Clone for GenerationalTokenList<T>
+where
+    T: Clone,
+{
+    fn clone(&self) -> Self {
+        GenerationalTokenList {
+            arena: self.arena.clone(),
+            head: self.head,
+            tail: self.tail,
+            unlinked_len: self.unlinked_len,
+            marks: self.marks.clone(),
+            generation: self.generation,
+            guards: None,
+            notifier: None,
+            recycled: Vec::new(),
+            max_len: self.max_len,
+            positions: Vec::new(),
+            version: self.version,
+            label: self.label.clone(),
+            #[cfg(feature = "metrics")]
+            metrics: Default::default(),
+            #[cfg(feature = "debug-checks")]
+            tombstones: self.tombstones.clone(),
+        }
+    }
+
+    /// If `self` holds items for exactly the same set of tokens as `source` (e.g. because `self` was
+    /// previously cloned from `source` and neither list has had items inserted or removed since),
+    /// the existing allocation is reused and each item is updated in place via
+    /// [`Clone::clone_from`]. Otherwise this falls back to `*self = source.clone()`.
+    ///
+    /// When the allocation is reused, `self` keeps its own order of free slots and its own
+    /// generation counter, so tokens handed out afterwards may differ from those `source` would hand
+    /// out. Use `clone` if the two lists need to stay in lockstep.
+    ///
+    /// In the fallback, the items of `self` are replaced wholesale, so its
+    /// [`SavedPosition`](crate::SavedPosition)s become empty, as they do on
+    /// [`clear`](Self::clear).
+    ///
+    /// # Panics
+    /// Panics if the allocation can't be reused while any item of `self` is protected by a
+    /// [`RemovalGuard`](crate::RemovalGuard), as the fallback would drop the guarded items.
+    fn clone_from(&mut self, source: &Self) {
+        let same_tokens = self.arena.len() == source.arena.len()
+            && source
+                .arena
+                .iter()
+                .all(|(index, _)| self.arena.contains(index));
+
+        if !same_tokens {
+            assert!(
+                !self.has_guards(),
+                "cannot replace the items of a list while items are guarded"
+            );
+
+            self.clear_positions();
+            let notifier = self.notifier.take();
+            let recycled = std::mem::take(&mut self.recycled);
+            let version = self.version;
+            let label = self.label.take();
+            *self = source.clone();
+            self.notifier = notifier;
+            self.recycled = recycled;
+            self.version = version;
+            self.label = label;
+            self.structure_changed();
+            return;
+        }
+
+        for (index, source_item) in source.arena.iter() {
+            let item = self.arena.get_mut(index).unwrap();
+            item.data.clone_from(&source_item.data);
+            item.previous = source_item.previous;
+            item.next = source_item.next;
+        }
+        self.head = source.head;
+        self.tail = source.tail;
+        self.unlinked_len = source.unlinked_len;
+        self.marks.clone_from(&source.marks);
+        self.structure_changed();
+    }
+}
+
 impl<T> GenerationalTokenList<T> {
     /// Creates a new `GenerationalTokenList<T>`.
     ///
@@ -113,15 +426,44 @@ impl<T> GenerationalTokenList<T> {
             arena: Arena::new(),
             head: None,
             tail: None,
+            unlinked_len: 0,
+            marks: TokenSet::new(),
+            generation: 0,
+            guards: None,
+            notifier: None,
+            recycled: Vec::new(),
+            max_len: None,
+            positions: Vec::new(),
+            version: 0,
+            label: None,
+            #[cfg(feature = "metrics")]
+            metrics: Default::default(),
+            #[cfg(feature = "debug-checks")]
+            tombstones: Default::default(),
         }
     }
 
-    /// Creates a new `GenerationalTokenList<T>` with given capacity.
+    /// Creates a new `GenerationalTokenList<T>` with given capacity. The first `n` insertions are
+    /// guaranteed not to allocate.
     pub fn with_capacity(n: usize) -> Self {
         GenerationalTokenList {
             arena: Arena::with_capacity(n),
             head: None,
             tail: None,
+            unlinked_len: 0,
+            marks: TokenSet::new(),
+            generation: 0,
+            guards: None,
+            notifier: None,
+            recycled: Vec::new(),
+            max_len: None,
+            positions: Vec::new(),
+            version: 0,
+            label: None,
+            #[cfg(feature = "metrics")]
+            metrics: Default::default(),
+            #[cfg(feature = "debug-checks")]
+            tombstones: Default::default(),
         }
     }
 
@@ -227,7 +569,11 @@ impl<T> GenerationalTokenList<T> {
         self.tail
     }
 
-    /// Remove all items from the arena. Invalidates all tokens.
+    /// Remove all items from the arena. Invalidates all tokens, except for items protected by a
+    /// [`RemovalGuard`](crate::RemovalGuard), which are kept. Items are dropped in arena slot order,
+    /// not list order.
+    ///
+    /// The allocated capacity is kept, see [`clear_keep_capacity`](Self::clear_keep_capacity).
     ///
     /// # Examples
     /// ```
@@ -240,13 +586,148 @@ impl<T> GenerationalTokenList<T> {
     /// assert_eq!(list.get(moo_2), None);
     /// ```
     pub fn clear(&mut self) {
+        trace_event!(len = self.arena.len(), "clear");
+        if self.has_guards() {
+            let unguarded: Vec<_> = self
+                .arena
+                .iter()
+                .map(|(index, _)| ItemToken { index })
+                .filter(|token| !self.is_guarded(*token))
+                .collect();
+            for token in unguarded {
+                self.remove(token);
+            }
+            self.marks.clear();
+            return;
+        }
+
+        self.marks.clear();
+        if self.arena.is_empty() {
+            return;
+        }
+
+        self.bump_generation();
+        #[cfg(feature = "debug-checks")]
+        for (index, _) in self.arena.iter() {
+            self.tombstones.record_removal(ItemToken { index });
+        }
         self.arena.clear();
         self.head = None;
         self.tail = None;
+        self.unlinked_len = 0;
+        self.clear_positions();
+        self.structure_changed();
+    }
+
+    /// Remove all items like [`clear`](Self::clear), and also reset the generation counter, so that
+    /// the list hands out tokens exactly like a list freshly created with
+    /// [`with_capacity`](Self::with_capacity) and the current capacity would.
+    ///
+    /// This is meant for very long-lived lists with a lot of churn, to give back the generation
+    /// space used up so far. It gives up the usual guarantee that a token is never valid again
+    /// once its item is removed: a token handed out before the call may match an item inserted
+    /// after it. Treat every outstanding token (including the keys of a
+    /// [`SecondaryMap`](crate::SecondaryMap) or a [`TokenSet`](crate::TokenSet)) as invalid by
+    /// construction, and drop them all.
+    ///
+    /// # Panics
+    /// Panics if any item is protected by a [`RemovalGuard`](crate::RemovalGuard).
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::new();
+    /// let first = list.push_back(1);
+    /// for i in 0..100 {
+    ///     list.pop_back();
+    ///     list.push_back(i);
+    /// }
+    ///
+    /// list.clear_and_reset_generations();
+    /// assert!(list.is_empty());
+    /// // Generations start over, so the first token comes back
+    /// assert_eq!(list.push_back(2), first);
+    /// ```
+    pub fn clear_and_reset_generations(&mut self) {
+        assert!(
+            !self.has_guards(),
+            "cannot reset generations while items are guarded"
+        );
+
+        self.clear();
+        self.arena = Arena::with_capacity(self.arena.capacity());
+        self.generation = 0;
+        #[cfg(feature = "debug-checks")]
+        self.tombstones.forget_removals();
+    }
+
+    /// Remove all items like [`clear`](Self::clear), keeping the allocated capacity so that the
+    /// list can be refilled up to its previous size without allocating. This is what `clear` does
+    /// too; this name makes the choice explicit at the call site.
+    ///
+    /// There is no variant that gives the memory back: the arena can't carry its generation counter
+    /// over to a new allocation, so releasing memory means resetting generations. Where that is
+    /// acceptable, follow the rules of
+    /// [`clear_and_reset_generations`](Self::clear_and_reset_generations) for outstanding tokens
+    /// and replace the list with a new one.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::with_capacity(100);
+    /// list.push_back_many(0..100);
+    /// list.clear_keep_capacity();
+    /// assert!(list.is_empty());
+    /// assert_eq!(list.capacity(), 100);
+    /// ```
+    pub fn clear_keep_capacity(&mut self) {
+        self.clear();
+    }
+
+    /// Remove all items like [`clear`](Self::clear), handing each one to `f` along with its (now
+    /// invalid) token: first the items of the list in list order, then the unlinked ones. Items
+    /// protected by a [`RemovalGuard`](crate::RemovalGuard) are kept.
+    ///
+    /// If `f` panics, the remaining items stay in the list.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::new();
+    /// list.push_back("b");
+    /// list.push_front("a");
+    /// list.push_back("c");
+    ///
+    /// let mut flushed = Vec::new();
+    /// list.clear_with(|_, item| flushed.push(item));
+    /// assert_eq!(flushed, vec!["a", "b", "c"]);
+    /// assert!(list.is_empty());
+    /// ```
+    pub fn clear_with(&mut self, mut f: impl FnMut(ItemToken, T)) {
+        let mut current = self.head;
+        while let Some(token) = current {
+            current = self.next_token(token);
+            if let Some(data) = self.remove(token) {
+                f(token, data);
+            }
+        }
+
+        let unlinked: Vec<_> = self
+            .arena
+            .iter()
+            .map(|(index, _)| ItemToken { index })
+            .filter(|token| self.is_unlinked(*token))
+            .collect();
+        for token in unlinked {
+            if let Some(data) = self.remove(token) {
+                f(token, data);
+            }
+        }
     }
 
     /// Remove the item identified by given token from the list and return the item. Invalidates the
-    /// token. Returns `None` if token is invalid.
+    /// token. Returns `None` if token is invalid, or if the item is protected by a
+    /// [`RemovalGuard`](crate::RemovalGuard).
     ///
     /// # Examples
     /// ```
@@ -264,1180 +745,5009 @@ impl<T> GenerationalTokenList<T> {
     /// assert_eq!(list.iter().collect::<Vec<_>>(), vec![&0, &2]);
     /// ```
     pub fn remove(&mut self, token: ItemToken) -> Option<T> {
+        if self.is_guarded(token) {
+            return None;
+        }
+        if self.arena.contains(token.index) {
+            self.bump_generation();
+        }
         let item = self.arena.remove(token.index)?;
+        trace_event!(token = ?token, "remove");
+        #[cfg(feature = "debug-checks")]
+        self.tombstones.record_removal(token);
+        #[cfg(feature = "metrics")]
+        self.metrics.record_removal();
+        self.move_positions(token, item.previous, item.next);
 
-        if self.head == Some(token) && self.tail == Some(token) {
-            // This was the only item in the list
-            self.head = None;
-            self.tail = None;
-        } else if self.head == Some(token) {
-            // This was the head and there is another item after us, so make it the new head
-            let next_token = item.next.unwrap();
-            let next = self.arena.get_mut(next_token.index).unwrap();
-            next.previous = None;
-            self.head = Some(next_token);
-        } else if self.tail == Some(token) {
-            // This was the head and there is another item before us, so make it the new tail
-            let prev_token = item.previous.unwrap();
-            let prev = self.arena.get_mut(prev_token.index).unwrap();
-            prev.next = None;
-            self.tail = Some(prev_token);
+        if self.head != Some(token) && item.previous.is_none() {
+            // The item was unlinked, so there are no neighbors to fix up
+            self.unlinked_len -= 1;
         } else {
-            // We were somewhere in the middle
-            let next_token = item.next.unwrap();
-            let prev_token = item.previous.unwrap();
-
-            let (next, prev) = self.arena.get2_mut(next_token.index, prev_token.index);
-
-            next.unwrap().previous = Some(prev_token);
-            prev.unwrap().next = Some(next_token);
+            self.join_neighbors(item.previous, item.next);
         }
+        self.structure_changed();
 
         Some(item.data)
     }
 
-    /// Remove first (head) item from the list and return it. Any tokens pointing to head are invalidated.
-    /// Returns `None` if the list is empty.
+    /// Detach the item identified by given token from the list without removing it. The token stays
+    /// valid and the item can still be accessed via [`get`](Self::get) and friends, but it is no
+    /// longer part of the sequence: it is skipped by iteration and not counted by
+    /// [`len`](Self::len). Use [`relink_after`](Self::relink_after),
+    /// [`relink_front`](Self::relink_front) or [`relink_back`](Self::relink_back) to put it back.
+    ///
+    /// Returns `false` (and does nothing) if the token is invalid or the item is already unlinked.
+    ///
+    /// Unlinked items are still removed by [`clear`](Self::clear) and can be removed individually
+    /// with [`remove`](Self::remove).
     ///
     /// # Examples
     /// ```
     /// # use generational_token_list::GenerationalTokenList;
     /// let mut list = GenerationalTokenList::new();
     /// list.push_back(1);
-    /// list.push_back(2);
+    /// let two = list.push_back(2);
     /// list.push_back(3);
-    /// assert_eq!(list.len(), 3);
-    /// assert_eq!(list.pop_front(), Some(1));
+    ///
+    /// assert!(list.unlink(two));
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), vec![&1, &3]);
     /// assert_eq!(list.len(), 2);
+    /// assert_eq!(list.get(two), Some(&2));
+    ///
+    /// list.relink_back(two);
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), vec![&1, &3, &2]);
     /// ```
-    pub fn pop_front(&mut self) -> Option<T> {
-        self.head.and_then(|token| self.remove(token))
+    pub fn unlink(&mut self, token: ItemToken) -> bool {
+        let (previous, next) = match self.arena.get(token.index) {
+            Some(item) if self.head == Some(token) || item.previous.is_some() => {
+                (item.previous, item.next)
+            }
+            _ => return false,
+        };
+
+        trace_event!(token = ?token, "unlink");
+        self.join_neighbors(previous, next);
+
+        let item = self.arena.get_mut(token.index).unwrap();
+        item.previous = None;
+        item.next = None;
+        self.unlinked_len += 1;
+        self.structure_changed();
+        true
     }
 
-    /// Remove last (tail) item from the list and return it. Any tokens pointing to tail are invalidated.
-    /// Returns `None` if the list is empty.
+    /// Returns `true` if the token is valid but its item has been detached with
+    /// [`unlink`](Self::unlink).
     ///
     /// # Examples
     /// ```
     /// # use generational_token_list::GenerationalTokenList;
     /// let mut list = GenerationalTokenList::new();
-    /// list.push_back(1);
-    /// list.push_back(2);
-    /// list.push_back(3);
-    /// assert_eq!(list.len(), 3);
-    /// assert_eq!(list.pop_back(), Some(3));
-    /// assert_eq!(list.len(), 2);
+    /// let one = list.push_back(1);
+    /// assert!(!list.is_unlinked(one));
+    /// list.unlink(one);
+    /// assert!(list.is_unlinked(one));
     /// ```
-    pub fn pop_back(&mut self) -> Option<T> {
-        self.tail.and_then(|token| self.remove(token))
+    pub fn is_unlinked(&self, token: ItemToken) -> bool {
+        match self.arena.get(token.index) {
+            Some(item) => self.head != Some(token) && item.previous.is_none(),
+            None => false,
+        }
     }
 
-    /// Returns the number of items in the list.
+    /// Put an item that was detached with [`unlink`](Self::unlink) back into the list, directly
+    /// after the item identified by `after`.
+    ///
+    /// # Panics
+    /// Panics if `token` is not an unlinked item, or if `after` is invalid or itself unlinked.
     ///
     /// # Examples
     /// ```
     /// # use generational_token_list::GenerationalTokenList;
     /// let mut list = GenerationalTokenList::new();
-    /// assert_eq!(list.len(), 0);
-    /// list.push_back(1);
+    /// let one = list.push_back(1);
     /// list.push_back(2);
-    /// list.push_back(3);
-    /// assert_eq!(list.len(), 3);
+    /// let three = list.push_back(3);
+    ///
+    /// list.unlink(three);
+    /// list.relink_after(three, one);
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), vec![&1, &3, &2]);
     /// ```
-    pub fn len(&self) -> usize {
-        self.arena.len()
+    pub fn relink_after(&mut self, token: ItemToken, after: ItemToken) {
+        self.assert_unlinked(token);
+        self.assert_linked(after);
+
+        let next = self.next_token(after);
+        self.link_between(token, Some(after), next);
     }
 
-    /// Returns the capacity of the list.
+    /// Put an item that was detached with [`unlink`](Self::unlink) back into the list as its first
+    /// item.
+    ///
+    /// # Panics
+    /// Panics if `token` is not an unlinked item.
     ///
     /// # Examples
     /// ```
     /// # use generational_token_list::GenerationalTokenList;
-    /// let mut list = GenerationalTokenList::<String>::with_capacity(10);
-    /// assert_eq!(list.capacity(), 10);
+    /// let mut list = GenerationalTokenList::new();
+    /// list.push_back(1);
+    /// let two = list.push_back(2);
+    ///
+    /// list.unlink(two);
+    /// list.relink_front(two);
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), vec![&2, &1]);
     /// ```
-    pub fn capacity(&self) -> usize {
-        self.arena.capacity()
+    pub fn relink_front(&mut self, token: ItemToken) {
+        self.assert_unlinked(token);
+        self.link_between(token, None, self.head);
     }
 
-    /// Get a reference to the data pointed to by given token, or `None` if token is invalid.
+    /// Put an item that was detached with [`unlink`](Self::unlink) back into the list as its last
+    /// item.
+    ///
+    /// # Panics
+    /// Panics if `token` is not an unlinked item.
     ///
     /// # Examples
     /// ```
     /// # use generational_token_list::GenerationalTokenList;
     /// let mut list = GenerationalTokenList::new();
-    /// let item1 = list.push_back(vec![0, 1, 2]);
-    /// let item2 = list.push_back(vec![3, 4, 5]);
-    /// let item3 = list.push_back(vec![6, 7, 8]);
-    /// assert_eq!(list.get(item2).unwrap(), &vec![3, 4, 5])
+    /// let one = list.push_back(1);
+    /// list.push_back(2);
+    ///
+    /// list.unlink(one);
+    /// list.relink_back(one);
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), vec![&2, &1]);
     /// ```
-    pub fn get(&self, token: ItemToken) -> Option<&T> {
-        self.arena.get(token.index).map(|i| &i.data)
+    pub fn relink_back(&mut self, token: ItemToken) {
+        self.assert_unlinked(token);
+        self.link_between(token, self.tail, None);
     }
 
-    /// Get a mutable reference to the data pointed to by given token, or `None` if token is invalid.
+    /// Detach the items from `range.start()` to `range.end()` (inclusive) from the list without
+    /// removing them, as with [`unlink`](Self::unlink). The returned [`DetachedSegment`] remembers
+    /// their order and can be put back in one piece with [`attach_after`](Self::attach_after),
+    /// [`attach_front`](Self::attach_front) or [`attach_back`](Self::attach_back). Tokens of the
+    /// detached items stay valid throughout.
+    ///
+    /// If the segment is dropped instead of being attached again, its items stay unlinked until
+    /// they are removed.
+    ///
+    /// # Panics
+    /// Panics if either token is invalid or if `range.end()` does not come at or after
+    /// `range.start()` in the list.
     ///
     /// # Examples
     /// ```
     /// # use generational_token_list::GenerationalTokenList;
     /// let mut list = GenerationalTokenList::new();
-    /// let item1 = list.push_back(vec![0, 1, 2]);
-    /// let item2 = list.push_back(vec![3, 4, 5]);
-    /// let item3 = list.push_back(vec![6, 7, 8]);
+    /// list.push_back(1);
+    /// let two = list.push_back(2);
+    /// let three = list.push_back(3);
+    /// let four = list.push_back(4);
     ///
-    /// let item2_data = list.get_mut(item2).unwrap();
-    /// item2_data.push(100);
-    /// assert_eq!(list.get(item2).unwrap(), &vec![3, 4, 5, 100]);
+    /// let segment = list.detach_range(two..=three);
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), vec![&1, &4]);
+    ///
+    /// list.attach_after(segment, four);
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), vec![&1, &4, &2, &3]);
+    /// assert_eq!(list.get(three), Some(&3));
     /// ```
-    pub fn get_mut(&mut self, token: ItemToken) -> Option<&mut T> {
-        self.arena.get_mut(token.index).map(|i| &mut i.data)
+    pub fn detach_range(&mut self, range: RangeInclusive<ItemToken>) -> DetachedSegment {
+        trace_span!("detach_range", start = ?range.start(), end = ?range.end());
+        let tokens = self.range_tokens(range);
+        for &token in &tokens {
+            self.unlink(token);
+        }
+        DetachedSegment { tokens }
     }
 
-    /// Get a pair of mutable (exclusive) references to the items identified by `token1` and `token2`.
+    /// Put the items of a segment obtained from [`detach_range`](Self::detach_range) back into the
+    /// list, in their original order, directly after the item identified by `after`.
     ///
     /// # Panics
-    /// Panics if `token1` and `token2` correspond to the same item.
+    /// Panics if the segment's items are no longer unlinked items of this list, or if `after` is
+    /// invalid or itself unlinked.
     ///
     /// # Examples
     /// ```
     /// # use generational_token_list::GenerationalTokenList;
     /// let mut list = GenerationalTokenList::new();
-    /// let item1 = list.push_back(vec![0, 1, 2]);
-    /// let item2 = list.push_back(vec![3, 4, 5]);
-    /// let item3 = list.push_back(vec![6, 7, 8]);
-    ///
-    /// let (item2_data, item3_data) = list.get2_mut(item2, item3);
+    /// let one = list.push_back(1);
+    /// let two = list.push_back(2);
+    /// let three = list.push_back(3);
     ///
-    /// item2_data.unwrap().clear();
-    /// item3_data.unwrap().pop();
-    /// assert_eq!(list.get(item2).unwrap(), &vec![]);
-    /// assert_eq!(list.get(item3).unwrap(), &vec![6, 7])
+    /// let segment = list.detach_range(two..=three);
+    /// list.push_back(4);
+    /// list.attach_after(segment, one);
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), vec![&1, &2, &3, &4]);
     /// ```
+    pub fn attach_after(&mut self, segment: DetachedSegment, after: ItemToken) {
+        trace_span!("attach_after", len = segment.len(), after = ?after);
+        let mut previous = after;
+        for token in segment.tokens {
+            self.relink_after(token, previous);
+            previous = token;
+        }
+    }
+
+    /// Put the items of a segment obtained from [`detach_range`](Self::detach_range) back at the
+    /// beginning of the list, in their original order.
     ///
-    /// This will panic:
+    /// # Panics
+    /// Panics if the segment's items are no longer unlinked items of this list.
     ///
-    /// ```should_panic
+    /// # Examples
+    /// ```
     /// # use generational_token_list::GenerationalTokenList;
     /// let mut list = GenerationalTokenList::new();
-    /// let item1 = list.push_back(vec![0, 1, 2]);
-    /// list.push_back(vec![3, 4, 5]);
+    /// list.push_back(1);
+    /// let two = list.push_back(2);
+    /// let three = list.push_back(3);
     ///
-    /// let (_, _) = list.get2_mut(item1, item1);
+    /// let segment = list.detach_range(two..=three);
+    /// list.attach_front(segment);
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), vec![&2, &3, &1]);
     /// ```
+    pub fn attach_front(&mut self, segment: DetachedSegment) {
+        trace_span!("attach_front", len = segment.len());
+        let mut previous = None;
+        for token in segment.tokens {
+            match previous {
+                None => self.relink_front(token),
+                Some(previous) => self.relink_after(token, previous),
+            }
+            previous = Some(token);
+        }
+    }
+
+    /// Put the items of a segment obtained from [`detach_range`](Self::detach_range) back at the end
+    /// of the list, in their original order.
     ///
-    /// Like `get_mut`, None will be returned if the token is invalid.
+    /// # Panics
+    /// Panics if the segment's items are no longer unlinked items of this list.
     ///
+    /// # Examples
     /// ```
     /// # use generational_token_list::GenerationalTokenList;
     /// let mut list = GenerationalTokenList::new();
-    /// let item1 = list.push_back(vec![0, 1, 2]);
-    /// let item2 = list.push_back(vec![3, 4, 5]);
-    /// list.remove(item2);
+    /// let one = list.push_back(1);
+    /// let two = list.push_back(2);
+    /// list.push_back(3);
     ///
-    /// let (item1_data, item2_data) = list.get2_mut(item1, item2);
-    /// assert_eq!(item1_data.unwrap(), &vec![0, 1, 2]);
-    /// assert_eq!(item2_data, None);
+    /// let segment = list.detach_range(one..=two);
+    /// list.attach_back(segment);
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), vec![&3, &1, &2]);
     /// ```
-    pub fn get2_mut(
-        &mut self,
-        token1: ItemToken,
-        token2: ItemToken,
-    ) -> (Option<&mut T>, Option<&mut T>) {
-        let (item1, item2) = self.arena.get2_mut(token1.index, token2.index);
-        (item1.map(|i| &mut i.data), item2.map(|i| &mut i.data))
+    pub fn attach_back(&mut self, segment: DetachedSegment) {
+        trace_span!("attach_back", len = segment.len());
+        for token in segment.tokens {
+            self.relink_back(token);
+        }
     }
 
-    /// Returns whether the list is empty.
+    /// Move the items identified by `tokens` (which don't need to be adjacent) to directly before
+    /// the item identified by `before`, keeping their relative order in the list. The order of
+    /// `tokens` itself doesn't matter, and duplicates are ignored. Tokens remain valid.
+    ///
+    /// This is what dragging a multi-selection to a new place does.
+    ///
+    /// # Panics
+    /// Panics if any of the tokens is invalid or unlinked, or if `before` is one of them.
     ///
     /// # Examples
     /// ```
     /// # use generational_token_list::GenerationalTokenList;
-    /// let empty_list = GenerationalTokenList::<String>::new();
-    /// assert!(empty_list.is_empty());
+    /// let mut list = GenerationalTokenList::new();
+    /// let tokens = list.push_back_many(0..6);
+    ///
+    /// list.move_many(&[tokens[4], tokens[1], tokens[2]], tokens[0]);
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), vec![&1, &2, &4, &0, &3, &5]);
     /// ```
-    pub fn is_empty(&self) -> bool {
-        self.arena.is_empty()
+    pub fn move_many(&mut self, tokens: &[ItemToken], before: ItemToken) {
+        trace_span!("move_many", len = tokens.len(), before = ?before);
+        let mut tokens = tokens.to_vec();
+        let linked = self.sort_tokens_by_position(&mut tokens);
+        assert_eq!(linked, tokens.len(), "invalid or unlinked token");
+        tokens.dedup();
+        assert!(
+            !tokens.contains(&before),
+            "cannot move items before one of them"
+        );
+        self.assert_linked(before);
+
+        for &token in &tokens {
+            self.unlink(token);
+        }
+        for token in tokens {
+            self.link_between(token, self.prev_token(before), Some(before));
+        }
     }
 
-    fn push_only_item_with(&mut self, create: impl FnOnce(ItemToken) -> T) -> ItemToken {
-        assert!(self.is_empty());
-        let token = self.new_node_with(|token| Item {
-            data: create(token),
-            previous: None,
-            next: None,
-        });
-        self.head = Some(token);
-        self.tail = Some(token);
-        token
-    }
-
-    fn new_node_with(&mut self, create: impl FnOnce(ItemToken) -> Item<T>) -> ItemToken {
-        let index = self.arena.insert_with(|index| create(ItemToken { index }));
-        ItemToken { index }
-    }
-
-    /// Insert the item returned by `create` at the end of the list. Returns a token which
-    /// corresponds to the new item.
+    /// Swap the item identified by given token with the item before it. Returns `false` (and does
+    /// nothing) if the item is the head, or unlinked.
     ///
-    /// This method allows you to add items that know their own token.
+    /// # Panics
+    /// Panics if `token` is invalid.
     ///
     /// # Examples
     /// ```
-    /// # use generational_token_list::{GenerationalTokenList, ItemToken};
-    /// struct Meta {
-    ///     data: u8,
-    ///     my_token: ItemToken,
-    /// }
-    ///
-    /// let mut list = GenerationalTokenList::new();
-    /// let item1 = list.push_back_with(|token| Meta { data: 1, my_token: token});
-    /// let item2 = list.push_back_with(|token| Meta { data: 2, my_token: token});
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut layers = GenerationalTokenList::new();
+    /// let background = layers.push_back("background");
+    /// let text = layers.push_back("text");
     ///
-    /// let item1_data = list.head().unwrap();
-    /// assert_eq!(item1, item1_data.my_token);
+    /// assert!(layers.move_up(text));
+    /// assert_eq!(layers.iter().collect::<Vec<_>>(), vec![&"text", &"background"]);
+    /// assert!(!layers.move_up(text));
+    /// assert!(layers.move_down(text));
+    /// assert_eq!(layers.head_token(), Some(background));
     /// ```
-    pub fn push_back_with(&mut self, create: impl FnOnce(ItemToken) -> T) -> ItemToken {
-        if self.head.is_none() {
-            return self.push_only_item_with(create);
+    pub fn move_up(&mut self, token: ItemToken) -> bool {
+        let previous = match self.prev_token(token) {
+            Some(previous) => previous,
+            None => return false,
+        };
+        self.unlink(token);
+        self.link_between(token, self.prev_token(previous), Some(previous));
+        true
+    }
+
+    /// Swap the item identified by given token with the item after it. Returns `false` (and does
+    /// nothing) if the item is the tail, or unlinked.
+    ///
+    /// # Panics
+    /// Panics if `token` is invalid.
+    pub fn move_down(&mut self, token: ItemToken) -> bool {
+        let next = match self.next_token(token) {
+            Some(next) => next,
+            None => return false,
+        };
+        self.unlink(token);
+        self.link_between(token, Some(next), self.next_token(next));
+        true
+    }
+
+    /// Make `previous` and `next` (the former neighbors of an item that is being taken out of the
+    /// list) point at each other, updating head/tail as needed.
+    fn join_neighbors(&mut self, previous: Option<ItemToken>, next: Option<ItemToken>) {
+        match previous {
+            Some(previous) => self.arena.get_mut(previous.index).unwrap().next = next,
+            None => self.head = next,
+        }
+        match next {
+            Some(next) => self.arena.get_mut(next.index).unwrap().previous = previous,
+            None => self.tail = previous,
         }
+    }
 
-        let old_tail = self.tail.unwrap();
+    /// Rewrite the links so that the list consists of `tokens`, in that order. `tokens` must be a
+    /// permutation of the linked items.
+    fn relink_in_order(&mut self, tokens: &[ItemToken]) {
+        for (i, token) in tokens.iter().enumerate() {
+            let item = self.arena.get_mut(token.index).unwrap();
+            item.previous = i.checked_sub(1).map(|previous| tokens[previous]);
+            item.next = tokens.get(i + 1).copied();
+        }
+        self.head = tokens.first().copied();
+        self.tail = tokens.last().copied();
+        self.structure_changed();
+    }
 
-        let ret = self.new_node_with(|token| Item {
-            data: create(token),
-            previous: Some(old_tail),
-            next: None,
-        });
+    /// Link the (unlinked) item identified by `token` in between `previous` and `next`, which must
+    /// be adjacent (or `None` to denote the start/end of the list).
+    fn link_between(
+        &mut self,
+        token: ItemToken,
+        previous: Option<ItemToken>,
+        next: Option<ItemToken>,
+    ) {
+        trace_event!(token = ?token, previous = ?previous, next = ?next, "link");
+        let item = self.arena.get_mut(token.index).unwrap();
+        item.previous = previous;
+        item.next = next;
 
-        // Fixup old tail
-        self.arena.get_mut(old_tail.index).unwrap().next = Some(ret);
+        match previous {
+            Some(previous) => self.arena.get_mut(previous.index).unwrap().next = Some(token),
+            None => self.head = Some(token),
+        }
+        match next {
+            Some(next) => self.arena.get_mut(next.index).unwrap().previous = Some(token),
+            None => self.tail = Some(token),
+        }
 
-        self.tail = Some(ret);
-        ret
+        self.unlinked_len -= 1;
+        self.structure_changed();
     }
 
-    /// Insert a new item at the end of the list. Returns a token which corresponds to the new item.
+    /// Remove the first item (in list order) for which `predicate` returns `true`, and return its
+    /// (now invalid) token along with the item. Returns `None` if no item matches. Items protected
+    /// by a [`RemovalGuard`](crate::RemovalGuard) are skipped without calling `predicate`.
     ///
     /// # Examples
     /// ```
     /// # use generational_token_list::GenerationalTokenList;
     /// let mut list = GenerationalTokenList::new();
-    /// let item1 = list.push_back("ITEM1");
-    /// assert_eq!(list.get(item1), Some(&"ITEM1"));
+    /// list.push_back(1);
+    /// let first_even = list.push_back(2);
+    /// list.push_back(4);
+    ///
+    /// assert_eq!(list.remove_first_where(|v| v % 2 == 0), Some((first_even, 2)));
+    /// assert_eq!(list.remove_first_where(|v| *v > 10), None);
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), vec![&1, &4]);
     /// ```
-    pub fn push_back(&mut self, data: T) -> ItemToken {
-        self.push_back_with(|_| data)
+    pub fn remove_first_where(
+        &mut self,
+        mut predicate: impl FnMut(&T) -> bool,
+    ) -> Option<(ItemToken, T)> {
+        let token = self
+            .iter_with_tokens()
+            .find(|&(token, data)| !self.is_guarded(token) && predicate(data))
+            .map(|(token, _)| token)?;
+        self.remove(token).map(|data| (token, data))
     }
 
-    /// Insert the item returned by `create` at the beginning of the list. Returns a token which
-    /// corresponds to the new item.
-    ///
-    /// This method allows you to add items that know their own token.
+    /// Remove first (head) item from the list and return it. Any tokens pointing to head are invalidated.
+    /// Returns `None` if the list is empty, or if the head is protected by a
+    /// [`RemovalGuard`](crate::RemovalGuard).
     ///
     /// # Examples
     /// ```
-    /// # use generational_token_list::{GenerationalTokenList, ItemToken};
-    /// struct Meta {
-    ///     data: u8,
-    ///     my_token: ItemToken,
-    /// }
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::new();
+    /// list.push_back(1);
+    /// list.push_back(2);
+    /// list.push_back(3);
+    /// assert_eq!(list.len(), 3);
+    /// assert_eq!(list.pop_front(), Some(1));
+    /// assert_eq!(list.len(), 2);
+    /// ```
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.head.and_then(|token| self.remove(token))
+    }
+
+    /// Remove last (tail) item from the list and return it. Any tokens pointing to tail are invalidated.
+    /// Returns `None` if the list is empty, or if the tail is protected by a
+    /// [`RemovalGuard`](crate::RemovalGuard).
     ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
     /// let mut list = GenerationalTokenList::new();
-    /// let item1 = list.push_front_with(|token| Meta { data: 1, my_token: token});
-    /// let item2 = list.push_front_with(|token| Meta { data: 2, my_token: token});
+    /// list.push_back(1);
+    /// list.push_back(2);
+    /// list.push_back(3);
+    /// assert_eq!(list.len(), 3);
+    /// assert_eq!(list.pop_back(), Some(3));
+    /// assert_eq!(list.len(), 2);
+    /// ```
+    pub fn pop_back(&mut self) -> Option<T> {
+        self.tail.and_then(|token| self.remove(token))
+    }
+
+    /// Remove the first (head) item from the list and return it, but only if `predicate` returns
+    /// `true` for it. Returns `None` if the list is empty or the predicate returns `false`, or if the head is
+    /// protected by a [`RemovalGuard`](crate::RemovalGuard).
     ///
-    /// let item2_data = list.head().unwrap();
-    /// assert_eq!(item2, item2_data.my_token);
+    /// # Examples
     /// ```
-    pub fn push_front_with(&mut self, create: impl FnOnce(ItemToken) -> T) -> ItemToken {
-        if self.head.is_none() {
-            return self.push_only_item_with(create);
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::new();
+    /// list.push_back(1);
+    /// list.push_back(2);
+    /// assert_eq!(list.pop_front_if(|v| *v > 1), None);
+    /// assert_eq!(list.pop_front_if(|v| *v == 1), Some(1));
+    /// assert_eq!(list.len(), 1);
+    /// ```
+    pub fn pop_front_if(&mut self, predicate: impl FnOnce(&mut T) -> bool) -> Option<T> {
+        let head = self.head?;
+        if predicate(self.get_mut(head).unwrap()) {
+            self.remove(head)
+        } else {
+            None
         }
+    }
 
-        let old_head = self.head.unwrap();
-        let ret = self.new_node_with(|token| Item {
-            data: create(token),
-            previous: None,
-            next: Some(old_head),
-        });
-
-        // Fixup old head
-        self.arena.get_mut(old_head.index).unwrap().previous = Some(ret);
+    /// Remove the last (tail) item from the list and return it, but only if `predicate` returns
+    /// `true` for it. Returns `None` if the list is empty or the predicate returns `false`, or if the tail is
+    /// protected by a [`RemovalGuard`](crate::RemovalGuard).
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::new();
+    /// list.push_back(1);
+    /// list.push_back(2);
+    /// assert_eq!(list.pop_back_if(|v| *v == 1), None);
+    /// assert_eq!(list.pop_back_if(|v| *v == 2), Some(2));
+    /// assert_eq!(list.len(), 1);
+    /// ```
+    pub fn pop_back_if(&mut self, predicate: impl FnOnce(&mut T) -> bool) -> Option<T> {
+        let tail = self.tail?;
+        if predicate(self.get_mut(tail).unwrap()) {
+            self.remove(tail)
+        } else {
+            None
+        }
+    }
 
-        self.head = Some(ret);
+    /// Remove up to `n` items from the front of the list and return them in list order. Any tokens
+    /// pointing to removed items are invalidated. Returns fewer than `n` items if the list runs out
+    /// or an item protected by a [`RemovalGuard`](crate::RemovalGuard) becomes the head.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::new();
+    /// list.push_back(1);
+    /// list.push_back(2);
+    /// list.push_back(3);
+    /// assert_eq!(list.pop_front_n(2), vec![1, 2]);
+    /// assert_eq!(list.pop_front_n(2), vec![3]);
+    /// assert!(list.is_empty());
+    /// ```
+    pub fn pop_front_n(&mut self, n: usize) -> Vec<T> {
+        let mut ret = Vec::with_capacity(n.min(self.len()));
+        while ret.len() < n {
+            match self.pop_front() {
+                Some(data) => ret.push(data),
+                None => break,
+            }
+        }
         ret
     }
 
-    /// Insert a new item at the beginning of the list. Returns a token which corresponds to the new item.
+    /// Remove up to `n` items from the back of the list and return them in the order they were
+    /// removed (i.e. the old tail comes first). Any tokens pointing to removed items are
+    /// invalidated. Returns fewer than `n` items if the list runs out or an item protected by a
+    /// [`RemovalGuard`](crate::RemovalGuard) becomes the tail.
     ///
     /// # Examples
     /// ```
     /// # use generational_token_list::GenerationalTokenList;
     /// let mut list = GenerationalTokenList::new();
-    /// list.push_back(":)");
-    /// list.push_back(":/");
-    /// list.push_front(":|");
-    /// assert_eq!(list.head(), Some(&":|"));
+    /// list.push_back(1);
+    /// list.push_back(2);
+    /// list.push_back(3);
+    /// assert_eq!(list.pop_back_n(2), vec![3, 2]);
+    /// assert_eq!(list.pop_back_n(2), vec![1]);
+    /// assert!(list.is_empty());
     /// ```
-    pub fn push_front(&mut self, data: T) -> ItemToken {
-        self.push_front_with(|_| data)
+    pub fn pop_back_n(&mut self, n: usize) -> Vec<T> {
+        let mut ret = Vec::with_capacity(n.min(self.len()));
+        while ret.len() < n {
+            match self.pop_back() {
+                Some(data) => ret.push(data),
+                None => break,
+            }
+        }
+        ret
     }
 
-    /// Insert the item returned by `create` after the item identified by given token. Returns a token
-    /// which corresponds to the new item.
+    /// Returns an iterator that removes and yields items from the front of the list for as long as
+    /// `predicate` returns `true` for the current head. Any tokens pointing to removed items are
+    /// invalidated. The iterator also stops at an item protected by a
+    /// [`RemovalGuard`](crate::RemovalGuard).
     ///
-    /// This method allows you to add items that know their own token.
-    ///
-    /// # Panics
-    /// Panics if `after` is an invalid token.
+    /// Items are only removed as the iterator is advanced; dropping the iterator early leaves the
+    /// remaining items in the list.
     ///
     /// # Examples
     /// ```
-    /// # use generational_token_list::{GenerationalTokenList, ItemToken};
-    /// struct Meta {
-    ///     data: u8,
-    ///     my_token: ItemToken,
-    /// }
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut timers = GenerationalTokenList::new();
+    /// timers.push_back(10);
+    /// timers.push_back(20);
+    /// timers.push_back(30);
     ///
-    /// let mut list = GenerationalTokenList::new();
-    /// let head = list.push_back_with(|token| Meta { data: 0, my_token: token });
-    /// list.push_back_with(|token| Meta { data: 2, my_token: token });
+    /// let now = 25;
+    /// let expired = timers.drain_while_front(|deadline| *deadline <= now).collect::<Vec<_>>();
+    /// assert_eq!(expired, vec![10, 20]);
+    /// assert_eq!(timers.iter().collect::<Vec<_>>(), vec![&30]);
+    /// ```
+    pub fn drain_while_front<F>(&mut self, predicate: F) -> DrainWhile<'_, T, F>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        DrainWhile {
+            list: self,
+            predicate,
+            from_back: false,
+        }
+    }
+
+    /// Returns an iterator that removes and yields items from the back of the list for as long as
+    /// `predicate` returns `true` for the current tail. Any tokens pointing to removed items are
+    /// invalidated. The iterator also stops at an item protected by a
+    /// [`RemovalGuard`](crate::RemovalGuard).
     ///
-    /// list.insert_after_with(head, |token| Meta { data: 1, my_token: token });
+    /// Items are only removed as the iterator is advanced; dropping the iterator early leaves the
+    /// remaining items in the list.
     ///
-    /// assert_eq!(list.into_iter().map(|m| m.data).collect::<Vec<_>>(), vec![0, 1, 2]);
+    /// # Examples
     /// ```
-    pub fn insert_after_with(
-        &mut self,
-        after: ItemToken,
-        create: impl FnOnce(ItemToken) -> T,
-    ) -> ItemToken {
-        assert!(!self.is_empty());
-
-        let item_token_following_after = self.arena.get(after.index).unwrap().next;
-        match item_token_following_after {
-            // `after` is in tail position
-            None => self.push_back_with(create),
-            Some(item_token_following_after) => {
-                // `after` is not in tail position, which means we are inserting between two items
-                let ret = self.new_node_with(|token| Item {
-                    data: create(token),
-                    previous: Some(after),
-                    next: Some(item_token_following_after),
-                });
-
-                let (after_item, item_following_after) = self
-                    .arena
-                    .get2_mut(after.index, item_token_following_after.index);
-
-                after_item.unwrap().next = Some(ret);
-                item_following_after.unwrap().previous = Some(ret);
-
-                ret
-            }
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::new();
+    /// list.push_back(1);
+    /// list.push_back(0);
+    /// list.push_back(0);
+    ///
+    /// let zeros = list.drain_while_back(|v| *v == 0).count();
+    /// assert_eq!(zeros, 2);
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), vec![&1]);
+    /// ```
+    pub fn drain_while_back<F>(&mut self, predicate: F) -> DrainWhile<'_, T, F>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        DrainWhile {
+            list: self,
+            predicate,
+            from_back: true,
         }
     }
 
-    /// Insert a new item after the item identified by given token.
+    /// Returns an iterator that walks the list from front to back, passing each item to `f`, and
+    /// removes the items for which `f` returns `Some`, yielding the returned values. Any tokens
+    /// pointing to removed items are invalidated. Items protected by a
+    /// [`RemovalGuard`](crate::RemovalGuard) are skipped without calling `f`.
     ///
-    /// # Panics
-    /// Panics if `after` is an invalid token.
+    /// Items are only visited as the iterator is advanced; dropping the iterator early leaves the
+    /// remaining items in the list, untouched.
     ///
     /// # Examples
     /// ```
     /// # use generational_token_list::GenerationalTokenList;
-    /// let mut list = GenerationalTokenList::<i32>::new();
-    /// let item1 = list.push_back(10);
-    /// list.push_back(20);
-    /// list.insert_after(item1, 300);
-    /// assert_eq!(list.into_iter().collect::<Vec<_>>(), vec![10, 300, 20])
+    /// let mut rows = GenerationalTokenList::from(vec!["id=1", "junk", "id=22"]);
+    /// let ids = rows
+    ///     .drain_filter_map(|row| row.strip_prefix("id=")?.parse::<u32>().ok())
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(ids, vec![1, 22]);
+    /// assert_eq!(rows.iter().collect::<Vec<_>>(), vec![&"junk"]);
     /// ```
-    pub fn insert_after(&mut self, after: ItemToken, data: T) -> ItemToken {
-        self.insert_after_with(after, |_| data)
+    pub fn drain_filter_map<U, F>(&mut self, f: F) -> DrainFilterMap<'_, T, F>
+    where
+        F: FnMut(&mut T) -> Option<U>,
+    {
+        DrainFilterMap {
+            next: self.head,
+            list: self,
+            f,
+        }
     }
 
-    /// Insert the item returned by `create` before the item identified by given token. Returns a token
-    /// which corresponds to the new item.
-    ///
-    /// This method allows you to add items that know their own token.
+    /// Retain only the items from `range.start()` to `range.end()` (inclusive) for which
+    /// `predicate` returns `true`. Items outside of the range are left untouched. Any tokens pointing
+    /// to removed items are invalidated.
     ///
     /// # Panics
-    /// Panics if `before` is an invalid token.
+    /// Panics if either token is invalid or if `range.end()` does not come at or after
+    /// `range.start()` in the list. In that case the list is not modified.
     ///
     /// # Examples
     /// ```
-    /// # use generational_token_list::{GenerationalTokenList, ItemToken};
-    /// struct Meta {
-    ///     data: u8,
-    ///     my_token: ItemToken,
-    /// }
-    ///
+    /// # use generational_token_list::GenerationalTokenList;
     /// let mut list = GenerationalTokenList::new();
-    /// list.push_back_with(|token| Meta { data: 0, my_token: token });
-    /// let tail = list.push_back_with(|token| Meta { data: 2, my_token: token });
-    ///
-    /// list.insert_before_with(tail, |token| Meta { data: 1, my_token: token });
+    /// list.push_back(1);
+    /// let start = list.push_back(2);
+    /// list.push_back(3);
+    /// let end = list.push_back(4);
+    /// list.push_back(5);
     ///
-    /// assert_eq!(list.into_iter().map(|m| m.data).collect::<Vec<_>>(), vec![0, 1, 2]);
+    /// list.retain_range(start..=end, |v| v % 2 == 0);
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), vec![&1, &2, &4, &5]);
     /// ```
-    pub fn insert_before_with(
+    pub fn retain_range(
         &mut self,
-        before: ItemToken,
-        create: impl FnOnce(ItemToken) -> T,
-    ) -> ItemToken {
-        assert!(!self.is_empty());
-
-        let item_token_preceding_before = self.arena.get(before.index).unwrap().previous;
-        match item_token_preceding_before {
-            // `before` is in head position
-            None => self.push_front_with(create),
-            Some(item_token_preceding_before) => {
-                // `before` is not in head position, which means we are inserting between two items
-                let ret = self.new_node_with(|token| Item {
-                    data: create(token),
-                    previous: Some(item_token_preceding_before),
-                    next: Some(before),
-                });
-
-                let (before_item, item_preceding_before) = self
-                    .arena
-                    .get2_mut(before.index, item_token_preceding_before.index);
-
-                before_item.unwrap().previous = Some(ret);
-                item_preceding_before.unwrap().next = Some(ret);
-
-                ret
+        range: RangeInclusive<ItemToken>,
+        mut predicate: impl FnMut(&T) -> bool,
+    ) {
+        for token in self.range_tokens(range) {
+            if !predicate(&self[token]) {
+                self.remove(token);
             }
         }
     }
 
-    /// Insert a new item before the item identified by given token.
+    /// Remove every item that equals one of `values`, e.g. the entries of an external set that
+    /// must no longer be in the list. Returns the number of removed items; items protected by a
+    /// [`RemovalGuard`](crate::RemovalGuard) are kept and not counted.
     ///
-    /// # Panics
-    /// Panics if `before` is an invalid token.
+    /// `values` is collected into a hash set first, so this takes time proportional to the length
+    /// of the list plus the number of values, rather than their product. Items can be looked up by
+    /// any type they can be borrowed as, like `str` for `String` items.
     ///
     /// # Examples
     /// ```
     /// # use generational_token_list::GenerationalTokenList;
-    /// let mut list = GenerationalTokenList::<i32>::new();
-    /// list.push_back(20);
-    /// list.push_back(100);
-    /// let item3 = list.push_back(10);
-    /// list.insert_before(item3, 300);
-    /// assert_eq!(list.into_iter().collect::<Vec<_>>(), vec![20, 100, 300, 10])
+    /// let mut list = GenerationalTokenList::new();
+    /// list.push_back(String::from("a"));
+    /// list.push_back(String::from("b"));
+    /// list.push_back(String::from("c"));
+    ///
+    /// assert_eq!(list.remove_all_in(vec!["a", "c", "d"]), 2);
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), vec!["b"]);
     /// ```
-    pub fn insert_before(&mut self, before: ItemToken, data: T) -> ItemToken {
-        self.insert_before_with(before, |_| data)
+    pub fn remove_all_in<'a, Q>(&mut self, values: impl IntoIterator<Item = &'a Q>) -> usize
+    where
+        T: Borrow<Q>,
+        Q: Eq + Hash + ?Sized + 'a,
+    {
+        let values: HashSet<_> = values.into_iter().collect();
+        self.remove_where_contained(&values, true)
     }
 
-    /// Returns an iterator of references to item data in the list.
+    /// Remove every item that doesn't equal any of `values`, e.g. to reconcile the list with an
+    /// external authoritative set. Returns the number of removed items; items protected by a
+    /// [`RemovalGuard`](crate::RemovalGuard) are kept and not counted. See
+    /// [`remove_all_in`](Self::remove_all_in) for details.
     ///
     /// # Examples
     /// ```
+    /// # use std::collections::HashSet;
     /// # use generational_token_list::GenerationalTokenList;
-    /// let mut list = GenerationalTokenList::<i32>::new();
-    /// list.push_back(5);
-    /// list.push_back(6);
-    /// list.push_back(7);
+    /// let mut list = GenerationalTokenList::from(vec![1, 2, 3, 4]);
+    /// let authoritative: HashSet<i32> = vec![2, 4, 6].into_iter().collect();
     ///
-    /// let i = list.iter().enumerate().collect::<Vec<_>>();
-    /// assert_eq!(i, vec![(0, &5), (1, &6), (2, &7)]);
+    /// assert_eq!(list.retain_all_in(&authoritative), 2);
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), vec![&2, &4]);
     /// ```
-    pub fn iter(&self) -> Iter<T> {
-        Iter {
-            inner: self.iter_with_tokens(),
-        }
+    pub fn retain_all_in<'a, Q>(&mut self, values: impl IntoIterator<Item = &'a Q>) -> usize
+    where
+        T: Borrow<Q>,
+        Q: Eq + Hash + ?Sized + 'a,
+    {
+        let values: HashSet<_> = values.into_iter().collect();
+        self.remove_where_contained(&values, false)
     }
 
-    /// Returns an iterator of pairs of (item tokens, references to item data) in the list.
-    pub fn iter_with_tokens(&self) -> IterWithTokens<T> {
-        IterWithTokens {
-            list: self,
-            next_item: self.head,
-        }
+    /// Remove the items that are (or aren't, if `contained` is `false`) in `values`.
+    fn remove_where_contained<Q>(&mut self, values: &HashSet<&Q>, contained: bool) -> usize
+    where
+        T: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        let doomed: Vec<_> = self
+            .iter_with_tokens()
+            .filter(|&(_, data)| values.contains(data.borrow()) == contained)
+            .map(|(token, _)| token)
+            .collect();
+        doomed
+            .into_iter()
+            .filter(|&token| self.remove(token).is_some())
+            .count()
     }
 
-    /// Returns an iterator of mutable (exclusive) references to item data in the list.
+    /// Mark the item identified by given token, to keep it alive through the next
+    /// [`sweep_unmarked`](Self::sweep_unmarked). Returns `false` if the token is invalid.
     ///
     /// # Examples
     /// ```
     /// # use generational_token_list::GenerationalTokenList;
-    /// let mut list = GenerationalTokenList::<i32>::new();
-    /// list.push_back(5);
-    /// list.push_back(6);
-    /// list.push_back(7);
+    /// let mut list = GenerationalTokenList::new();
+    /// let root = list.push_back("root");
+    /// let leaf = list.push_back("leaf");
+    /// list.push_back("garbage");
     ///
-    /// for i in list.iter_mut() {
-    ///     *i += 10;
-    /// }
+    /// list.mark(root);
+    /// list.mark(leaf);
+    /// assert!(list.is_marked(leaf));
     ///
-    /// assert_eq!(list.into_iter().collect::<Vec<_>>(), vec![15, 16, 17]);
+    /// assert_eq!(list.sweep_unmarked(), 1);
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), vec![&"root", &"leaf"]);
     /// ```
-    #[cfg(feature = "iter-mut")]
-    pub fn iter_mut(&mut self) -> IterMut<T> {
-        IterMut {
-            inner: self.iter_with_tokens_mut(),
+    pub fn mark(&mut self, token: ItemToken) -> bool {
+        if !self.arena.contains(token.index) {
+            return false;
         }
+
+        self.marks.insert(token);
+        true
     }
 
-    /// Returns an iterator of pairs of (item tokens, mutable (exclusive) references to item data) in the list.
-    #[cfg(feature = "iter-mut")]
-    pub fn iter_with_tokens_mut(&mut self) -> IterWithTokensMut<T> {
-        let head = self.head;
-        IterWithTokensMut {
-            list: self,
-            next_item: head,
-        }
+    /// Returns `true` if the token is valid and its item has been marked with
+    /// [`mark`](Self::mark) since the last sweep or [`clear_marks`](Self::clear_marks).
+    pub fn is_marked(&self, token: ItemToken) -> bool {
+        self.arena.contains(token.index) && self.marks.contains(token)
     }
 
-    /// Returns the token corresponding to the item that is after that identified by `token`. Returns
-    /// `None` if no item comes after it (i.e. it is the tail).
+    /// Unmark all items.
     ///
-    /// # Panics
-    /// Panics if `token` is invalid.
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::new();
+    /// let item = list.push_back(1);
+    /// list.mark(item);
+    /// list.clear_marks();
+    /// assert!(!list.is_marked(item));
+    /// ```
+    pub fn clear_marks(&mut self) {
+        self.marks.clear();
+    }
+
+    /// Remove every item that has not been marked with [`mark`](Self::mark), including unlinked
+    /// items, and return how many were removed. All marks are cleared afterwards, so the next
+    /// mark phase starts from scratch.
     ///
     /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::new();
+    /// let one = list.push_back(1);
+    /// list.push_back(2);
+    /// list.push_back(3);
+    ///
+    /// list.mark(one);
+    /// assert_eq!(list.sweep_unmarked(), 2);
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), vec![&1]);
+    ///
+    /// // Nothing is marked anymore
+    /// assert_eq!(list.sweep_unmarked(), 1);
+    /// assert!(list.is_empty());
+    /// ```
+    pub fn sweep_unmarked(&mut self) -> usize {
+        let unmarked: Vec<_> = self
+            .arena
+            .iter()
+            .map(|(index, _)| ItemToken { index })
+            .filter(|token| !self.marks.contains(*token))
+            .collect();
+
+        let removed = unmarked
+            .into_iter()
+            .filter(|&token| self.remove(token).is_some())
+            .count();
+        self.marks.clear();
+
+        removed
+    }
+
+    /// Consume the list and return its items, in order, as a `Vec`.
     ///
+    /// # Examples
     /// ```
     /// # use generational_token_list::GenerationalTokenList;
-    /// let mut list = GenerationalTokenList::<i32>::new();
-    /// let item1 = list.push_back(5);
-    /// let item2 = list.push_back(6);
-    /// list.push_back(7);
+    /// let mut list = GenerationalTokenList::new();
+    /// let two = list.push_back(2);
+    /// list.push_front(1);
+    /// list.insert_after(two, 3);
+    /// assert_eq!(list.into_vec(), vec![1, 2, 3]);
+    /// ```
+    pub fn into_vec(self) -> Vec<T> {
+        let mut ret = Vec::with_capacity(self.len());
+        ret.extend(self);
+        ret
+    }
+
+    /// Returns the number of items in the list.
     ///
-    /// assert_eq!(list.next_token(item1), Some(item2));
+    /// # Examples
     /// ```
-    pub fn next_token(&self, token: ItemToken) -> Option<ItemToken> {
-        self.arena.get(token.index).unwrap().next
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::new();
+    /// assert_eq!(list.len(), 0);
+    /// list.push_back(1);
+    /// list.push_back(2);
+    /// list.push_back(3);
+    /// assert_eq!(list.len(), 3);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.arena.len() - self.unlinked_len
     }
 
-    /// Returns the token corresponding to the item that is before that identified by `token`. Returns
-    /// `None` if no item comes before it (i.e. it is the head).
+    /// Check the internal consistency of the list and panic if something is off: the links between
+    /// items must agree in both directions, end at the head and tail, and cover exactly
+    /// [`len`](Self::len) items, and unlinked items must have no links.
     ///
-    /// # Panics
-    /// Panics if `token` is invalid.
+    /// This takes time proportional to the capacity of the list. With the `debug-checks` feature,
+    /// it runs automatically after every structural change, to catch corruption (e.g. from
+    /// downstream `unsafe` code) as close to its cause as possible.
     ///
     /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::new();
+    /// let one = list.push_back(1);
+    /// list.push_back(2);
+    /// list.unlink(one);
+    /// list.assert_invariants();
+    /// ```
+    pub fn assert_invariants(&self) {
+        let mut linked = 0;
+        let mut previous = None;
+        let mut current = self.head;
+        while let Some(token) = current {
+            let item = self
+                .arena
+                .get(token.index)
+                .unwrap_or_else(|| panic!("link to invalid item {:?}", token));
+            assert_eq!(
+                item.previous, previous,
+                "item {:?} has a wrong previous link",
+                token
+            );
+
+            linked += 1;
+            assert!(linked <= self.arena.len(), "cycle in list links");
+            previous = current;
+            current = item.next;
+        }
+        assert_eq!(previous, self.tail, "tail is not the last linked item");
+        assert_eq!(linked, self.len(), "number of linked items is off");
+
+        let unlinked = self
+            .arena
+            .iter()
+            .filter(|(index, item)| {
+                self.head != Some(ItemToken { index: *index }) && item.previous.is_none()
+            })
+            .inspect(|(index, item)| {
+                assert_eq!(item.next, None, "unlinked item {:?} has a next link", index)
+            })
+            .count();
+        assert_eq!(
+            unlinked, self.unlinked_len,
+            "number of unlinked items is off"
+        );
+    }
+
+    /// Returns the linkage of the list: a `(token, previous, next)` triple for every item, in list
+    /// order. Meant for tests that want to check tokens and links without going through the list's
+    /// internals. Unlinked items are not included.
     ///
+    /// # Examples
     /// ```
     /// # use generational_token_list::GenerationalTokenList;
-    /// let mut list = GenerationalTokenList::<i32>::new();
-    /// list.push_back(5);
-    /// let item2 = list.push_back(6);
-    /// let item3 = list.push_back(7);
+    /// let mut list = GenerationalTokenList::new();
+    /// let a = list.push_back("a");
+    /// let b = list.push_back("b");
+    /// assert_eq!(list.structure(), vec![(a, None, Some(b)), (b, Some(a), None)]);
+    /// ```
+    pub fn structure(&self) -> Vec<(ItemToken, Option<ItemToken>, Option<ItemToken>)> {
+        let mut structure = Vec::with_capacity(self.len());
+        let mut current = self.head;
+        while let Some(token) = current {
+            let item = &self.arena[token.index];
+            structure.push((token, item.previous, item.next));
+            current = item.next;
+        }
+        structure
+    }
+
+    /// Returns the capacity of the list.
     ///
-    /// assert_eq!(list.prev_token(item3), Some(item2));
+    /// # Examples
     /// ```
-    pub fn prev_token(&self, token: ItemToken) -> Option<ItemToken> {
-        self.arena.get(token.index).unwrap().previous
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::<String>::with_capacity(10);
+    /// assert_eq!(list.capacity(), 10);
+    /// ```
+    pub fn capacity(&self) -> usize {
+        self.arena.capacity()
     }
 
-    /// Returns the token corresponding to the item at position `pos`. Returns
-    /// `None` if `pos` is invalid.
+    /// Returns how many more items can be inserted before the list needs to allocate.
+    ///
+    /// Slots freed by removals are reused, so this goes back up when items are removed.
     ///
     /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::with_capacity(4);
+    /// let item = list.push_back(1);
+    /// assert_eq!(list.spare_capacity(), 3);
+    /// list.remove(item);
+    /// assert_eq!(list.spare_capacity(), 4);
+    /// ```
+    pub fn spare_capacity(&self) -> usize {
+        self.arena.capacity() - self.arena.len()
+    }
+
+    /// Make sure at least `n` more items can be inserted without allocating, i.e. that
+    /// [`spare_capacity`](Self::spare_capacity) is at least `n`. Does nothing if there is enough
+    /// room already.
     ///
+    /// Use this ahead of time when insertions must not hit the allocator, e.g. on a real-time
+    /// thread.
+    ///
+    /// # Examples
     /// ```
     /// # use generational_token_list::GenerationalTokenList;
-    /// let mut list = GenerationalTokenList::<i32>::new();
-    /// let item1 = list.push_back(5);
-    /// let item2 = list.push_back(6);
-    /// let item3 = list.push_back(7);
+    /// let mut list = GenerationalTokenList::new();
+    /// list.push_back(0);
+    /// list.prewarm(100);
+    /// assert!(list.spare_capacity() >= 100);
     ///
-    /// assert_eq!(list.token_at(0), Some(item1));
-    /// assert_eq!(list.token_at(1), Some(item2));
-    /// assert_eq!(list.token_at(2), Some(item3));
-    /// assert_eq!(list.token_at(3), None);
-    /// assert_eq!(list.token_at(4), None);
+    /// let capacity = list.capacity();
+    /// for i in 0..100 {
+    ///     list.push_back(i);
+    /// }
+    /// assert_eq!(list.capacity(), capacity);
     /// ```
-    pub fn token_at(&self, pos: usize) -> Option<ItemToken> {
-        self.iter_with_tokens().nth(pos).map(|ret| ret.0)
+    pub fn prewarm(&mut self, n: usize) {
+        let free = self.spare_capacity();
+        if n > free {
+            self.arena.reserve(n - free);
+            #[cfg(feature = "metrics")]
+            self.metrics.record_reallocation();
+        }
     }
 
-    /// Returns the token corresponding to the item at position `pos` from the back. Returns
-    /// `None` if `pos` is invalid.
+    /// Returns the generation that newly inserted items get.
+    ///
+    /// The list has a single generation counter, which is incremented whenever an item is removed
+    /// (or the list is cleared). A token is only valid while the generation stored in it matches
+    /// the one of the item in its slot, which is why a stale token can't refer to an item inserted
+    /// later.
     ///
     /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::new();
+    /// let item = list.push_back(1);
+    /// assert_eq!(list.generation(), 0);
+    /// list.remove(item);
+    /// assert_eq!(list.generation(), 1);
+    /// ```
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Returns a counter that is incremented on every structural change: insertions, removals,
+    /// unlinking and relinking, reordering and clearing. Changing the value of an item in place is
+    /// not a structural change.
+    ///
+    /// Compare it with a value saved earlier to find out cheaply whether anything has changed in
+    /// the meantime, e.g. to invalidate a cache. A clone starts out with the version of the
+    /// original.
     ///
+    /// # Examples
     /// ```
     /// # use generational_token_list::GenerationalTokenList;
-    /// let mut list = GenerationalTokenList::<i32>::new();
-    /// let item1 = list.push_back(5);
-    /// let item2 = list.push_back(6);
-    /// let item3 = list.push_back(7);
+    /// let mut list = GenerationalTokenList::new();
+    /// let item = list.push_back(1);
+    /// let seen = list.version();
     ///
-    /// assert_eq!(list.token_at_back(0), Some(item3));
-    /// assert_eq!(list.token_at_back(1), Some(item2));
-    /// assert_eq!(list.token_at_back(2), Some(item1));
-    /// assert_eq!(list.token_at_back(3), None);
-    /// assert_eq!(list.token_at_back(4), None);
+    /// list[item] = 2;
+    /// assert_eq!(list.version(), seen);
+    /// list.push_back(3);
+    /// assert_ne!(list.version(), seen);
     /// ```
-    pub fn token_at_back(&self, pos: usize) -> Option<ItemToken> {
-        if pos >= self.len() {
-            return None;
-        }
+    pub fn version(&self) -> u64 {
+        self.version
+    }
 
-        // TODO: implement DoubleEndedIterator and use that instead
-        self.token_at(self.len() - pos - 1)
+    /// Name the list, e.g. after its role in the program. The label is included in panic messages
+    /// about invalid tokens and the like, to tell apart lists of the same type. It is carried over
+    /// to clones.
+    ///
+    /// # Examples
+    /// ```should_panic
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut queue = GenerationalTokenList::new();
+    /// queue.set_label("render_queue");
+    /// let item = queue.push_back(1);
+    /// queue.remove(item);
+    /// // Panics with "render_queue: invalid token (slot 0, generation 0)"
+    /// queue.next_token(item);
+    /// ```
+    pub fn set_label(&mut self, label: impl Into<String>) {
+        self.label = Some(label.into());
     }
-}
 
-#[cfg(feature = "iter-mut")]
-pub struct IterWithTokensMut<'a, T>
-where
-    T: 'a,
-{
-    list: &'a mut GenerationalTokenList<T>,
-    next_item: Option<ItemToken>,
-}
+    /// Returns the label set by [`set_label`](Self::set_label), if any.
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
 
-#[cfg(feature = "iter-mut")]
-impl<'a, T> Iterator for IterWithTokensMut<'a, T>
-where
-    T: 'a,
-{
-    type Item = (ItemToken, &'a mut T);
+    /// Returns how many more removals the generation counter allows.
+    ///
+    /// Once the counter is exhausted, removing an item panics rather than wrapping around, so a
+    /// stale token is guaranteed to never alias a new item. Even at a billion removals per second,
+    /// exhausting the 64-bit counter would take centuries, so this is mostly of interest for
+    /// monitoring.
+    pub fn generation_headroom(&self) -> u64 {
+        u64::MAX - self.generation
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        let next_item = self.next_item?;
+    /// Panic about an invalid token, with as much detail as is known about it.
+    fn invalid_token(&self, token: ItemToken) -> ! {
+        #[cfg(feature = "debug-checks")]
+        if let TokenStatus::Removed { op } = self.token_status(token) {
+            self.fail(format_args!(
+                "invalid token {}: item was removed at op #{}",
+                describe(token),
+                op
+            ));
+        }
+        self.fail(format_args!("invalid token {}", describe(token)));
+    }
 
-        if let Some(item) = self.list.arena.get_mut(next_item.index) {
-            self.next_item = item.next;
+    /// Panic with given message, prefixed with the list's label if it has one.
+    fn fail(&self, message: std::fmt::Arguments<'_>) -> ! {
+        match &self.label {
+            Some(label) => panic!("{}: {}", label, message),
+            None => panic!("{}", message),
+        }
+    }
 
-            #[cfg_attr(feature = "iter-mut", allow(unsafe_code))]
-            let data = unsafe { &mut *(&mut item.data as *mut T) };
-            Some((next_item, data))
-        } else {
-            None
+    /// Returns the item identified by given token, or panics if the token is invalid.
+    fn item(&self, token: ItemToken) -> &Item<T> {
+        match self.arena.get(token.index) {
+            Some(item) => item,
+            None => self.invalid_token(token),
         }
     }
-}
 
-#[cfg(feature = "iter-mut")]
-pub struct IterMut<'a, T>
-where
-    T: 'a,
-{
-    inner: IterWithTokensMut<'a, T>,
-}
+    /// Panic unless given token identifies an item that is linked into the list.
+    fn assert_linked(&self, token: ItemToken) {
+        self.item(token);
+        if self.is_unlinked(token) {
+            self.fail(format_args!("item {} is unlinked", describe(token)));
+        }
+    }
 
-#[cfg(feature = "iter-mut")]
-impl<'a, T> Iterator for IterMut<'a, T>
-where
+    /// Panic unless given token identifies an unlinked item.
+    fn assert_unlinked(&self, token: ItemToken) {
+        self.item(token);
+        if !self.is_unlinked(token) {
+            self.fail(format_args!("item {} is not unlinked", describe(token)));
+        }
+    }
+
+    /// Hook called after every structural change.
+    fn structure_changed(&mut self) {
+        self.version = self.version.wrapping_add(1);
+        debug_check!(self);
+        if let Some(notifier) = &self.notifier {
+            notifier.notify();
+        }
+    }
+
+    fn bump_generation(&mut self) {
+        self.generation = self
+            .generation
+            .checked_add(1)
+            .expect("generation counter exhausted");
+    }
+
+    /// Get a reference to the data pointed to by given token, or `None` if token is invalid.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::new();
+    /// let item1 = list.push_back(vec![0, 1, 2]);
+    /// let item2 = list.push_back(vec![3, 4, 5]);
+    /// let item3 = list.push_back(vec![6, 7, 8]);
+    /// assert_eq!(list.get(item2).unwrap(), &vec![3, 4, 5])
+    /// ```
+    pub fn get(&self, token: ItemToken) -> Option<&T> {
+        self.arena.get(token.index).map(|i| &i.data)
+    }
+
+    /// Get a mutable reference to the data pointed to by given token, or `None` if token is invalid.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::new();
+    /// let item1 = list.push_back(vec![0, 1, 2]);
+    /// let item2 = list.push_back(vec![3, 4, 5]);
+    /// let item3 = list.push_back(vec![6, 7, 8]);
+    ///
+    /// let item2_data = list.get_mut(item2).unwrap();
+    /// item2_data.push(100);
+    /// assert_eq!(list.get(item2).unwrap(), &vec![3, 4, 5, 100]);
+    /// ```
+    pub fn get_mut(&mut self, token: ItemToken) -> Option<&mut T> {
+        self.arena.get_mut(token.index).map(|i| &mut i.data)
+    }
+
+    /// Get a pair of mutable (exclusive) references to the items identified by `token1` and `token2`.
+    ///
+    /// # Panics
+    /// Panics if `token1` and `token2` correspond to the same item.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::new();
+    /// let item1 = list.push_back(vec![0, 1, 2]);
+    /// let item2 = list.push_back(vec![3, 4, 5]);
+    /// let item3 = list.push_back(vec![6, 7, 8]);
+    ///
+    /// let (item2_data, item3_data) = list.get2_mut(item2, item3);
+    ///
+    /// item2_data.unwrap().clear();
+    /// item3_data.unwrap().pop();
+    /// assert_eq!(list.get(item2).unwrap(), &vec![]);
+    /// assert_eq!(list.get(item3).unwrap(), &vec![6, 7])
+    /// ```
+    ///
+    /// This will panic:
+    ///
+    /// ```should_panic
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::new();
+    /// let item1 = list.push_back(vec![0, 1, 2]);
+    /// list.push_back(vec![3, 4, 5]);
+    ///
+    /// let (_, _) = list.get2_mut(item1, item1);
+    /// ```
+    ///
+    /// Like `get_mut`, None will be returned if the token is invalid.
+    ///
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::new();
+    /// let item1 = list.push_back(vec![0, 1, 2]);
+    /// let item2 = list.push_back(vec![3, 4, 5]);
+    /// list.remove(item2);
+    ///
+    /// let (item1_data, item2_data) = list.get2_mut(item1, item2);
+    /// assert_eq!(item1_data.unwrap(), &vec![0, 1, 2]);
+    /// assert_eq!(item2_data, None);
+    /// ```
+    pub fn get2_mut(
+        &mut self,
+        token1: ItemToken,
+        token2: ItemToken,
+    ) -> (Option<&mut T>, Option<&mut T>) {
+        let (item1, item2) = self.arena.get2_mut(token1.index, token2.index);
+        (item1.map(|i| &mut i.data), item2.map(|i| &mut i.data))
+    }
+
+    /// Get mutable references to the items identified by all of the given tokens at once, or `None`
+    /// if any token is invalid or two tokens identify the same item.
+    ///
+    /// The tokens are sorted by slot to rule out duplicates, then looked up one by one, so this
+    /// takes O(N log N) time. Handing out several mutable references into the arena needs `unsafe`
+    /// code, hence the `iter-mut` feature; [`get2_mut`](Self::get2_mut) works without it.
+    ///
+    /// This is not a branded (GhostCell) access mode, which the list doesn't offer. Mutable access
+    /// to arbitrary items from many places over a longer time (rather than for one batch of
+    /// tokens) needs no support from the list: use an item type with interior mutability, e.g. a
+    /// branded cell like `GhostCell` from the `ghost-cell` crate, and [`get`](Self::get).
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::new();
+    /// let a = list.push_back(1);
+    /// let b = list.push_back(2);
+    /// let c = list.push_back(3);
+    ///
+    /// let [c_data, a_data, b_data] = list.get_many_mut([c, a, b]).unwrap();
+    /// std::mem::swap(a_data, c_data);
+    /// *b_data *= 10;
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), vec![&3, &20, &1]);
+    ///
+    /// assert_eq!(list.get_many_mut([a, a]), None);
+    /// ```
+    #[cfg(feature = "iter-mut")]
+    pub fn get_many_mut<const N: usize>(&mut self, tokens: [ItemToken; N]) -> Option<[&mut T; N]> {
+        let mut slots = tokens.map(ItemToken::slot);
+        slots.sort_unstable();
+        if slots.windows(2).any(|pair| pair[0] == pair[1]) {
+            return None;
+        }
+        if !tokens.iter().all(|token| self.arena.contains(token.index)) {
+            return None;
+        }
+
+        Some(tokens.map(|token| {
+            let data = &mut self.arena.get_mut(token.index).unwrap().data;
+            // The slots are distinct, so the references never alias
+            #[cfg_attr(feature = "iter-mut", allow(unsafe_code))]
+            let data = unsafe { &mut *(data as *mut T) };
+            data
+        }))
+    }
+
+    /// Returns references to the items identified by the given tokens, in the same order, with
+    /// `None` for each invalid token.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::new();
+    /// let a = list.push_back("a");
+    /// let b = list.push_back("b");
+    /// let c = list.push_back("c");
+    /// list.remove(b);
+    ///
+    /// let selection = [c, b, a];
+    /// assert_eq!(list.get_each(&selection), vec![Some(&"c"), None, Some(&"a")]);
+    /// ```
+    pub fn get_each(&self, tokens: &[ItemToken]) -> Vec<Option<&T>> {
+        tokens.iter().map(|token| self.get(*token)).collect()
+    }
+
+    /// Calls `f` with the token and a mutable reference to the item of each of the given tokens,
+    /// in the given order, skipping invalid tokens. Returns the number of items `f` was called
+    /// for. A token that occurs several times is visited each time.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::from(vec![1, 2, 3]);
+    /// let selection = list.iter_with_tokens().map(|(token, _)| token).collect::<Vec<_>>();
+    /// list.pop_back();
+    ///
+    /// assert_eq!(list.update_many(selection, |_, value| *value *= 10), 2);
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), vec![&10, &20]);
+    /// ```
+    pub fn update_many(
+        &mut self,
+        tokens: impl IntoIterator<Item = ItemToken>,
+        mut f: impl FnMut(ItemToken, &mut T),
+    ) -> usize {
+        let mut updated = 0;
+        for token in tokens {
+            if let Some(data) = self.get_mut(token) {
+                f(token, data);
+                updated += 1;
+            }
+        }
+        updated
+    }
+
+    /// Returns whether the list is empty.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let empty_list = GenerationalTokenList::<String>::new();
+    /// assert!(empty_list.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn push_only_item_with(&mut self, create: impl FnOnce(ItemToken) -> T) -> ItemToken {
+        assert!(self.is_empty());
+        let token = self.new_node_with(|token| Item {
+            data: create(token),
+            previous: None,
+            next: None,
+        });
+        self.head = Some(token);
+        self.tail = Some(token);
+        self.structure_changed();
+        token
+    }
+
+    fn new_node_with(&mut self, create: impl FnOnce(ItemToken) -> Item<T>) -> ItemToken {
+        #[cfg(feature = "metrics")]
+        let capacity = self.arena.capacity();
+        let index = self.arena.insert_with(|index| create(ItemToken { index }));
+        trace_event!(token = ?ItemToken { index }, "insert");
+        #[cfg(feature = "debug-checks")]
+        self.tombstones.record_insertion();
+        #[cfg(feature = "metrics")]
+        self.metrics
+            .record_insertion(self.arena.capacity() != capacity);
+        ItemToken { index }
+    }
+
+    /// Insert the item returned by `create` at the end of the list. Returns a token which
+    /// corresponds to the new item.
+    ///
+    /// This method allows you to add items that know their own token.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::{GenerationalTokenList, ItemToken};
+    /// struct Meta {
+    ///     data: u8,
+    ///     my_token: ItemToken,
+    /// }
+    ///
+    /// let mut list = GenerationalTokenList::new();
+    /// let item1 = list.push_back_with(|token| Meta { data: 1, my_token: token});
+    /// let item2 = list.push_back_with(|token| Meta { data: 2, my_token: token});
+    ///
+    /// let item1_data = list.head().unwrap();
+    /// assert_eq!(item1, item1_data.my_token);
+    /// ```
+    pub fn push_back_with(&mut self, create: impl FnOnce(ItemToken) -> T) -> ItemToken {
+        if self.head.is_none() {
+            return self.push_only_item_with(create);
+        }
+
+        let old_tail = self.tail.unwrap();
+
+        let ret = self.new_node_with(|token| Item {
+            data: create(token),
+            previous: Some(old_tail),
+            next: None,
+        });
+
+        // Fixup old tail
+        self.arena.get_mut(old_tail.index).unwrap().next = Some(ret);
+
+        self.tail = Some(ret);
+        self.structure_changed();
+        ret
+    }
+
+    /// Insert a new item at the end of the list. Returns a token which corresponds to the new item.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::new();
+    /// let item1 = list.push_back("ITEM1");
+    /// assert_eq!(list.get(item1), Some(&"ITEM1"));
+    /// ```
+    pub fn push_back(&mut self, data: T) -> ItemToken {
+        self.push_back_with(|_| data)
+    }
+
+    /// Insert the item returned by `create` at the beginning of the list. Returns a token which
+    /// corresponds to the new item.
+    ///
+    /// This method allows you to add items that know their own token.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::{GenerationalTokenList, ItemToken};
+    /// struct Meta {
+    ///     data: u8,
+    ///     my_token: ItemToken,
+    /// }
+    ///
+    /// let mut list = GenerationalTokenList::new();
+    /// let item1 = list.push_front_with(|token| Meta { data: 1, my_token: token});
+    /// let item2 = list.push_front_with(|token| Meta { data: 2, my_token: token});
+    ///
+    /// let item2_data = list.head().unwrap();
+    /// assert_eq!(item2, item2_data.my_token);
+    /// ```
+    pub fn push_front_with(&mut self, create: impl FnOnce(ItemToken) -> T) -> ItemToken {
+        if self.head.is_none() {
+            return self.push_only_item_with(create);
+        }
+
+        let old_head = self.head.unwrap();
+        let ret = self.new_node_with(|token| Item {
+            data: create(token),
+            previous: None,
+            next: Some(old_head),
+        });
+
+        // Fixup old head
+        self.arena.get_mut(old_head.index).unwrap().previous = Some(ret);
+
+        self.head = Some(ret);
+        self.structure_changed();
+        ret
+    }
+
+    /// Insert a new item at the beginning of the list. Returns a token which corresponds to the new item.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::new();
+    /// list.push_back(":)");
+    /// list.push_back(":/");
+    /// list.push_front(":|");
+    /// assert_eq!(list.head(), Some(&":|"));
+    /// ```
+    pub fn push_front(&mut self, data: T) -> ItemToken {
+        self.push_front_with(|_| data)
+    }
+
+    /// Insert all items from `iter` at the end of the list, in iteration order. Returns the tokens
+    /// corresponding to the new items, in the same order.
+    ///
+    /// Capacity for the items is reserved up front based on the iterator's size hint.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::new();
+    /// list.push_back(0);
+    /// let tokens = list.push_back_many(vec![1, 2, 3]);
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), vec![&0, &1, &2, &3]);
+    /// assert_eq!(list.get(tokens[2]), Some(&3));
+    /// ```
+    pub fn push_back_many(&mut self, iter: impl IntoIterator<Item = T>) -> Vec<ItemToken> {
+        let iter = iter.into_iter();
+        self.prewarm(iter.size_hint().0);
+
+        iter.map(|data| self.push_back(data)).collect()
+    }
+
+    /// Insert all items from `iter` at the beginning of the list. The items keep their iteration
+    /// order, i.e. the first item yielded by `iter` becomes the new head. Returns the tokens
+    /// corresponding to the new items, in the same order.
+    ///
+    /// Capacity for the items is reserved up front based on the iterator's size hint.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::new();
+    /// list.push_back(3);
+    /// let tokens = list.push_front_many(vec![0, 1, 2]);
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), vec![&0, &1, &2, &3]);
+    /// assert_eq!(list.head_token(), Some(tokens[0]));
+    /// ```
+    pub fn push_front_many(&mut self, iter: impl IntoIterator<Item = T>) -> Vec<ItemToken> {
+        let iter = iter.into_iter();
+        self.prewarm(iter.size_hint().0);
+
+        let mut tokens = Vec::with_capacity(iter.size_hint().0);
+        for data in iter {
+            let token = match tokens.last() {
+                None => self.push_front(data),
+                Some(&previous) => self.insert_after(previous, data),
+            };
+            tokens.push(token);
+        }
+        tokens
+    }
+
+    /// Append `n` items returned by `create`, which is passed the token of each new item (as with
+    /// [`push_back_with`](Self::push_back_with)). Returns the tokens of the new items, in order.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::{GenerationalTokenList, ItemToken};
+    /// struct Worker {
+    ///     me: ItemToken,
+    /// }
+    ///
+    /// let mut pool = GenerationalTokenList::new();
+    /// let workers = pool.extend_with(4, |me| Worker { me });
+    /// assert_eq!(workers.len(), 4);
+    /// assert!(workers.iter().all(|&token| pool[token].me == token));
+    /// ```
+    pub fn extend_with(
+        &mut self,
+        n: usize,
+        mut create: impl FnMut(ItemToken) -> T,
+    ) -> Vec<ItemToken> {
+        self.prewarm(n);
+        (0..n).map(|_| self.push_back_with(&mut create)).collect()
+    }
+
+    /// Merge `iter`, whose items must be sorted, into the list, which must be sorted as well, in a
+    /// single pass. New items are placed after existing items that compare equal to them. Returns
+    /// the tokens of the new items, in the order they were yielded.
+    ///
+    /// This takes time proportional to the length of the list plus the number of new items, where
+    /// inserting them one by one at their sorted position would scan the list for each of them. If
+    /// either the list or `iter` isn't sorted, the items end up in an unspecified order.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::from(vec![10, 20, 30]);
+    /// let tokens = list.extend_sorted(vec![5, 20, 25, 40]);
+    /// assert_eq!(
+    ///     list.iter().collect::<Vec<_>>(),
+    ///     vec![&5, &10, &20, &20, &25, &30, &40]
+    /// );
+    /// assert_eq!(list.next_token(tokens[1]), list.token_at(4));
+    /// ```
+    pub fn extend_sorted(&mut self, iter: impl IntoIterator<Item = T>) -> Vec<ItemToken>
+    where
+        T: Ord,
+    {
+        self.extend_sorted_by(iter, T::cmp)
+    }
+
+    /// Merge `iter` into the list like [`extend_sorted`](Self::extend_sorted), with both sorted
+    /// according to `compare`.
+    pub fn extend_sorted_by(
+        &mut self,
+        iter: impl IntoIterator<Item = T>,
+        mut compare: impl FnMut(&T, &T) -> Ordering,
+    ) -> Vec<ItemToken> {
+        let mut tokens = Vec::new();
+        let mut next = self.head;
+        for data in iter {
+            while let Some(token) = next {
+                if compare(&self[token], &data) == Ordering::Greater {
+                    break;
+                }
+                next = self.next_token(token);
+            }
+
+            let token = match next {
+                Some(before) => self.insert_before(before, data),
+                None => self.push_back(data),
+            };
+            tokens.push(token);
+        }
+        tokens
+    }
+
+    /// Resize the list to `new_len` items, like [`Vec::resize_with`]: removes items from the end if
+    /// the list is longer, or appends items returned by `f` if it is shorter. Removal stops early at
+    /// an item protected by a [`RemovalGuard`](crate::RemovalGuard).
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::from(vec![1, 2, 3]);
+    /// list.resize_with(1, Default::default);
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), vec![&1]);
+    ///
+    /// let mut next = 1;
+    /// list.resize_with(3, || {
+    ///     next *= 2;
+    ///     next
+    /// });
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), vec![&1, &2, &4]);
+    /// ```
+    pub fn resize_with(&mut self, new_len: usize, mut f: impl FnMut() -> T) {
+        let len = self.len();
+        if new_len < len {
+            self.pop_back_n(len - new_len);
+        } else {
+            self.push_back_many((len..new_len).map(|_| f()));
+        }
+    }
+
+    /// Compare the positions of the items identified by given tokens in the list. Returns `None` if
+    /// either token is invalid or belongs to an unlinked item.
+    ///
+    /// This walks the list from `a` towards the back, so it takes time proportional to the distance
+    /// between the items (or to the distance from `a` to the end of the list, if `b` comes first).
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::cmp::Ordering;
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::new();
+    /// let b = list.push_back("b");
+    /// let a = list.push_front("a");
+    ///
+    /// assert_eq!(list.compare_tokens(a, b), Some(Ordering::Less));
+    /// assert_eq!(list.compare_tokens(b, a), Some(Ordering::Greater));
+    /// assert_eq!(list.compare_tokens(a, a), Some(Ordering::Equal));
+    ///
+    /// list.remove(a);
+    /// assert_eq!(list.compare_tokens(a, b), None);
+    /// ```
+    pub fn compare_tokens(&self, a: ItemToken, b: ItemToken) -> Option<Ordering> {
+        if !self.arena.contains(a.index)
+            || !self.arena.contains(b.index)
+            || self.is_unlinked(a)
+            || self.is_unlinked(b)
+        {
+            return None;
+        }
+
+        let mut current = a;
+        loop {
+            if current == b {
+                return Some(if a == b {
+                    Ordering::Equal
+                } else {
+                    Ordering::Less
+                });
+            }
+            match self.next_token(current) {
+                Some(next) => current = next,
+                None => return Some(Ordering::Greater),
+            }
+        }
+    }
+
+    /// Sort `tokens` according to the position of their items in the list. Invalid tokens and tokens
+    /// of unlinked items are moved to the end (keeping their relative order). Returns the number of
+    /// tokens that have a position, i.e. the length of the sorted prefix.
+    ///
+    /// This takes a single pass over the list, so it is much cheaper than looking up the position
+    /// of every token individually.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::new();
+    /// let a = list.push_back("a");
+    /// let c = list.push_back("c");
+    /// let b = list.insert_after(a, "b");
+    /// let stale = list.push_back("d");
+    /// list.remove(stale);
+    ///
+    /// let mut tokens = vec![c, stale, a, b];
+    /// let valid = list.sort_tokens_by_position(&mut tokens);
+    /// assert_eq!(valid, 3);
+    /// assert_eq!(tokens, vec![a, b, c, stale]);
+    /// ```
+    pub fn sort_tokens_by_position(&self, tokens: &mut [ItemToken]) -> usize {
+        let mut positions = vec![usize::MAX; self.arena.capacity()];
+        for (position, (token, _)) in self.iter_with_tokens().enumerate() {
+            positions[token.slot()] = position;
+        }
+
+        let position_of = |token: &ItemToken| {
+            if self.arena.contains(token.index) {
+                positions[token.slot()]
+            } else {
+                usize::MAX
+            }
+        };
+
+        tokens.sort_by_key(position_of);
+        tokens
+            .iter()
+            .take_while(|token| position_of(token) != usize::MAX)
+            .count()
+    }
+
+    /// Sort the list with a key extraction function, calling `f` exactly once per item. The sort is
+    /// stable: items with equal keys keep their relative order. Tokens remain valid, as items are
+    /// only relinked, not moved.
+    ///
+    /// This allocates a temporary buffer of keys, and is the better choice when computing keys is
+    /// expensive compared to comparing them (e.g. normalizing strings). Unlinked items are left
+    /// alone.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::new();
+    /// let b = list.push_back("B");
+    /// list.push_back("c");
+    /// let a = list.push_back("a");
+    ///
+    /// list.sort_by_cached_key(|s| s.to_lowercase());
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), vec![&"a", &"B", &"c"]);
+    /// assert_eq!(list.head_token(), Some(a));
+    /// assert_eq!(list.get(b), Some(&"B"));
+    /// ```
+    pub fn sort_by_cached_key<K: Ord>(&mut self, mut f: impl FnMut(&T) -> K) {
+        trace_span!("sort_by_cached_key", len = self.len());
+        let mut keyed: Vec<_> = self
+            .iter_with_tokens()
+            .map(|(token, data)| (f(data), token))
+            .collect();
+        keyed.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        let tokens: Vec<_> = keyed.into_iter().map(|(_, token)| token).collect();
+        self.relink_in_order(&tokens);
+    }
+
+    /// Move all items of `other` into this list, alternating with the existing items (`self[0]`,
+    /// `other[0]`, `self[1]`, `other[1]`, ...). Once either list runs out, the remainder of the
+    /// longer one follows in order. Returns the new tokens of the items moved from `other`, in their
+    /// original order.
+    ///
+    /// Tokens of items already in `self` remain valid; tokens obtained from `other` do not carry
+    /// over.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut evens = GenerationalTokenList::new();
+    /// evens.push_back(0);
+    /// evens.push_back(2);
+    ///
+    /// let mut odds = GenerationalTokenList::new();
+    /// odds.push_back(1);
+    /// odds.push_back(3);
+    /// odds.push_back(5);
+    ///
+    /// let moved = evens.interleave(odds);
+    /// assert_eq!(evens.iter().collect::<Vec<_>>(), vec![&0, &1, &2, &3, &5]);
+    /// assert_eq!(evens.get(moved[2]), Some(&5));
+    /// ```
+    pub fn interleave(&mut self, other: Self) -> Vec<ItemToken> {
+        self.prewarm(other.len());
+
+        let mut tokens = Vec::with_capacity(other.len());
+        let mut current = self.head;
+        for data in other {
+            let token = match current {
+                Some(current_token) => {
+                    let token = self.insert_after(current_token, data);
+                    current = self.next_token(token);
+                    token
+                }
+                None => self.push_back(data),
+            };
+            tokens.push(token);
+        }
+        tokens
+    }
+
+    /// Remove the item identified by given token and move all items of `other` into its place, in
+    /// order. Returns the new tokens of the items moved from `other`, in their original order.
+    ///
+    /// The removed item is dropped; tokens obtained from `other` do not carry over.
+    ///
+    /// # Panics
+    /// Panics if `token` is an invalid token, an unlinked item or protected by a
+    /// [`RemovalGuard`](crate::RemovalGuard).
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut program = GenerationalTokenList::new();
+    /// program.push_back("start");
+    /// let call = program.push_back("call inc");
+    /// program.push_back("end");
+    ///
+    /// let mut body = GenerationalTokenList::new();
+    /// body.push_back("load");
+    /// body.push_back("add 1");
+    /// body.push_back("store");
+    ///
+    /// let inlined = program.replace_with_list(call, body);
+    /// assert_eq!(
+    ///     program.iter().collect::<Vec<_>>(),
+    ///     vec![&"start", &"load", &"add 1", &"store", &"end"]
+    /// );
+    /// assert_eq!(program.get(inlined[1]), Some(&"add 1"));
+    /// assert_eq!(program.get(call), None);
+    /// ```
+    pub fn replace_with_list(&mut self, token: ItemToken, other: Self) -> Vec<ItemToken> {
+        self.assert_linked(token);
+        if self.is_guarded(token) {
+            self.fail(format_args!("item {} is guarded", describe(token)));
+        }
+
+        let previous = self.prev_token(token);
+        let next = self.next_token(token);
+        self.remove(token);
+        match (previous, next) {
+            (Some(previous), _) => self.insert_after_many(previous, other),
+            (None, Some(next)) => self.insert_before_many(next, other),
+            (None, None) => self.push_back_many(other),
+        }
+    }
+
+    /// Split the list into `n` consecutive pieces whose lengths differ by at most one (earlier pieces
+    /// are the longer ones). Always returns exactly `n` lists, some of which are empty if the list
+    /// has fewer than `n` items.
+    ///
+    /// The first piece is the original list with the remaining items removed, so tokens for its
+    /// items stay valid. Items in the other pieces get new tokens.
+    ///
+    /// # Panics
+    /// Panics if `n` is zero, or if any item is protected by a
+    /// [`RemovalGuard`](crate::RemovalGuard), as guarded items can't be moved to another piece.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::new();
+    /// let first = list.push_back(0);
+    /// list.push_back_many(1..7);
+    ///
+    /// let pieces = list.split_into(3);
+    /// let contents = pieces
+    ///     .iter()
+    ///     .map(|piece| piece.iter().copied().collect::<Vec<_>>())
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(contents, vec![vec![0, 1, 2], vec![3, 4], vec![5, 6]]);
+    /// assert_eq!(pieces[0].get(first), Some(&0));
+    /// ```
+    pub fn split_into(mut self, n: usize) -> Vec<Self> {
+        assert!(n > 0, "cannot split a list into zero pieces");
+        assert!(
+            !self.has_guards(),
+            "cannot split a list while items are guarded"
+        );
+
+        let base = self.len() / n;
+        let extra = self.len() % n;
+
+        // Split from the back so that the first piece can keep the original arena
+        let mut pieces = Vec::with_capacity(n);
+        for i in (1..n).rev() {
+            let piece_len = if i < extra { base + 1 } else { base };
+            pieces.push(self.split_off_back(piece_len));
+        }
+        pieces.push(self);
+        pieces.reverse();
+        pieces
+    }
+
+    /// Split the list between each pair of adjacent items for which `split` returns `true`. Returns
+    /// the pieces in order; a list without any split points (including an empty list) comes back
+    /// as a single piece.
+    ///
+    /// `split` is called with each pair of neighboring items, in list order. As with
+    /// [`split_into`](Self::split_into), the first piece is the original list with the remaining
+    /// items removed, so tokens for its items stay valid. Items in the other pieces get new tokens.
+    ///
+    /// # Panics
+    /// Panics if any item is protected by a [`RemovalGuard`](crate::RemovalGuard), as guarded
+    /// items can't be moved to another piece.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let timestamps = GenerationalTokenList::from(vec![1, 2, 3, 10, 11, 30]);
+    ///
+    /// let bursts = timestamps.split_when(|previous, next| next - previous > 5);
+    /// let contents = bursts
+    ///     .iter()
+    ///     .map(|burst| burst.iter().copied().collect::<Vec<_>>())
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(contents, vec![vec![1, 2, 3], vec![10, 11], vec![30]]);
+    /// ```
+    pub fn split_when(mut self, mut split: impl FnMut(&T, &T) -> bool) -> Vec<Self> {
+        assert!(
+            !self.has_guards(),
+            "cannot split a list while items are guarded"
+        );
+
+        let mut lengths = vec![];
+        let mut length = 0;
+        let mut previous = None;
+        for data in self.iter() {
+            if previous.is_some_and(|previous| split(previous, data)) {
+                lengths.push(length);
+                length = 0;
+            }
+            length += 1;
+            previous = Some(data);
+        }
+        lengths.push(length);
+
+        // Split from the back so that the first piece can keep the original arena
+        let mut pieces = Vec::with_capacity(lengths.len());
+        for length in lengths.into_iter().skip(1).rev() {
+            pieces.push(self.split_off_back(length));
+        }
+        pieces.push(self);
+        pieces.reverse();
+        pieces
+    }
+
+    /// Split the list into `n` consecutive segments of mutable references, whose lengths differ by
+    /// at most one (earlier segments are the longer ones), like [`split_into`](Self::split_into)
+    /// but in place. Always returns exactly `n` segments, each a `Vec` of (item token, mutable
+    /// reference to item data) pairs in list order.
+    ///
+    /// The segments don't overlap, so they can be handed to different threads, e.g. with
+    /// [`std::thread::scope`]. This takes time proportional to the capacity of the list.
+    ///
+    /// # Panics
+    /// Panics if `n` is zero.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = (0..5).collect::<GenerationalTokenList<_>>();
+    ///
+    /// std::thread::scope(|scope| {
+    ///     for (i, segment) in list.split_mut(2).into_iter().enumerate() {
+    ///         scope.spawn(move || {
+    ///             for (_, item) in segment {
+    ///                 *item += 10 * (i + 1);
+    ///             }
+    ///         });
+    ///     }
+    /// });
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), vec![&10, &11, &12, &23, &24]);
+    /// ```
+    pub fn split_mut(&mut self, n: usize) -> Vec<Vec<(ItemToken, &mut T)>> {
+        assert!(n > 0, "cannot split a list into zero segments");
+
+        // The arena only hands out mutable references in slot order, so sort them into list order
+        // by position
+        let mut positions = vec![usize::MAX; self.arena.capacity()];
+        for (position, (token, _)) in self.iter_with_tokens().enumerate() {
+            positions[token.slot()] = position;
+        }
+        let len = self.len();
+        let mut items: Vec<Option<(ItemToken, &mut T)>> = (0..len).map(|_| None).collect();
+        for (index, item) in self.arena.iter_mut() {
+            let token = ItemToken { index };
+            if let Some(slot) = items.get_mut(positions[token.slot()]) {
+                *slot = Some((token, &mut item.data));
+            }
+        }
+
+        let base = len / n;
+        let extra = len % n;
+        let mut items = items.into_iter().map(Option::unwrap);
+        (0..n)
+            .map(|i| {
+                let segment_len = if i < extra { base + 1 } else { base };
+                items.by_ref().take(segment_len).collect()
+            })
+            .collect()
+    }
+
+    /// Remove the last `n` items and return them (in order) as a new list. The list must not have
+    /// any guarded items.
+    fn split_off_back(&mut self, n: usize) -> Self {
+        let mut items = self.pop_back_n(n);
+        items.reverse();
+
+        let mut list = Self::with_capacity(items.len());
+        list.push_back_many(items);
+        list
+    }
+
+    /// Returns the tokens from `range.start()` to `range.end()` (inclusive), in list order.
+    ///
+    /// Panics if either token is invalid or if the end is not reachable from the start.
+    fn range_tokens(&self, range: RangeInclusive<ItemToken>) -> Vec<ItemToken> {
+        let (first, last) = range.into_inner();
+        self.item(last);
+
+        let mut tokens = vec![first];
+        let mut current = first;
+        while current != last {
+            current = self
+                .next_token(current)
+                .expect("end of range is not reachable from its start");
+            tokens.push(current);
+        }
+        tokens
+    }
+
+    /// Insert the item returned by `create` after the item identified by given token. Returns a token
+    /// which corresponds to the new item.
+    ///
+    /// This method allows you to add items that know their own token.
+    ///
+    /// # Panics
+    /// Panics if `after` is an invalid token or an unlinked item.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::{GenerationalTokenList, ItemToken};
+    /// struct Meta {
+    ///     data: u8,
+    ///     my_token: ItemToken,
+    /// }
+    ///
+    /// let mut list = GenerationalTokenList::new();
+    /// let head = list.push_back_with(|token| Meta { data: 0, my_token: token });
+    /// list.push_back_with(|token| Meta { data: 2, my_token: token });
+    ///
+    /// list.insert_after_with(head, |token| Meta { data: 1, my_token: token });
+    ///
+    /// assert_eq!(list.into_iter().map(|m| m.data).collect::<Vec<_>>(), vec![0, 1, 2]);
+    /// ```
+    pub fn insert_after_with(
+        &mut self,
+        after: ItemToken,
+        create: impl FnOnce(ItemToken) -> T,
+    ) -> ItemToken {
+        self.assert_linked(after);
+
+        let item_token_following_after = self.item(after).next;
+        match item_token_following_after {
+            // `after` is in tail position
+            None => self.push_back_with(create),
+            Some(item_token_following_after) => {
+                // `after` is not in tail position, which means we are inserting between two items
+                let ret = self.new_node_with(|token| Item {
+                    data: create(token),
+                    previous: Some(after),
+                    next: Some(item_token_following_after),
+                });
+
+                let (after_item, item_following_after) = self
+                    .arena
+                    .get2_mut(after.index, item_token_following_after.index);
+
+                after_item.unwrap().next = Some(ret);
+                item_following_after.unwrap().previous = Some(ret);
+                self.structure_changed();
+
+                ret
+            }
+        }
+    }
+
+    /// Insert a new item after the item identified by given token.
+    ///
+    /// # Panics
+    /// Panics if `after` is an invalid token or an unlinked item.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::<i32>::new();
+    /// let item1 = list.push_back(10);
+    /// list.push_back(20);
+    /// list.insert_after(item1, 300);
+    /// assert_eq!(list.into_iter().collect::<Vec<_>>(), vec![10, 300, 20])
+    /// ```
+    pub fn insert_after(&mut self, after: ItemToken, data: T) -> ItemToken {
+        self.insert_after_with(after, |_| data)
+    }
+
+    /// Insert the item returned by `create` before the item identified by given token. Returns a token
+    /// which corresponds to the new item.
+    ///
+    /// This method allows you to add items that know their own token.
+    ///
+    /// # Panics
+    /// Panics if `before` is an invalid token or an unlinked item.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::{GenerationalTokenList, ItemToken};
+    /// struct Meta {
+    ///     data: u8,
+    ///     my_token: ItemToken,
+    /// }
+    ///
+    /// let mut list = GenerationalTokenList::new();
+    /// list.push_back_with(|token| Meta { data: 0, my_token: token });
+    /// let tail = list.push_back_with(|token| Meta { data: 2, my_token: token });
+    ///
+    /// list.insert_before_with(tail, |token| Meta { data: 1, my_token: token });
+    ///
+    /// assert_eq!(list.into_iter().map(|m| m.data).collect::<Vec<_>>(), vec![0, 1, 2]);
+    /// ```
+    pub fn insert_before_with(
+        &mut self,
+        before: ItemToken,
+        create: impl FnOnce(ItemToken) -> T,
+    ) -> ItemToken {
+        self.assert_linked(before);
+
+        let item_token_preceding_before = self.item(before).previous;
+        match item_token_preceding_before {
+            // `before` is in head position
+            None => self.push_front_with(create),
+            Some(item_token_preceding_before) => {
+                // `before` is not in head position, which means we are inserting between two items
+                let ret = self.new_node_with(|token| Item {
+                    data: create(token),
+                    previous: Some(item_token_preceding_before),
+                    next: Some(before),
+                });
+
+                let (before_item, item_preceding_before) = self
+                    .arena
+                    .get2_mut(before.index, item_token_preceding_before.index);
+
+                before_item.unwrap().previous = Some(ret);
+                item_preceding_before.unwrap().next = Some(ret);
+                self.structure_changed();
+
+                ret
+            }
+        }
+    }
+
+    /// Insert a new item before the item identified by given token.
+    ///
+    /// # Panics
+    /// Panics if `before` is an invalid token or an unlinked item.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::<i32>::new();
+    /// list.push_back(20);
+    /// list.push_back(100);
+    /// let item3 = list.push_back(10);
+    /// list.insert_before(item3, 300);
+    /// assert_eq!(list.into_iter().collect::<Vec<_>>(), vec![20, 100, 300, 10])
+    /// ```
+    pub fn insert_before(&mut self, before: ItemToken, data: T) -> ItemToken {
+        self.insert_before_with(before, |_| data)
+    }
+
+    /// Insert all items from `iter` after the item identified by given token, in iteration order.
+    /// Returns the tokens corresponding to the new items, in the same order.
+    ///
+    /// # Panics
+    /// Panics if `after` is an invalid token or an unlinked item.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::<i32>::new();
+    /// let anchor = list.push_back(0);
+    /// list.push_back(4);
+    /// let tokens = list.insert_after_many(anchor, vec![1, 2, 3]);
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), vec![&0, &1, &2, &3, &4]);
+    /// assert_eq!(list.next_token(anchor), Some(tokens[0]));
+    /// ```
+    pub fn insert_after_many(
+        &mut self,
+        after: ItemToken,
+        iter: impl IntoIterator<Item = T>,
+    ) -> Vec<ItemToken> {
+        self.assert_linked(after);
+
+        let iter = iter.into_iter();
+        self.prewarm(iter.size_hint().0);
+
+        let mut last = after;
+        iter.map(|data| {
+            last = self.insert_after(last, data);
+            last
+        })
+        .collect()
+    }
+
+    /// Insert all items from `iter` before the item identified by given token, in iteration order
+    /// (i.e. the last item yielded by `iter` ends up directly before `before`). Returns the tokens
+    /// corresponding to the new items, in the same order.
+    ///
+    /// # Panics
+    /// Panics if `before` is an invalid token or an unlinked item.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::<i32>::new();
+    /// list.push_back(0);
+    /// let anchor = list.push_back(4);
+    /// let tokens = list.insert_before_many(anchor, vec![1, 2, 3]);
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), vec![&0, &1, &2, &3, &4]);
+    /// assert_eq!(list.prev_token(anchor), Some(tokens[2]));
+    /// ```
+    pub fn insert_before_many(
+        &mut self,
+        before: ItemToken,
+        iter: impl IntoIterator<Item = T>,
+    ) -> Vec<ItemToken> {
+        self.assert_linked(before);
+
+        let iter = iter.into_iter();
+        self.prewarm(iter.size_hint().0);
+
+        let mut tokens: Vec<ItemToken> = Vec::with_capacity(iter.size_hint().0);
+        for data in iter {
+            let token = match tokens.last() {
+                None => self.insert_before(before, data),
+                Some(&previous) => self.insert_after(previous, data),
+            };
+            tokens.push(token);
+        }
+        tokens
+    }
+
+    /// Returns an iterator of references to item data in the list.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::<i32>::new();
+    /// list.push_back(5);
+    /// list.push_back(6);
+    /// list.push_back(7);
+    ///
+    /// let i = list.iter().enumerate().collect::<Vec<_>>();
+    /// assert_eq!(i, vec![(0, &5), (1, &6), (2, &7)]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            inner: self.iter_with_tokens(),
+        }
+    }
+
+    /// Returns an iterator of pairs of (item tokens, references to item data) in the list.
+    pub fn iter_with_tokens(&self) -> IterWithTokens<'_, T> {
+        #[cfg(feature = "metrics")]
+        self.metrics.record_traversal();
+        IterWithTokens {
+            list: self,
+            next_item: self.head,
+        }
+    }
+
+    /// Returns an iterator of triples of (positions, item tokens, references to item data) in the
+    /// list.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::new();
+    /// let a = list.push_back("a");
+    /// let b = list.push_back("b");
+    ///
+    /// let i = list.enumerate_with_tokens().collect::<Vec<_>>();
+    /// assert_eq!(i, vec![(0, a, &"a"), (1, b, &"b")]);
+    /// ```
+    pub fn enumerate_with_tokens(&self) -> EnumerateWithTokens<'_, T> {
+        EnumerateWithTokens {
+            inner: self.iter_with_tokens(),
+            position: 0,
+        }
+    }
+
+    /// Returns an iterator of pairs of (positions, references to item data) in the list, where the
+    /// position tells whether an item is the first, the last, the only one or somewhere in the
+    /// middle.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::{GenerationalTokenList, Position};
+    /// let list = GenerationalTokenList::from(vec!["a", "b", "c"]);
+    ///
+    /// let mut rendered = String::from("[");
+    /// for (position, item) in list.with_position() {
+    ///     rendered.push_str(item);
+    ///     if matches!(position, Position::First | Position::Middle) {
+    ///         rendered.push_str(", ");
+    ///     }
+    /// }
+    /// rendered.push(']');
+    /// assert_eq!(rendered, "[a, b, c]");
+    ///
+    /// let single = GenerationalTokenList::from(vec!["a"]);
+    /// assert_eq!(single.with_position().next(), Some((Position::Only, &"a")));
+    /// ```
+    pub fn with_position(&self) -> WithPosition<'_, T> {
+        let mut inner = self.iter();
+        let next = inner.next();
+        WithPosition {
+            inner,
+            next,
+            first: true,
+        }
+    }
+
+    /// Returns an iterator over runs of adjacent items for which `same_chunk` returns `true`. Each
+    /// run is yielded as a `Vec` of (item token, reference to item data) pairs.
+    ///
+    /// `same_chunk` is called with each pair of neighboring items, in list order. This is the linked
+    /// list equivalent of [`slice::chunk_by`](https://doc.rust-lang.org/std/primitive.slice.html#method.chunk_by).
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::<i32>::new();
+    /// for i in [1, 1, 2, 3, 3, 3, 1] {
+    ///     list.push_back(i);
+    /// }
+    ///
+    /// let runs = list
+    ///     .chunk_by(|a, b| a == b)
+    ///     .map(|run| (*run[0].1, run.len()))
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(runs, vec![(1, 2), (2, 1), (3, 3), (1, 1)]);
+    /// ```
+    pub fn chunk_by<F>(&self, same_chunk: F) -> ChunkBy<'_, T, F>
+    where
+        F: FnMut(&T, &T) -> bool,
+    {
+        ChunkBy {
+            inner: self.iter_with_tokens(),
+            pending: None,
+            same_chunk,
+        }
+    }
+
+    /// Returns an iterator of mutable (exclusive) references to item data in the list.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::<i32>::new();
+    /// list.push_back(5);
+    /// list.push_back(6);
+    /// list.push_back(7);
+    ///
+    /// for i in list.iter_mut() {
+    ///     *i += 10;
+    /// }
+    ///
+    /// assert_eq!(list.into_iter().collect::<Vec<_>>(), vec![15, 16, 17]);
+    /// ```
+    #[cfg(feature = "iter-mut")]
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            inner: self.iter_with_tokens_mut(),
+        }
+    }
+
+    /// Returns an iterator of pairs of (item tokens, mutable (exclusive) references to item data) in the list.
+    #[cfg(feature = "iter-mut")]
+    pub fn iter_with_tokens_mut(&mut self) -> IterWithTokensMut<'_, T> {
+        let head = self.head;
+        IterWithTokensMut {
+            list: self,
+            next_item: head,
+        }
+    }
+
+    /// Calls `f` on each pair of (item token, reference to item data) in the list, stopping as soon
+    /// as `f` returns `ControlFlow::Break`. Returns the token of the item that caused the early exit
+    /// along with the break value, or `ControlFlow::Continue(())` if every item was visited.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// use std::ops::ControlFlow;
+    ///
+    /// let mut list = GenerationalTokenList::<i32>::new();
+    /// list.push_back(1);
+    /// let negative = list.push_back(-2);
+    /// list.push_back(3);
+    ///
+    /// let result = list.try_for_each_with_tokens(|_, value| {
+    ///     if *value < 0 {
+    ///         ControlFlow::Break("negative")
+    ///     } else {
+    ///         ControlFlow::Continue(())
+    ///     }
+    /// });
+    /// assert_eq!(result, ControlFlow::Break((negative, "negative")));
+    /// ```
+    pub fn try_for_each_with_tokens<B>(
+        &self,
+        mut f: impl FnMut(ItemToken, &T) -> ControlFlow<B>,
+    ) -> ControlFlow<(ItemToken, B)> {
+        for (token, data) in self.iter_with_tokens() {
+            if let ControlFlow::Break(b) = f(token, data) {
+                return ControlFlow::Break((token, b));
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    /// Like [`try_for_each_with_tokens`](Self::try_for_each_with_tokens), but `f` receives a
+    /// mutable (exclusive) reference to item data.
+    ///
+    /// This method does not require the `iter-mut` feature.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// use std::ops::ControlFlow;
+    ///
+    /// let mut list = GenerationalTokenList::<i32>::new();
+    /// list.push_back(1);
+    /// list.push_back(2);
+    /// let stop = list.push_back(0);
+    /// list.push_back(4);
+    ///
+    /// let result = list.try_for_each_with_tokens_mut(|_, value| {
+    ///     if *value == 0 {
+    ///         return ControlFlow::Break(());
+    ///     }
+    ///     *value *= 10;
+    ///     ControlFlow::Continue(())
+    /// });
+    /// assert_eq!(result, ControlFlow::Break((stop, ())));
+    /// assert_eq!(list.into_iter().collect::<Vec<_>>(), vec![10, 20, 0, 4]);
+    /// ```
+    pub fn try_for_each_with_tokens_mut<B>(
+        &mut self,
+        mut f: impl FnMut(ItemToken, &mut T) -> ControlFlow<B>,
+    ) -> ControlFlow<(ItemToken, B)> {
+        let mut next_item = self.head;
+        while let Some(token) = next_item {
+            let item = self.arena.get_mut(token.index).unwrap();
+            next_item = item.next;
+            if let ControlFlow::Break(b) = f(token, &mut item.data) {
+                return ControlFlow::Break((token, b));
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    /// Folds every item into an accumulator by calling `f` with the accumulator, the item's token
+    /// and a reference to the item, in list order. Returns the final accumulator.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::new();
+    /// list.push_back(3);
+    /// let largest = list.push_back(7);
+    /// list.push_back(5);
+    ///
+    /// let (max_token, _) = list.fold_with_tokens((None, i32::MIN), |(best, max), token, &value| {
+    ///     if value > max {
+    ///         (Some(token), value)
+    ///     } else {
+    ///         (best, max)
+    ///     }
+    /// });
+    /// assert_eq!(max_token, Some(largest));
+    /// ```
+    pub fn fold_with_tokens<B>(&self, init: B, mut f: impl FnMut(B, ItemToken, &T) -> B) -> B {
+        self.iter_with_tokens()
+            .fold(init, |acc, (token, data)| f(acc, token, data))
+    }
+
+    /// Like [`fold_with_tokens`](Self::fold_with_tokens), but `f` receives a mutable (exclusive)
+    /// reference to item data.
+    ///
+    /// This method does not require the `iter-mut` feature.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::from(vec![1, 2, 3]);
+    ///
+    /// // Replace each item with the running total
+    /// let total = list.fold_with_tokens_mut(0, |total, _, value| {
+    ///     *value += total;
+    ///     *value
+    /// });
+    /// assert_eq!(total, 6);
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), vec![&1, &3, &6]);
+    /// ```
+    pub fn fold_with_tokens_mut<B>(
+        &mut self,
+        init: B,
+        mut f: impl FnMut(B, ItemToken, &mut T) -> B,
+    ) -> B {
+        let mut acc = init;
+        let mut next_item = self.head;
+        while let Some(token) = next_item {
+            let item = self.arena.get_mut(token.index).unwrap();
+            next_item = item.next;
+            acc = f(acc, token, &mut item.data);
+        }
+        acc
+    }
+
+    /// Returns `true` if both lists have the same length and `eq` returns `true` for each pair of
+    /// items at the same position. Useful when `T` doesn't implement `PartialEq`, or when items
+    /// should be compared some other way.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let measured = GenerationalTokenList::from(vec![0.1_f64 + 0.2, 1.0]);
+    /// let expected = GenerationalTokenList::from(vec![0.3, 1.0]);
+    ///
+    /// assert!(measured.eq_by(&expected, |a, b| (a - b).abs() < 1e-9));
+    /// assert!(!measured.eq_by(&expected, |a, b| a == b));
+    /// ```
+    pub fn eq_by<U>(
+        &self,
+        other: &GenerationalTokenList<U>,
+        mut eq: impl FnMut(&T, &U) -> bool,
+    ) -> bool {
+        self.len() == other.len() && self.iter().zip(other.iter()).all(|(a, b)| eq(a, b))
+    }
+
+    /// Calls `f` with the list and the token of each item, in list order, while allowing `f` to
+    /// modify the list in any way.
+    ///
+    /// The order is captured up front: every item that is in the list when this is called gets
+    /// visited once, in the order the items had then, unless it has been removed or unlinked by the
+    /// time its turn comes. Items inserted by `f` are not visited, and moving an item does not
+    /// change when it is visited.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::from(vec![1, 2, 4, 5, 7]);
+    ///
+    /// // Remove each even item along with its successor, and duplicate each odd one
+    /// list.for_each_token_snapshot(|list, token| {
+    ///     if list[token] % 2 == 0 {
+    ///         if let Some(next) = list.next_token(token) {
+    ///             list.remove(next);
+    ///         }
+    ///         list.remove(token);
+    ///     } else {
+    ///         list.insert_after(token, list[token]);
+    ///     }
+    /// });
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), vec![&1, &1, &5, &5, &7, &7]);
+    /// ```
+    pub fn for_each_token_snapshot(&mut self, mut f: impl FnMut(&mut Self, ItemToken)) {
+        let tokens: Vec<_> = self.iter_with_tokens().map(|(token, _)| token).collect();
+        for token in tokens {
+            if self.arena.contains(token.index) && !self.is_unlinked(token) {
+                f(self, token);
+            }
+        }
+    }
+
+    /// Returns the token corresponding to the item that is after that identified by `token`. Returns
+    /// `None` if no item comes after it (i.e. it is the tail).
+    ///
+    /// # Panics
+    /// Panics if `token` is invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::<i32>::new();
+    /// let item1 = list.push_back(5);
+    /// let item2 = list.push_back(6);
+    /// list.push_back(7);
+    ///
+    /// assert_eq!(list.next_token(item1), Some(item2));
+    /// ```
+    pub fn next_token(&self, token: ItemToken) -> Option<ItemToken> {
+        self.item(token).next
+    }
+
+    /// Returns the token corresponding to the item that is before that identified by `token`. Returns
+    /// `None` if no item comes before it (i.e. it is the head).
+    ///
+    /// # Panics
+    /// Panics if `token` is invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::<i32>::new();
+    /// list.push_back(5);
+    /// let item2 = list.push_back(6);
+    /// let item3 = list.push_back(7);
+    ///
+    /// assert_eq!(list.prev_token(item3), Some(item2));
+    /// ```
+    pub fn prev_token(&self, token: ItemToken) -> Option<ItemToken> {
+        self.item(token).previous
+    }
+
+    /// Returns the token corresponding to the item `n` steps after the item identified by `token`,
+    /// or `n` steps before it if `n` is negative. Returns `None` if `token` is invalid or if the walk
+    /// runs off either end of the list.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::<i32>::new();
+    /// let item1 = list.push_back(5);
+    /// let item2 = list.push_back(6);
+    /// let item3 = list.push_back(7);
+    ///
+    /// assert_eq!(list.advance_token(item1, 2), Some(item3));
+    /// assert_eq!(list.advance_token(item3, -1), Some(item2));
+    /// assert_eq!(list.advance_token(item2, 0), Some(item2));
+    /// assert_eq!(list.advance_token(item2, 2), None);
+    ///
+    /// list.remove(item1);
+    /// assert_eq!(list.advance_token(item1, 1), None);
+    /// ```
+    pub fn advance_token(&self, token: ItemToken, n: isize) -> Option<ItemToken> {
+        let mut current = token;
+        let mut item = self.arena.get(current.index)?;
+        for _ in 0..n.unsigned_abs() {
+            current = if n > 0 { item.next } else { item.previous }?;
+            item = self.arena.get(current.index).unwrap();
+        }
+        Some(current)
+    }
+
+    /// Returns the token of the item closest to the one identified by `token` for which `predicate`
+    /// returns `true`, searching outward from it, alternating between the item after and the item
+    /// before. The item itself is checked first, and of two matches at the same distance, the one
+    /// after wins. Returns `None` if there is no match, or if `token` is invalid.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut buttons = GenerationalTokenList::new();
+    /// let ok = buttons.push_back(("ok", true));
+    /// let apply = buttons.push_back(("apply", false));
+    /// buttons.push_back(("delete", false));
+    /// buttons.push_back(("help", false));
+    /// let cancel = buttons.push_back(("cancel", true));
+    ///
+    /// // Move focus off a button that is being disabled
+    /// assert_eq!(buttons.find_nearest(apply, |(_, enabled)| *enabled), Some(ok));
+    /// assert_eq!(buttons.find_nearest(cancel, |(_, enabled)| *enabled), Some(cancel));
+    /// ```
+    pub fn find_nearest(
+        &self,
+        token: ItemToken,
+        mut predicate: impl FnMut(&T) -> bool,
+    ) -> Option<ItemToken> {
+        let item = self.arena.get(token.index)?;
+        if predicate(&item.data) {
+            return Some(token);
+        }
+
+        let (mut forward, mut backward) = (item.next, item.previous);
+        while forward.is_some() || backward.is_some() {
+            if let Some(current) = forward {
+                let item = &self.arena[current.index];
+                if predicate(&item.data) {
+                    return Some(current);
+                }
+                forward = item.next;
+            }
+            if let Some(current) = backward {
+                let item = &self.arena[current.index];
+                if predicate(&item.data) {
+                    return Some(current);
+                }
+                backward = item.previous;
+            }
+        }
+        None
+    }
+
+    /// Returns the token corresponding to the item at position `pos`. Returns
+    /// `None` if `pos` is invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::<i32>::new();
+    /// let item1 = list.push_back(5);
+    /// let item2 = list.push_back(6);
+    /// let item3 = list.push_back(7);
+    ///
+    /// assert_eq!(list.token_at(0), Some(item1));
+    /// assert_eq!(list.token_at(1), Some(item2));
+    /// assert_eq!(list.token_at(2), Some(item3));
+    /// assert_eq!(list.token_at(3), None);
+    /// assert_eq!(list.token_at(4), None);
+    /// ```
+    pub fn token_at(&self, pos: usize) -> Option<ItemToken> {
+        self.iter_with_tokens().nth(pos).map(|ret| ret.0)
+    }
+
+    /// Returns the token corresponding to the item at position `pos` from the back. Returns
+    /// `None` if `pos` is invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::<i32>::new();
+    /// let item1 = list.push_back(5);
+    /// let item2 = list.push_back(6);
+    /// let item3 = list.push_back(7);
+    ///
+    /// assert_eq!(list.token_at_back(0), Some(item3));
+    /// assert_eq!(list.token_at_back(1), Some(item2));
+    /// assert_eq!(list.token_at_back(2), Some(item1));
+    /// assert_eq!(list.token_at_back(3), None);
+    /// assert_eq!(list.token_at_back(4), None);
+    /// ```
+    pub fn token_at_back(&self, pos: usize) -> Option<ItemToken> {
+        if pos >= self.len() {
+            return None;
+        }
+
+        // TODO: implement DoubleEndedIterator and use that instead
+        self.token_at(self.len() - pos - 1)
+    }
+}
+
+#[cfg(feature = "iter-mut")]
+pub struct IterWithTokensMut<'a, T>
+where
+    T: 'a,
+{
+    list: &'a mut GenerationalTokenList<T>,
+    next_item: Option<ItemToken>,
+}
+
+#[cfg(feature = "iter-mut")]
+impl<'a, T> Iterator for IterWithTokensMut<'a, T>
+where
+    T: 'a,
+{
+    type Item = (ItemToken, &'a mut T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next_item = self.next_item?;
+
+        if let Some(item) = self.list.arena.get_mut(next_item.index) {
+            self.next_item = item.next;
+
+            #[cfg_attr(feature = "iter-mut", allow(unsafe_code))]
+            let data = unsafe { &mut *(&mut item.data as *mut T) };
+            Some((next_item, data))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(feature = "iter-mut")]
+pub struct IterMut<'a, T>
+where
+    T: 'a,
+{
+    inner: IterWithTokensMut<'a, T>,
+}
+
+#[cfg(feature = "iter-mut")]
+impl<'a, T> Iterator for IterMut<'a, T>
+where
+    T: 'a,
+{
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|d| d.1)
+    }
+}
+
+pub struct IterWithTokens<'a, T>
+where
+    T: 'a,
+{
+    list: &'a GenerationalTokenList<T>,
+    next_item: Option<ItemToken>,
+}
+
+impl<'a, T> Iterator for IterWithTokens<'a, T>
+where
+    T: 'a,
+{
+    type Item = (ItemToken, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next_item = self.next_item?;
+
+        #[cfg(feature = "metrics")]
+        self.list.metrics.record_traversal_step();
+        self.list.arena.get(next_item.index).map(|i| {
+            self.next_item = i.next;
+            (next_item, &i.data)
+        })
+    }
+
+    /// Skips `n` items by following links, without going through [`next`](Self::next).
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        for _ in 0..n {
+            self.next_item = self.list.arena.get(self.next_item?.index)?.next;
+        }
+        self.next()
+    }
+
+    /// The iterator always runs to the end of the list, so this is the tail.
+    fn last(self) -> Option<Self::Item> {
+        self.next_item?;
+        let tail = self.list.tail?;
+
+        #[cfg(feature = "metrics")]
+        self.list.metrics.record_traversal_step();
+        Some((tail, &self.list.arena[tail.index].data))
+    }
+
+    /// Returns the list's length if the iterator hasn't been advanced yet, otherwise counts the
+    /// remaining links.
+    fn count(self) -> usize {
+        if self.next_item == self.list.head {
+            return self.list.len();
+        }
+
+        let mut count = 0;
+        let mut current = self.next_item;
+        while let Some(token) = current {
+            count += 1;
+            current = self.list.arena.get(token.index).and_then(|item| item.next);
+        }
+        count
+    }
+}
+
+pub struct EnumerateWithTokens<'a, T>
+where
+    T: 'a,
+{
+    inner: IterWithTokens<'a, T>,
+    position: usize,
+}
+
+impl<'a, T> Iterator for EnumerateWithTokens<'a, T>
+where
+    T: 'a,
+{
+    type Item = (usize, ItemToken, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (token, data) = self.inner.next()?;
+        let position = self.position;
+        self.position += 1;
+        Some((position, token, data))
+    }
+}
+
+pub struct WithPosition<'a, T>
+where
+    T: 'a,
+{
+    inner: Iter<'a, T>,
+    next: Option<&'a T>,
+    first: bool,
+}
+
+impl<'a, T> Iterator for WithPosition<'a, T>
+where
+    T: 'a,
+{
+    type Item = (Position, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.next.take()?;
+        self.next = self.inner.next();
+
+        let position = match (self.first, self.next.is_some()) {
+            (true, false) => Position::Only,
+            (true, true) => Position::First,
+            (false, true) => Position::Middle,
+            (false, false) => Position::Last,
+        };
+        self.first = false;
+        Some((position, current))
+    }
+}
+
+pub struct Iter<'a, T>
+where
+    T: 'a,
+{
+    inner: IterWithTokens<'a, T>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T>
+where
+    T: 'a,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|d| d.1)
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.inner.nth(n).map(|d| d.1)
+    }
+
+    fn last(self) -> Option<Self::Item> {
+        self.inner.last().map(|d| d.1)
+    }
+
+    fn count(self) -> usize {
+        self.inner.count()
+    }
+}
+
+pub struct ChunkBy<'a, T, F>
+where
+    T: 'a,
+{
+    inner: IterWithTokens<'a, T>,
+    pending: Option<(ItemToken, &'a T)>,
+    same_chunk: F,
+}
+
+impl<'a, T, F> Iterator for ChunkBy<'a, T, F>
+where
+    T: 'a,
+    F: FnMut(&T, &T) -> bool,
+{
+    type Item = Vec<(ItemToken, &'a T)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let first = self.pending.take().or_else(|| self.inner.next())?;
+
+        let mut chunk = vec![first];
+        for next in self.inner.by_ref() {
+            let last = chunk.last().unwrap().1;
+            if (self.same_chunk)(last, next.1) {
+                chunk.push(next);
+            } else {
+                self.pending = Some(next);
+                break;
+            }
+        }
+
+        Some(chunk)
+    }
+}
+
+pub struct DrainWhile<'a, T, F>
+where
+    T: 'a,
+{
+    list: &'a mut GenerationalTokenList<T>,
+    predicate: F,
+    from_back: bool,
+}
+
+impl<'a, T, F> Iterator for DrainWhile<'a, T, F>
+where
+    T: 'a,
+    F: FnMut(&T) -> bool,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let end = if self.from_back {
+            self.list.tail?
+        } else {
+            self.list.head?
+        };
+
+        if (self.predicate)(self.list.get(end).unwrap()) {
+            self.list.remove(end)
+        } else {
+            None
+        }
+    }
+}
+
+pub struct DrainFilterMap<'a, T, F>
+where
+    T: 'a,
+{
+    list: &'a mut GenerationalTokenList<T>,
+    next: Option<ItemToken>,
+    f: F,
+}
+
+impl<'a, T, U, F> Iterator for DrainFilterMap<'a, T, F>
+where
     T: 'a,
+    F: FnMut(&mut T) -> Option<U>,
+{
+    type Item = U;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(token) = self.next {
+            self.next = self.list.next_token(token);
+            if self.list.is_guarded(token) {
+                continue;
+            }
+            if let Some(mapped) = (self.f)(self.list.get_mut(token).unwrap()) {
+                self.list.remove(token);
+                return Some(mapped);
+            }
+        }
+        None
+    }
+}
+
+pub struct IntoIter<T> {
+    list: GenerationalTokenList<T>,
+    next_item: Option<ItemToken>,
+}
+
+impl<T> IntoIterator for GenerationalTokenList<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let next_item = self.head;
+
+        IntoIter {
+            list: self,
+            next_item,
+        }
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next_item = self.next_item?;
+
+        self.list.arena.remove(next_item.index).map(|item| {
+            self.next_item = item.next;
+            item.data
+        })
+    }
+}
+
+impl<T> FromIterator<T> for GenerationalTokenList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.push_back_many(iter);
+        list
+    }
+}
+
+/// # Examples
+/// ```
+/// # use generational_token_list::GenerationalTokenList;
+/// let list = GenerationalTokenList::from(vec![1, 2, 3]);
+/// assert_eq!(list.iter().collect::<Vec<_>>(), vec![&1, &2, &3]);
+/// ```
+impl<T> From<Vec<T>> for GenerationalTokenList<T> {
+    fn from(items: Vec<T>) -> Self {
+        items.into_iter().collect()
+    }
+}
+
+/// # Examples
+/// ```
+/// # use generational_token_list::GenerationalTokenList;
+/// use std::collections::VecDeque;
+///
+/// let list = GenerationalTokenList::from(VecDeque::from(vec![1, 2, 3]));
+/// assert_eq!(list.iter().collect::<Vec<_>>(), vec![&1, &2, &3]);
+/// ```
+impl<T> From<VecDeque<T>> for GenerationalTokenList<T> {
+    fn from(items: VecDeque<T>) -> Self {
+        items.into_iter().collect()
+    }
+}
+
+/// # Examples
+/// ```
+/// # use generational_token_list::GenerationalTokenList;
+/// use std::collections::LinkedList;
+///
+/// let list = GenerationalTokenList::from(vec![1, 2, 3].into_iter().collect::<LinkedList<_>>());
+/// assert_eq!(list.iter().collect::<Vec<_>>(), vec![&1, &2, &3]);
+/// ```
+impl<T> From<LinkedList<T>> for GenerationalTokenList<T> {
+    fn from(items: LinkedList<T>) -> Self {
+        items.into_iter().collect()
+    }
+}
+
+/// # Examples
+/// ```
+/// # use generational_token_list::GenerationalTokenList;
+/// let mut list = GenerationalTokenList::new();
+/// list.push_back(1);
+/// list.push_front(0);
+/// assert_eq!(Vec::from(list), vec![0, 1]);
+/// ```
+impl<T> From<GenerationalTokenList<T>> for Vec<T> {
+    fn from(list: GenerationalTokenList<T>) -> Self {
+        list.into_vec()
+    }
+}
+
+/// # Examples
+/// ```
+/// # use generational_token_list::GenerationalTokenList;
+/// use std::collections::VecDeque;
+///
+/// let mut list = GenerationalTokenList::new();
+/// list.push_back(1);
+/// list.push_front(0);
+/// assert_eq!(VecDeque::from(list), VecDeque::from(vec![0, 1]));
+/// ```
+impl<T> From<GenerationalTokenList<T>> for VecDeque<T> {
+    fn from(list: GenerationalTokenList<T>) -> Self {
+        list.into_iter().collect()
+    }
+}
+
+/// # Examples
+/// ```
+/// # use generational_token_list::GenerationalTokenList;
+/// use std::collections::LinkedList;
+///
+/// let mut list = GenerationalTokenList::new();
+/// list.push_back(1);
+/// list.push_front(0);
+/// assert_eq!(LinkedList::from(list), vec![0, 1].into_iter().collect::<LinkedList<_>>());
+/// ```
+impl<T> From<GenerationalTokenList<T>> for LinkedList<T> {
+    fn from(list: GenerationalTokenList<T>) -> Self {
+        list.into_iter().collect()
+    }
+}
+
+/// Lists of pinned boxes. The boxed data never moves while it is in the list, no matter how the
+/// arena is resized or the list is reordered.
+impl<U: ?Sized> GenerationalTokenList<Pin<Box<U>>> {
+    /// Pin `data` in a new box and append it to the end of the list.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::new();
+    /// let item = list.push_back_pinned(1);
+    /// let address = list.get(item).map(|data| &**data as *const i32);
+    ///
+    /// for i in 0..100 {
+    ///     list.push_front_pinned(i);
+    /// }
+    /// assert_eq!(list.get(item).map(|data| &**data as *const i32), address);
+    /// ```
+    pub fn push_back_pinned(&mut self, data: U) -> ItemToken
+    where
+        U: Sized,
+    {
+        self.push_back(Box::pin(data))
+    }
+
+    /// Pin `data` in a new box and prepend it to the beginning of the list.
+    pub fn push_front_pinned(&mut self, data: U) -> ItemToken
+    where
+        U: Sized,
+    {
+        self.push_front(Box::pin(data))
+    }
+
+    /// Returns a pinned mutable reference to the data identified by given token, or `None` if the
+    /// token is invalid.
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::future::Future;
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list: GenerationalTokenList<std::pin::Pin<Box<dyn Future<Output = ()>>>> =
+    ///     GenerationalTokenList::new();
+    /// let task = list.push_back(Box::pin(async {}));
+    /// let future = list.get_pin(task).unwrap();
+    /// # let _ = future;
+    /// ```
+    pub fn get_pin(&mut self, token: ItemToken) -> Option<Pin<&mut U>> {
+        self.get_mut(token).map(Pin::as_mut)
+    }
+}
+
+impl<T> GenerationalTokenList<T>
+where
+    T: Clone,
 {
-    type Item = &'a mut T;
+    /// Creates a list of `n` clones of `value`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let pool = GenerationalTokenList::repeat(String::from("idle"), 3);
+    /// assert_eq!(pool.len(), 3);
+    /// assert!(pool.iter().all(|state| state == "idle"));
+    /// ```
+    pub fn repeat(value: T, n: usize) -> Self {
+        let mut list = Self::with_capacity(n);
+        list.resize(n, value);
+        list
+    }
+
+    /// Resize the list to `new_len` items, like [`Vec::resize`]: removes items from the end if the
+    /// list is longer, or appends clones of `value` if it is shorter. Removal stops early at an
+    /// item protected by a [`RemovalGuard`](crate::RemovalGuard).
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut slots = GenerationalTokenList::from(vec![Some(1)]);
+    /// slots.resize(3, None);
+    /// assert_eq!(slots.iter().collect::<Vec<_>>(), vec![&Some(1), &None, &None]);
+    /// slots.resize(0, None);
+    /// assert!(slots.is_empty());
+    /// ```
+    pub fn resize(&mut self, new_len: usize, value: T) {
+        self.resize_with(new_len, || value.clone());
+    }
+
+    /// Clone all items in `other` and append them to the end of the list. Returns the tokens
+    /// corresponding to the new items, in the same order.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::new();
+    /// list.push_back(String::from("a"));
+    /// let tokens = list.extend_from_slice(&[String::from("b"), String::from("c")]);
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), vec!["a", "b", "c"]);
+    /// assert_eq!(list.get(tokens[0]).unwrap(), "b");
+    /// ```
+    pub fn extend_from_slice(&mut self, other: &[T]) -> Vec<ItemToken> {
+        self.push_back_many(other.iter().cloned())
+    }
+
+    /// Clone the items from `range.start()` to `range.end()` (inclusive) and append the clones to
+    /// the end of the list. Returns the tokens corresponding to the new items, in the same order.
+    ///
+    /// # Panics
+    /// Panics if either token is invalid or if `range.end()` does not come at or after
+    /// `range.start()` in the list.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::new();
+    /// list.push_back(0);
+    /// let one = list.push_back(1);
+    /// let two = list.push_back(2);
+    /// list.push_back(3);
+    ///
+    /// list.extend_from_within(one..=two);
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), vec![&0, &1, &2, &3, &1, &2]);
+    /// ```
+    pub fn extend_from_within(&mut self, range: RangeInclusive<ItemToken>) -> Vec<ItemToken> {
+        let clones = self
+            .range_tokens(range)
+            .into_iter()
+            .map(|token| self[token].clone())
+            .collect::<Vec<_>>();
+        self.push_back_many(clones)
+    }
+}
+
+impl<T> GenerationalTokenList<T>
+where
+    T: PartialEq,
+{
+    /// Returns `true` if list contains an item that equals `value`, `false` otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::<i32>::new();
+    /// list.push_back(5);
+    /// list.push_back(6);
+    /// list.push_back(7);
+    ///
+    /// assert!(list.contains(&5));
+    /// assert!(! list.contains(&100));
+    /// ```
+    pub fn contains(&self, value: &T) -> bool {
+        self.iter().any(|v| v == value)
+    }
+
+    /// Returns the token corresponding to the first item in the list comparing equal to `value`,
+    /// or `false` if no such item is found.
+    ///
+    /// If you require a different search strategy (for example, finding all items that compare equal),
+    /// consider using `iter` and the methods available on the [`Iterator`](https://doc.rust-lang.org/std/iter/trait.Iterator.html) trait.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::<i32>::new();
+    /// list.push_back(5);
+    /// list.push_back(6);
+    /// let seven = list.push_back(7);
+    /// let a_different_seven = list.push_back(7);
+    /// // Remember, they are different!
+    /// assert_ne!(seven, a_different_seven);
+    ///
+    /// assert_eq!(list.find_token(&7), Some(seven));
+    /// assert_eq!(list.find_token(&0), None);
+    /// ```
+    pub fn find_token(&self, value: &T) -> Option<ItemToken> {
+        self.iter_with_tokens()
+            .find(|(_, data)| *data == value)
+            .map(|(token, _)| token)
+    }
+
+    /// Returns `true` if the first items of the list are equal to `prefix`, in order.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let frame = GenerationalTokenList::from(vec![0x7e, 0x01, 0x42, 0x7e]);
+    /// assert!(frame.starts_with(&[0x7e, 0x01]));
+    /// assert!(frame.starts_with(&[]));
+    /// assert!(!frame.starts_with(&[0x01]));
+    /// ```
+    pub fn starts_with(&self, prefix: &[T]) -> bool {
+        prefix.len() <= self.len() && self.iter().zip(prefix).all(|(data, value)| data == value)
+    }
+
+    /// Returns `true` if the last items of the list are equal to `suffix`, in order. Only walks
+    /// back from the tail as far as `suffix` is long.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let frame = GenerationalTokenList::from(vec![0x7e, 0x01, 0x42, 0x7e]);
+    /// assert!(frame.ends_with(&[0x42, 0x7e]));
+    /// assert!(!frame.ends_with(&[0x42]));
+    /// ```
+    pub fn ends_with(&self, suffix: &[T]) -> bool {
+        if suffix.len() > self.len() {
+            return false;
+        }
+
+        let mut current = self.tail;
+        suffix.iter().rev().all(|value| {
+            let item = &self.arena[current.unwrap().index];
+            current = item.previous;
+            item.data == *value
+        })
+    }
+}
+
+impl<T> GenerationalTokenList<T>
+where
+    T: Eq + Hash,
+{
+    /// Remove every item that equals an item before it, wherever it is in the list (unlike
+    /// removing only adjacent duplicates). The first occurrence of each value is kept, together
+    /// with its token. Returns the number of removed items; items protected by a
+    /// [`RemovalGuard`](crate::RemovalGuard) are kept and not counted.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::new();
+    /// let a = list.push_back("a");
+    /// list.push_back("b");
+    /// list.push_back("a");
+    /// list.push_back("c");
+    /// list.push_back("b");
+    ///
+    /// assert_eq!(list.dedup_all(), 2);
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), vec![&"a", &"b", &"c"]);
+    /// assert_eq!(list.get(a), Some(&"a"));
+    /// ```
+    pub fn dedup_all(&mut self) -> usize {
+        let duplicates: Vec<_> = {
+            let mut seen = HashSet::with_capacity(self.len());
+            self.iter_with_tokens()
+                .filter(|(_, data)| !seen.insert(*data))
+                .map(|(token, _)| token)
+                .collect()
+        };
+        duplicates
+            .into_iter()
+            .filter(|&token| self.remove(token).is_some())
+            .count()
+    }
+}
+
+impl<T> GenerationalTokenList<T>
+where
+    T: Display,
+{
+    /// Format the items in list order, with `separator` between each pair of neighbors.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let list = GenerationalTokenList::from(vec![1, 2, 3]);
+    /// assert_eq!(list.join(", "), "1, 2, 3");
+    /// assert_eq!(GenerationalTokenList::<i32>::new().join(", "), "");
+    /// ```
+    pub fn join(&self, separator: &str) -> String {
+        // Assumes short items; the string grows as needed if they aren't
+        let mut joined = String::with_capacity(self.len() * (separator.len() + 4));
+        for (i, item) in self.iter().enumerate() {
+            if i > 0 {
+                joined.push_str(separator);
+            }
+            write!(joined, "{}", item).unwrap();
+        }
+        joined
+    }
+}
+
+impl<T> GenerationalTokenList<T>
+where
+    T: Hash,
+{
+    /// Returns a hash of the list's contents, in order. Lists with the same items in the same order
+    /// have the same fingerprint, regardless of their tokens, so this is a cheap way to tell whether
+    /// a list changed.
+    ///
+    /// Uses [`DefaultHasher`], whose output is stable within a program but may change between Rust
+    /// releases; use [`fingerprint_with`](Self::fingerprint_with) to pick (or seed) the hasher.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::new();
+    /// list.push_back(2);
+    /// let before = list.fingerprint();
+    ///
+    /// let one = list.push_front(1);
+    /// assert_ne!(list.fingerprint(), before);
+    /// list.remove(one);
+    /// assert_eq!(list.fingerprint(), before);
+    /// ```
+    pub fn fingerprint(&self) -> u64 {
+        self.fingerprint_with(DefaultHasher::new())
+    }
+
+    /// Like [`fingerprint`](Self::fingerprint), but feeds the length and the items to `hasher`.
+    pub fn fingerprint_with<H: Hasher>(&self, mut hasher: H) -> u64 {
+        self.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+/// Hashes the length and the items, in order, like a `Vec` holding the same items would.
+impl<T> Hash for GenerationalTokenList<T>
+where
+    T: Hash,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.len());
+        for item in self.iter() {
+            item.hash(state);
+        }
+    }
+}
+
+impl<T> std::ops::Index<ItemToken> for GenerationalTokenList<T> {
+    type Output = T;
+
+    fn index(&self, token: ItemToken) -> &Self::Output {
+        match self.get(token) {
+            Some(data) => data,
+            None => self.invalid_token(token),
+        }
+    }
+}
+
+impl<T> std::ops::IndexMut<ItemToken> for GenerationalTokenList<T> {
+    fn index_mut(&mut self, token: ItemToken) -> &mut Self::Output {
+        if self.get(token).is_none() {
+            self.invalid_token(token);
+        }
+        self.get_mut(token).unwrap()
+    }
+}
+
+/// Concatenates two lists. Tokens for items of the left-hand list remain valid; items of the
+/// right-hand list get new tokens.
+///
+/// # Examples
+/// ```
+/// # use generational_token_list::GenerationalTokenList;
+/// let mut left = GenerationalTokenList::new();
+/// let one = left.push_back(1);
+/// let mut right = GenerationalTokenList::new();
+/// right.push_back(2);
+///
+/// let both = left + right;
+/// assert_eq!(both.iter().collect::<Vec<_>>(), vec![&1, &2]);
+/// assert_eq!(both.get(one), Some(&1));
+/// ```
+impl<T> std::ops::Add for GenerationalTokenList<T> {
+    type Output = Self;
+
+    fn add(mut self, rhs: Self) -> Self::Output {
+        self += rhs;
+        self
+    }
+}
+
+/// Appends all items of the right-hand list, which get new tokens.
+///
+/// # Examples
+/// ```
+/// # use generational_token_list::GenerationalTokenList;
+/// let mut list = GenerationalTokenList::new();
+/// list.push_back(1);
+/// let mut other = GenerationalTokenList::new();
+/// other.push_back(2);
+///
+/// list += other;
+/// assert_eq!(list.iter().collect::<Vec<_>>(), vec![&1, &2]);
+/// ```
+impl<T> std::ops::AddAssign for GenerationalTokenList<T> {
+    fn add_assign(&mut self, rhs: Self) {
+        self.push_back_many(rhs);
+    }
+}
+
+/// Format a token for panic messages.
+fn describe(token: ItemToken) -> String {
+    format!("(slot {}, generation {})", token.slot(), token.generation())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::{GenerationalTokenList, Item};
+
+    macro_rules! assert_eq_contents {
+        ($list:ident, $right:expr) => {
+            // do the lazy thing and just clone the data to compare
+            let data = $list.iter().map(Clone::clone).collect::<Vec<_>>();
+            pretty_assertions::assert_eq!(data.as_slice(), $right);
+        };
+    }
+
+    #[test]
+    fn push_back() {
+        let mut list = GenerationalTokenList::new();
+        list.push_back(10);
+        list.push_back(20);
+        list.push_back(30);
+
+        assert_eq_contents!(list, &[10, 20, 30]);
+    }
+
+    #[test]
+    fn push_back_internals() {
+        let mut list = GenerationalTokenList::new();
+        let item1 = list.push_back(10);
+        let item2 = list.push_back(20);
+        let item3 = list.push_back(30);
+
+        assert_eq!(
+            list.arena.iter().collect::<Vec<_>>(),
+            vec![
+                (
+                    item1.index,
+                    &Item {
+                        data: 10,
+                        previous: None,
+                        next: Some(item2)
+                    }
+                ),
+                (
+                    item2.index,
+                    &Item {
+                        data: 20,
+                        previous: Some(item1),
+                        next: Some(item3)
+                    }
+                ),
+                (
+                    item3.index,
+                    &Item {
+                        data: 30,
+                        previous: Some(item2),
+                        next: None
+                    }
+                )
+            ]
+        );
+    }
+
+    #[test]
+    fn remove_invalid() {
+        let mut list = GenerationalTokenList::new();
+        let item1 = list.push_back(10);
+        list.push_back(20);
+        assert_eq_contents!(list, &[10, 20]);
+        assert_eq!(list.remove(item1), Some(10));
+        assert_eq_contents!(list, &[20]);
+        assert_eq!(list.remove(item1), None);
+        assert_eq_contents!(list, &[20]);
+    }
+
+    #[test]
+    fn remove_only() {
+        let mut list = GenerationalTokenList::new();
+        let item1 = list.push_back(10);
+
+        assert_eq!(list.head, Some(item1));
+        assert_eq!(list.tail, Some(item1));
+        assert_eq!(
+            list.arena.iter().collect::<Vec<_>>(),
+            vec![(
+                item1.index,
+                &Item {
+                    data: 10,
+                    previous: None,
+                    next: None
+                }
+            ),]
+        );
+
+        list.remove(item1);
+
+        assert_eq!(list.head, None);
+        assert_eq!(list.tail, None);
+        assert_eq!(list.arena.into_iter().collect::<Vec<_>>(), vec![]);
+    }
+
+    #[test]
+    fn remove_head() {
+        let mut list = GenerationalTokenList::new();
+        let item1 = list.push_back(10);
+        let item2 = list.push_back(20);
+
+        assert_eq!(list.head, Some(item1));
+        assert_eq!(list.tail, Some(item2));
+        assert_eq!(
+            list.arena.iter().collect::<Vec<_>>(),
+            vec![
+                (
+                    item1.index,
+                    &Item {
+                        data: 10,
+                        previous: None,
+                        next: Some(item2)
+                    }
+                ),
+                (
+                    item2.index,
+                    &Item {
+                        data: 20,
+                        previous: Some(item1),
+                        next: None,
+                    }
+                )
+            ]
+        );
+
+        list.remove(item1);
+
+        assert_eq!(list.head, Some(item2));
+        assert_eq!(list.tail, Some(item2));
+        assert_eq!(
+            list.arena.iter().collect::<Vec<_>>(),
+            vec![(
+                item2.index,
+                &Item {
+                    data: 20,
+                    previous: None,
+                    next: None,
+                }
+            )]
+        );
+    }
+
+    #[test]
+    fn remove_tail() {
+        let mut list = GenerationalTokenList::new();
+        let item1 = list.push_back(10);
+        let item2 = list.push_back(20);
+
+        assert_eq!(list.head, Some(item1));
+        assert_eq!(list.tail, Some(item2));
+        assert_eq!(
+            list.arena.iter().collect::<Vec<_>>(),
+            vec![
+                (
+                    item1.index,
+                    &Item {
+                        data: 10,
+                        previous: None,
+                        next: Some(item2)
+                    }
+                ),
+                (
+                    item2.index,
+                    &Item {
+                        data: 20,
+                        previous: Some(item1),
+                        next: None,
+                    }
+                )
+            ]
+        );
+
+        list.remove(item2);
+
+        assert_eq!(list.head, Some(item1));
+        assert_eq!(list.tail, Some(item1));
+        assert_eq!(
+            list.arena.iter().collect::<Vec<_>>(),
+            vec![(
+                item1.index,
+                &Item {
+                    data: 10,
+                    previous: None,
+                    next: None,
+                }
+            )]
+        );
+    }
+
+    #[test]
+    fn remove_middle() {
+        let mut list = GenerationalTokenList::new();
+        let item1 = list.push_back(10);
+        let item2 = list.push_back(20);
+        let item3 = list.push_back(30);
+        let item4 = list.push_back(40);
+
+        assert_eq!(list.head, Some(item1));
+        assert_eq!(list.tail, Some(item4));
+        assert_eq!(
+            list.arena.iter().collect::<Vec<_>>(),
+            vec![
+                (
+                    item1.index,
+                    &Item {
+                        data: 10,
+                        previous: None,
+                        next: Some(item2)
+                    }
+                ),
+                (
+                    item2.index,
+                    &Item {
+                        data: 20,
+                        previous: Some(item1),
+                        next: Some(item3),
+                    }
+                ),
+                (
+                    item3.index,
+                    &Item {
+                        data: 30,
+                        previous: Some(item2),
+                        next: Some(item4),
+                    }
+                ),
+                (
+                    item4.index,
+                    &Item {
+                        data: 40,
+                        previous: Some(item3),
+                        next: None,
+                    }
+                )
+            ]
+        );
+
+        list.remove(item2);
+
+        assert_eq!(list.head, Some(item1));
+        assert_eq!(list.tail, Some(item4));
+        assert_eq!(
+            list.arena.iter().collect::<Vec<_>>(),
+            vec![
+                (
+                    item1.index,
+                    &Item {
+                        data: 10,
+                        previous: None,
+                        next: Some(item3),
+                    }
+                ),
+                (
+                    item3.index,
+                    &Item {
+                        data: 30,
+                        previous: Some(item1),
+                        next: Some(item4),
+                    }
+                ),
+                (
+                    item4.index,
+                    &Item {
+                        data: 40,
+                        previous: Some(item3),
+                        next: None,
+                    }
+                )
+            ]
+        );
+
+        list.remove(item3);
+        assert_eq!(list.head, Some(item1));
+        assert_eq!(list.tail, Some(item4));
+        assert_eq!(
+            list.arena.iter().collect::<Vec<_>>(),
+            vec![
+                (
+                    item1.index,
+                    &Item {
+                        data: 10,
+                        previous: None,
+                        next: Some(item4),
+                    }
+                ),
+                (
+                    item4.index,
+                    &Item {
+                        data: 40,
+                        previous: Some(item1),
+                        next: None,
+                    }
+                )
+            ]
+        );
+    }
+
+    #[test]
+    fn unlink_and_relink() {
+        let mut list = GenerationalTokenList::new();
+        let item1 = list.push_back(10);
+        let item2 = list.push_back(20);
+        let item3 = list.push_back(30);
+
+        assert!(list.unlink(item1));
+        assert!(!list.unlink(item1));
+        assert!(list.unlink(item3));
+        assert_eq!(list.head, Some(item2));
+        assert_eq!(list.tail, Some(item2));
+        assert_eq!(list.len(), 1);
+        assert_eq_contents!(list, &[20]);
+
+        assert!(list.unlink(item2));
+        assert_eq!(list.head, None);
+        assert_eq!(list.tail, None);
+        assert!(list.is_empty());
+        assert_eq!(list.find_token(&20), None);
+
+        list.relink_front(item3);
+        list.relink_front(item2);
+        list.relink_after(item1, item3);
+        assert_eq_contents!(list, &[20, 30, 10]);
+        assert_eq!(
+            list.arena.get(item3.index),
+            Some(&Item {
+                data: 30,
+                previous: Some(item2),
+                next: Some(item1),
+            })
+        );
+        assert_eq!(list.tail, Some(item1));
+        assert_eq!(list.len(), 3);
+    }
+
+    #[test]
+    fn remove_unlinked() {
+        let mut list = GenerationalTokenList::new();
+        let item1 = list.push_back(10);
+        let item2 = list.push_back(20);
+
+        list.unlink(item1);
+        assert_eq!(list.remove(item1), Some(10));
+        assert_eq!(list.len(), 1);
+        assert_eq!(list.head, Some(item2));
+        assert_eq!(list.tail, Some(item2));
+
+        list.unlink(item2);
+        list.clear();
+        assert_eq!(list.len(), 0);
+        list.push_back(30);
+        assert_eq!(list.len(), 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn insert_after_unlinked() {
+        let mut list = GenerationalTokenList::new();
+        let item1 = list.push_back(10);
+        list.push_back(20);
+        list.unlink(item1);
+        list.insert_after(item1, 15);
+    }
+
+    #[test]
+    fn pop_front() {
+        let mut list = GenerationalTokenList::new();
+        list.push_back(10);
+        list.push_back(20);
+        list.push_back(30);
+        assert_eq_contents!(list, &[10, 20, 30]);
+        assert_eq!(list.pop_front(), Some(10));
+        assert_eq_contents!(list, &[20, 30]);
+        assert_eq!(list.pop_front(), Some(20));
+        assert_eq_contents!(list, &[30]);
+        assert_eq!(list.pop_front(), Some(30));
+        assert_eq_contents!(list, &[]);
+    }
+
+    #[test]
+    fn push_many_reserves_once() {
+        let mut list = GenerationalTokenList::with_capacity(1);
+        list.push_back(0);
+
+        let back = list.push_back_many(1..=4);
+        assert_eq!(list.capacity(), 5);
+        let front = list.push_front_many(vec![-2, -1]);
+        assert_eq!(list.capacity(), 7);
+
+        assert_eq_contents!(list, &[-2, -1, 0, 1, 2, 3, 4]);
+        assert_eq!(list.token_at(0), Some(front[0]));
+        assert_eq!(list.token_at(1), Some(front[1]));
+        assert_eq!(list.token_at(3), Some(back[0]));
+        assert_eq!(list.tail, Some(back[3]));
+    }
+
+    #[test]
+    fn insert_many_at_ends() {
+        let mut list = GenerationalTokenList::new();
+        let head = list.push_back(1);
+        let front = list.insert_before_many(head, vec![-1, 0]);
+        let back = list.insert_after_many(head, vec![2, 3]);
+
+        assert_eq_contents!(list, &[-1, 0, 1, 2, 3]);
+        assert_eq!(list.head, Some(front[0]));
+        assert_eq!(list.tail, Some(back[1]));
+        assert_eq!(list.insert_after_many(head, Vec::new()), vec![]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn extend_from_within_reversed_range() {
+        let mut list = GenerationalTokenList::new();
+        let item1 = list.push_back(10);
+        let item2 = list.push_back(20);
+        list.extend_from_within(item2..=item1);
+    }
+
+    #[test]
+    fn clone_from_reuses_items() {
+        let mut source = GenerationalTokenList::new();
+        let item1 = source.push_back(vec![1, 2, 3]);
+        let item2 = source.push_back(vec![4, 5, 6]);
+
+        let mut target = source.clone();
+        let buffer = target[item1].as_ptr();
+
+        source[item1][0] = 100;
+        target.clone_from(&source);
+        assert_eq_contents!(target, &[vec![100, 2, 3], vec![4, 5, 6]]);
+        // Same tokens, so the item was updated in place
+        assert_eq!(target[item1].as_ptr(), buffer);
+
+        source.remove(item2);
+        target.clone_from(&source);
+        assert_eq_contents!(target, &[vec![100, 2, 3]]);
+        assert_eq!(target.get(item2), None);
+        assert_eq!(target.tail, Some(item1));
+    }
+
+    #[test]
+    fn clone_from_forgets_saved_positions_when_replacing_items() {
+        let mut source = GenerationalTokenList::new();
+        source.push_back(1);
+        source.push_back(2);
+
+        let mut target = GenerationalTokenList::new();
+        let item = target.push_back(10);
+        let position = target.save_position(item, crate::Bias::After);
+
+        // The tokens differ, so the items are replaced and the saved token may now name an
+        // unrelated item of `source`
+        target.clone_from(&source);
+        assert_eq_contents!(target, &[1, 2]);
+        assert_eq!(position.token(), None);
+    }
+
+    #[test]
+    fn split_into_more_pieces_than_items() {
+        let mut list = GenerationalTokenList::new();
+        list.push_back(10);
+        list.push_back(20);
+
+        let pieces = list.split_into(4);
+        assert_eq!(pieces.len(), 4);
+        assert_eq!(
+            pieces.iter().map(|piece| piece.len()).collect::<Vec<_>>(),
+            vec![1, 1, 0, 0]
+        );
+        let first = &pieces[0];
+        assert_eq_contents!(first, &[10]);
+        assert_eq!(first.head, first.tail);
+    }
+
+    #[test]
+    fn into_iter() {
+        let mut list = GenerationalTokenList::<i32>::new();
+        list.push_back(10);
+        list.push_back(20);
+        list.push_back(30);
+
+        let data = list.into_iter().collect::<Vec<_>>();
+        assert_eq!(data, vec![10, 20, 30]);
+    }
+
+    #[test]
+    fn index() {
+        let mut list = GenerationalTokenList::<i32>::new();
+        let item1 = list.push_back(10);
+        let item2 = list.push_back(20);
+        let item3 = list.push_back(30);
+        assert_eq!(list[item1], 10);
+        assert_eq!(list[item2], 20);
+        assert_eq!(list[item3], 30);
+    }
+
+    #[test]
+    fn index_mut() {
+        let mut list = GenerationalTokenList::<i32>::new();
+        let item1 = list.push_back(10);
+        let item2 = list.push_back(20);
+        let item3 = list.push_back(30);
+
+        list[item1] = 500;
+        list[item3] *= 9;
+
+        assert_eq!(list[item1], 500);
+        assert_eq!(list[item2], 20);
+        assert_eq!(list[item3], 270);
+    }
+
+    #[test]
+    fn iter_with_tokens() {
+        let mut list = GenerationalTokenList::<i32>::new();
+        let item1 = list.push_back(10);
+        let item2 = list.push_back(20);
+        let item3 = list.push_back(30);
+
+        let mut iter = list.iter_with_tokens();
+        assert_eq!(iter.next(), Some((item1, &10)));
+        assert_eq!(iter.next(), Some((item2, &20)));
+        assert_eq!(iter.next(), Some((item3, &30)));
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn pinned_data_does_not_move() {
+        let mut list = GenerationalTokenList::new();
+        let item = list.push_back_pinned(String::from("pinned"));
+        let address = list.get_pin(item).map(|data| &*data as *const String);
+
+        for i in 0..100 {
+            list.push_back_pinned(i.to_string());
+        }
+        list.unlink(item);
+        list.relink_back(item);
+        list.pop_front();
+
+        assert_eq!(
+            list.get_pin(item).map(|data| &*data as *const String),
+            address
+        );
+    }
+
+    #[test]
+    fn sorted_list_with_duplicates() {
+        let mut list = crate::SortedTokenList::new_by(|a: &(i32, &str), b| a.0.cmp(&b.0));
+        let a = list.insert((1, "a"));
+        let b = list.insert((1, "b"));
+        list.insert((0, "c"));
+        let d = list.insert((1, "d"));
+        assert_eq!(
+            list.iter().map(|item| item.1).collect::<Vec<_>>(),
+            vec!["c", "a", "b", "d"]
+        );
+        assert_eq!(list.find(&(1, "")), Some(a));
+
+        assert_eq!(list.remove(b), Some((1, "b")));
+        assert_eq!(list.remove(a), Some((1, "a")));
+        assert_eq!(list.find(&(1, "")), Some(d));
+        assert_eq!(list.pop_first(), Some((0, "c")));
+        assert_eq!(list.pop_last(), Some((1, "d")));
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn sorted_list_with_capturing_comparator() {
+        let priorities = std::collections::HashMap::from([("low", 2), ("high", 0), ("mid", 1)]);
+        let mut list = crate::SortedTokenList::new_by(move |a: &&str, b: &&str| {
+            priorities[a].cmp(&priorities[b])
+        });
+        list.extend(vec!["low", "high", "mid"]);
+        assert_eq!(
+            list.iter().copied().collect::<Vec<_>>(),
+            vec!["high", "mid", "low"]
+        );
+        assert_eq!(
+            list.find(&"mid"),
+            list.iter_with_tokens().nth(1).map(|(token, _)| token)
+        );
+    }
+
+    #[test]
+    fn indexed_list_with_duplicates() {
+        let mut list = crate::IndexedTokenList::new();
+        let a = list.push_back(1);
+        list.push_back(2);
+        let c = list.push_front(1);
+        assert_eq!(list.find_token(&1), Some(a));
+        assert_eq!(list.count_of(&1), 2);
+
+        assert_eq!(list.pop_back(), Some(2));
+        assert_eq!(list.remove(a), Some(1));
+        assert_eq!(list.find_token(&1), Some(c));
+        list.clear();
+        assert_eq!(list.count_of(&1), 0);
+    }
+
+    #[test]
+    fn aggregated_list_matches_brute_force() {
+        let mut list = crate::AggregatedTokenList::new(
+            Vec::new(),
+            |data: &u32| vec![*data],
+            |a, b| a.iter().chain(b).copied().collect(),
+        );
+        let mut seed = 7u32;
+        let mut random = move |n: usize| {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            (seed >> 8) as usize % n
+        };
+
+        for i in 0..300 {
+            let tokens = list.iter_with_tokens().map(|(t, _)| t).collect::<Vec<_>>();
+            match random(5) {
+                0 if !tokens.is_empty() => {
+                    list.remove(tokens[random(tokens.len())]);
+                }
+                1 if !tokens.is_empty() => {
+                    list.insert_after(tokens[random(tokens.len())], i);
+                }
+                2 if !tokens.is_empty() => {
+                    list.insert_before(tokens[random(tokens.len())], i);
+                }
+                3 if !tokens.is_empty() => {
+                    list.update(tokens[random(tokens.len())], |data| *data += 1000);
+                }
+                _ => {
+                    list.push_front(i);
+                }
+            }
+
+            let items = list.iter().copied().collect::<Vec<_>>();
+            assert_eq!(list.aggregate(), items);
+            let tokens = list.iter_with_tokens().map(|(t, _)| t).collect::<Vec<_>>();
+            if !tokens.is_empty() {
+                let start = random(tokens.len());
+                let end = start + random(tokens.len() - start);
+                assert_eq!(
+                    list.aggregate_range(tokens[start]..=tokens[end]),
+                    items[start..=end].to_vec()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn fixed_list_reuses_slots() {
+        let mut list = crate::FixedTokenList::<i32, 3>::new();
+        let one = list.try_push_back(1).unwrap();
+        let two = list.try_push_back(2).unwrap();
+        let three = list.try_push_back(3).unwrap();
+        assert!(list.is_full());
+
+        assert_eq!(list.remove(two), Some(2));
+        assert_eq!(list.next_token(one), Some(three));
+        assert_eq!(list.prev_token(three), Some(one));
+
+        let four = list.try_insert_before(three, 4).unwrap();
+        assert_eq!(list.get(two), None);
+        assert_eq!(list.get(four), Some(&4));
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![1, 4, 3]);
+
+        assert_eq!(list.pop_front(), Some(1));
+        assert_eq!(list.pop_back(), Some(3));
+        assert_eq!(list.head_token(), Some(four));
+        assert_eq!(list.tail_token(), Some(four));
+
+        list.clear();
+        assert!(list.is_empty());
+        assert_eq!(list.head(), None);
+    }
+
+    #[test]
+    fn generation_matches_arena() {
+        let mut list = GenerationalTokenList::new();
+        let item1 = list.push_back(1);
+        let item2 = list.push_back(2);
+        list.remove(item1);
+        list.remove(item1);
+        list.pop_back();
+        assert_eq!(list.push_back(3).generation(), list.generation());
+
+        list.clear();
+        list.clear();
+        assert_eq!(list.push_back(4).generation(), list.generation());
+        assert_eq!(list.generation(), 3);
+        assert_ne!(item2.generation(), list.generation());
+    }
+
+    #[test]
+    fn tokens_are_deterministic() {
+        fn run(seed: u64) -> Vec<crate::ItemToken> {
+            let mut state = seed;
+            let mut next = move || {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state
+            };
+
+            let mut list = GenerationalTokenList::new();
+            let mut tokens = Vec::new();
+            for i in 0..1000 {
+                match next() % 4 {
+                    0 | 1 => tokens.push(list.push_back(i)),
+                    2 => tokens.push(list.push_front(i)),
+                    _ => {
+                        if !tokens.is_empty() {
+                            let token = tokens[next() as usize % tokens.len()];
+                            list.remove(token);
+                        }
+                    }
+                }
+            }
+            tokens
+        }
+
+        assert_eq!(run(0x2545_f491_4f6c_dd1d), run(0x2545_f491_4f6c_dd1d));
+    }
+
+    #[test]
+    fn diff_and_patch_reproduce_other() {
+        use crate::Edit;
+
+        let cases: &[(&[i32], &[i32])] = &[
+            (&[], &[]),
+            (&[], &[1, 2]),
+            (&[1, 2], &[]),
+            (&[1, 2, 3, 4, 5], &[1, 3, 5]),
+            (&[1, 2, 3], &[3, 2, 1]),
+            (&[5, 1, 2, 3, 6], &[7, 1, 8, 3, 9]),
+        ];
+
+        for (old, new) in cases {
+            let old_list = old.iter().copied().collect::<GenerationalTokenList<_>>();
+            let new_list = new.iter().copied().collect::<GenerationalTokenList<_>>();
+            let edits = old_list.diff(&new_list);
+
+            let mut result = Vec::new();
+            let mut kept = 0;
+            for edit in &edits {
+                match edit {
+                    Edit::Keep(token) => {
+                        result.push(old_list[*token]);
+                        kept += 1;
+                    }
+                    Edit::Remove(token) => assert!(old_list.get(*token).is_some()),
+                    Edit::Insert(value) => result.push(**value),
+                }
+            }
+            assert_eq!(&result, new);
+            assert_eq!(edits.len(), old.len() + new.len() - kept);
+
+            let mut patched = old_list.clone();
+            patched.apply_patch(edits.into_iter().map(Edit::cloned));
+            assert_eq!(&patched.into_vec(), new);
+        }
+    }
+
+    #[test]
+    fn removal_guards() {
+        let mut list = GenerationalTokenList::new();
+        let item1 = list.push_back(1);
+        let item2 = list.push_back(2);
+        let item3 = list.push_back(3);
+
+        let guard = list.guard(item2);
+        let guard2 = guard.clone();
+        assert_eq!(list.sweep_unmarked(), 2);
+        assert_eq!(list.get(item1), None);
+        assert_eq!(list.get(item3), None);
+
+        drop(guard);
+        assert!(list.is_guarded(item2));
+        assert_eq!(list.pop_front(), None);
+
+        drop(guard2);
+        assert!(!list.is_guarded(item2));
+        assert_eq!(list.pop_front(), Some(2));
+    }
+
+    #[test]
+    fn replicated_lists_converge() {
+        use crate::ReplicatedList;
+
+        let mut replicas = (1..=3).map(ReplicatedList::new).collect::<Vec<_>>();
+        let base = vec![replicas[0].insert(0, 'x'), replicas[0].insert(1, 'y')];
+        for replica in &mut replicas[1..] {
+            for op in &base {
+                assert!(replica.apply(op.clone()));
+            }
+        }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        self.inner.next().map(|d| d.1)
+        // Every replica edits concurrently
+        let ops = [
+            vec![replicas[0].insert(1, 'a'), replicas[0].remove(0).unwrap()],
+            vec![replicas[1].insert(1, 'b'), replicas[1].insert(2, 'c')],
+            vec![replicas[2].insert(1, 'd'), replicas[2].remove(2).unwrap()],
+        ];
+
+        // Deliver in a different order to each replica (keeping each replica's own ops in order)
+        let orders = [[1, 2], [2, 0], [0, 1]];
+        for (replica, order) in replicas.iter_mut().zip(&orders) {
+            for &source in order.iter().rev() {
+                for op in &ops[source] {
+                    assert!(replica.apply(op.clone()));
+                    assert!(replica.apply(op.clone()));
+                }
+            }
+        }
+
+        let contents = replicas
+            .iter()
+            .map(|replica| replica.iter().collect::<String>())
+            .collect::<Vec<_>>();
+        assert_eq!(contents[0], contents[1]);
+        assert_eq!(contents[1], contents[2]);
+        assert_eq!(contents[0].len(), 4);
+        assert!(!contents[0].contains('x'));
+        assert!(!contents[0].contains('y'));
     }
-}
 
-pub struct IterWithTokens<'a, T>
-where
-    T: 'a,
-{
-    list: &'a GenerationalTokenList<T>,
-    next_item: Option<ItemToken>,
-}
+    #[test]
+    fn subscription_is_woken_by_changes() {
+        use std::future::Future;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+        use std::task::{Context, Poll, Wake, Waker};
 
-impl<'a, T> Iterator for IterWithTokens<'a, T>
-where
-    T: 'a,
-{
-    type Item = (ItemToken, &'a T);
+        struct CountingWaker(AtomicUsize);
+        impl Wake for CountingWaker {
+            fn wake(self: Arc<Self>) {
+                self.0.fetch_add(1, Ordering::SeqCst);
+            }
+        }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        let next_item = self.next_item?;
+        let counter = Arc::new(CountingWaker(AtomicUsize::new(0)));
+        let waker = Waker::from(counter.clone());
+        let mut cx = Context::from_waker(&waker);
 
-        self.list.arena.get(next_item.index).map(|i| {
-            self.next_item = i.next;
-            (next_item, &i.data)
-        })
-    }
-}
+        let mut list = GenerationalTokenList::new();
+        let mut changes = list.subscribe();
+        assert_eq!(
+            std::pin::pin!(changes.changed()).poll(&mut cx),
+            Poll::Pending
+        );
 
-pub struct Iter<'a, T>
-where
-    T: 'a,
-{
-    inner: IterWithTokens<'a, T>,
-}
+        // Falls back to a full clone, which must keep the subscription
+        let source = GenerationalTokenList::from(vec![1, 2]);
+        list.clone_from(&source);
+        assert_eq!(counter.0.load(Ordering::SeqCst), 1);
+        assert_eq!(
+            std::pin::pin!(changes.changed()).poll(&mut cx),
+            Poll::Ready(true)
+        );
 
-impl<'a, T> Iterator for Iter<'a, T>
-where
-    T: 'a,
-{
-    type Item = &'a T;
+        list.clear();
+        list.clear();
+        assert!(changes.has_changed());
+        changes.mark_seen();
+        list.clear();
+        assert!(!changes.has_changed());
+        assert!(!changes.is_closed());
 
-    fn next(&mut self) -> Option<Self::Item> {
-        self.inner.next().map(|d| d.1)
+        assert_eq!(
+            std::pin::pin!(changes.changed()).poll(&mut cx),
+            Poll::Pending
+        );
+        drop(list);
+        assert_eq!(counter.0.load(Ordering::SeqCst), 2);
+        assert!(changes.is_closed());
     }
-}
 
-pub struct IntoIter<T> {
-    list: GenerationalTokenList<T>,
-    next_item: Option<ItemToken>,
-}
+    #[test]
+    fn iter_nth_last_count() {
+        let mut list = (0..5).collect::<GenerationalTokenList<_>>();
+        let unlinked = list.push_back(5);
+        list.unlink(unlinked);
 
-impl<T> IntoIterator for GenerationalTokenList<T> {
-    type Item = T;
-    type IntoIter = IntoIter<T>;
+        assert_eq!(list.iter().count(), 5);
+        assert_eq!(list.iter().last(), Some(&4));
+        assert_eq!(list.iter().nth(5), None);
 
-    fn into_iter(self) -> Self::IntoIter {
-        let next_item = self.head;
+        let mut iter = list.iter();
+        assert_eq!(iter.nth(1), Some(&1));
+        assert_eq!(iter.count(), 3);
 
-        IntoIter {
-            list: self,
-            next_item,
-        }
+        let mut iter = list.iter();
+        assert_eq!(iter.nth(3), Some(&3));
+        assert_eq!(iter.last(), Some(&4));
+
+        let mut iter = list.iter();
+        assert_eq!(iter.nth(4), Some(&4));
+        assert_eq!(iter.last(), None);
     }
-}
 
-impl<T> Iterator for IntoIter<T> {
-    type Item = T;
+    #[cfg(feature = "iter-mut")]
+    #[test]
+    fn get_many_mut_rejects_stale_tokens() {
+        let mut list = GenerationalTokenList::new();
+        let a = list.push_back(1);
+        let b = list.push_back(2);
+        let c = list.push_back(3);
+        list.remove(b);
+        let d = list.push_back(4);
 
-    fn next(&mut self) -> Option<Self::Item> {
-        let next_item = self.next_item?;
+        assert_eq!(list.get_many_mut([a, b]), None);
+        assert_eq!(list.get_many_mut([c, b, a]), None);
+        assert_eq!(list.get_many_mut([c, a, c]), None);
+        assert_eq!(list.get_many_mut::<0>([]), Some([]));
 
-        self.list.arena.remove(next_item.index).map(|item| {
-            self.next_item = item.next;
-            item.data
-        })
+        let [d_data, a_data] = list.get_many_mut([d, a]).unwrap();
+        std::mem::swap(d_data, a_data);
+        assert_eq_contents!(list, &[4, 3, 1]);
     }
-}
 
-impl<T> GenerationalTokenList<T>
-where
-    T: PartialEq,
-{
-    /// Returns `true` if list contains an item that equals `value`, `false` otherwise.
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// # use generational_token_list::GenerationalTokenList;
-    /// let mut list = GenerationalTokenList::<i32>::new();
-    /// list.push_back(5);
-    /// list.push_back(6);
-    /// list.push_back(7);
-    ///
-    /// assert!(list.contains(&5));
-    /// assert!(! list.contains(&100));
-    /// ```
-    pub fn contains(&self, value: &T) -> bool {
-        self.iter().any(|v| v == value)
-    }
+    #[test]
+    fn clear_with_unlinked_and_guarded_items() {
+        let mut list = GenerationalTokenList::new();
+        let unlinked = list.push_back(1);
+        list.push_back(2);
+        let guarded = list.push_back(3);
+        list.push_front(4);
+        list.unlink(unlinked);
+        let guard = list.guard(guarded);
 
-    /// Returns the token corresponding to the first item in the list comparing equal to `value`,
-    /// or `false` if no such item is found.
-    ///
-    /// If you require a different search strategy (for example, finding all items that compare equal),
-    /// consider using `iter` and the methods available on the [`Iterator`](https://doc.rust-lang.org/std/iter/trait.Iterator.html) trait.
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// # use generational_token_list::GenerationalTokenList;
-    /// let mut list = GenerationalTokenList::<i32>::new();
-    /// list.push_back(5);
-    /// list.push_back(6);
-    /// let seven = list.push_back(7);
-    /// let a_different_seven = list.push_back(7);
-    /// // Remember, they are different!
-    /// assert_ne!(seven, a_different_seven);
-    ///
-    /// assert_eq!(list.find_token(&7), Some(seven));
-    /// assert_eq!(list.find_token(&0), None);
-    /// ```
-    pub fn find_token(&self, value: &T) -> Option<ItemToken> {
-        self.arena
-            .iter()
-            .find(|item| &(*item).1.data == value)
-            .map(|(index, _)| ItemToken { index })
+        let mut flushed = Vec::new();
+        list.clear_with(|token, item| flushed.push((token, item)));
+        assert_eq!(
+            flushed.iter().map(|(_, item)| *item).collect::<Vec<_>>(),
+            vec![4, 2, 1]
+        );
+        assert_eq!(flushed[2].0, unlinked);
+        assert_eq_contents!(list, &[3]);
+        list.assert_invariants();
+
+        drop(guard);
+        list.clear_with(|_, item| flushed.push((guarded, item)));
+        assert!(list.is_empty());
+        assert_eq!(flushed.len(), 4);
     }
-}
 
-impl<T> std::ops::Index<ItemToken> for GenerationalTokenList<T> {
-    type Output = T;
+    #[test]
+    fn removal_from_a_guarded_end_fails() {
+        let mut list: GenerationalTokenList<_> = (1..=6).collect();
+        let head = list.head_token().unwrap();
+        let tail = list.tail_token().unwrap();
+        let guards = (list.guard(head), list.guard(tail));
 
-    fn index(&self, token: ItemToken) -> &Self::Output {
-        self.get(token).unwrap()
+        assert_eq!(list.pop_back(), None);
+        assert_eq!(list.pop_front(), None);
+        assert_eq!(list.pop_back_if(|_| true), None);
+        assert_eq!(list.pop_front_n(2), Vec::<i32>::new());
+        assert_eq!(list.drain_while_back(|_| true).count(), 0);
+        list.resize_with(3, Default::default);
+        assert_eq_contents!(list, &[1, 2, 3, 4, 5, 6]);
+
+        drop(guards);
+        list.push_back(7);
+        let guard = list.guard(list.tail_token().unwrap());
+        assert_eq!(list.pop_back_n(3), Vec::<i32>::new());
+        drop(guard);
+        assert_eq!(list.pop_back_n(3), vec![7, 6, 5]);
+        list.assert_invariants();
     }
-}
 
-impl<T> std::ops::IndexMut<ItemToken> for GenerationalTokenList<T> {
-    fn index_mut(&mut self, token: ItemToken) -> &mut Self::Output {
-        self.get_mut(token).unwrap()
+    #[test]
+    #[should_panic(expected = "cannot split a list while items are guarded")]
+    fn split_into_with_guarded_items() {
+        let mut list: GenerationalTokenList<_> = (1..=6).collect();
+        let _guard = list.guard(list.tail_token().unwrap());
+        list.split_into(3);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use pretty_assertions::assert_eq;
+    #[test]
+    fn clone_from_keeps_guards() {
+        let mut list: GenerationalTokenList<_> = (1..=3).collect();
+        let head = list.head_token().unwrap();
+        let guard = list.guard(head);
 
-    use crate::{GenerationalTokenList, Item};
+        // Same tokens, so the items are updated in place and the guard still applies
+        let mut source = list.clone();
+        source[head] = 10;
+        list.clone_from(&source);
+        assert!(list.is_guarded(head));
+        list.clear();
+        assert_eq_contents!(list, &[10]);
 
-    macro_rules! assert_eq_contents {
-        ($list:ident, $right:expr) => {
-            // do the lazy thing and just clone the data to compare
-            let data = $list.iter().map(Clone::clone).collect::<Vec<_>>();
-            pretty_assertions::assert_eq!(data.as_slice(), $right);
-        };
+        let result =
+            std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| list.clone_from(&source)));
+        assert!(result.is_err());
+        assert!(list.is_guarded(head));
+        drop(guard);
     }
 
     #[test]
-    fn push_back() {
+    fn split_mut_follows_list_order() {
         let mut list = GenerationalTokenList::new();
-        list.push_back(10);
-        list.push_back(20);
-        list.push_back(30);
+        let b = list.push_back('b');
+        let a = list.push_front('a');
+        let unlinked = list.push_back('x');
+        let c = list.push_back('c');
+        list.unlink(unlinked);
 
-        assert_eq_contents!(list, &[10, 20, 30]);
+        let segments = list.split_mut(4);
+        let tokens = segments
+            .iter()
+            .map(|segment| segment.iter().map(|(token, _)| *token).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(tokens, vec![vec![a], vec![b], vec![c], vec![]]);
     }
 
     #[test]
-    fn push_back_internals() {
+    fn split_when_without_split_points() {
+        let empty = GenerationalTokenList::<i32>::new();
+        assert_eq!(empty.split_when(|_, _| true).len(), 1);
+
         let mut list = GenerationalTokenList::new();
-        let item1 = list.push_back(10);
-        let item2 = list.push_back(20);
-        let item3 = list.push_back(30);
+        let one = list.push_back(1);
+        list.push_back(2);
+        let pieces = list.split_when(|_, _| false);
+        assert_eq!(pieces.len(), 1);
+        let piece = &pieces[0];
+        assert_eq!(piece.get(one), Some(&1));
+        assert_eq_contents!(piece, &[1, 2]);
+    }
 
-        assert_eq!(
-            list.arena.iter().collect::<Vec<_>>(),
-            vec![
-                (
-                    item1.index,
-                    &Item {
-                        data: 10,
-                        previous: None,
-                        next: Some(item2)
-                    }
-                ),
-                (
-                    item2.index,
-                    &Item {
-                        data: 20,
-                        previous: Some(item1),
-                        next: Some(item3)
-                    }
-                ),
-                (
-                    item3.index,
-                    &Item {
-                        data: 30,
-                        previous: Some(item2),
-                        next: None
-                    }
-                )
-            ]
-        );
+    #[test]
+    fn find_nearest_prefers_forward_on_ties() {
+        let list = GenerationalTokenList::from(vec![1, 0, 0, 1, 0, 1, 0]);
+        let tokens = list.iter_with_tokens().map(|(t, _)| t).collect::<Vec<_>>();
+        let is_one = |value: &i32| *value == 1;
+
+        assert_eq!(list.find_nearest(tokens[4], is_one), Some(tokens[5]));
+        assert_eq!(list.find_nearest(tokens[2], is_one), Some(tokens[3]));
+        assert_eq!(list.find_nearest(tokens[1], is_one), Some(tokens[0]));
+        assert_eq!(list.find_nearest(tokens[6], is_one), Some(tokens[5]));
+        assert_eq!(list.find_nearest(tokens[6], |value| *value == 2), None);
     }
 
     #[test]
-    fn remove_invalid() {
+    #[should_panic(expected = "render_queue: item (slot 1, generation 0) is unlinked")]
+    fn panic_messages_include_label() {
         let mut list = GenerationalTokenList::new();
-        let item1 = list.push_back(10);
-        list.push_back(20);
-        assert_eq_contents!(list, &[10, 20]);
-        assert_eq!(list.remove(item1), Some(10));
-        assert_eq_contents!(list, &[20]);
-        assert_eq!(list.remove(item1), None);
-        assert_eq_contents!(list, &[20]);
+        list.set_label("render_queue");
+        list.push_back(1);
+        let item = list.push_back(2);
+        list.unlink(item);
+        list.insert_after(item, 3);
     }
 
     #[test]
-    fn remove_only() {
+    fn max_len_only_limits_fallible_insertion() {
         let mut list = GenerationalTokenList::new();
-        let item1 = list.push_back(10);
-
-        assert_eq!(list.head, Some(item1));
-        assert_eq!(list.tail, Some(item1));
-        assert_eq!(
-            list.arena.iter().collect::<Vec<_>>(),
-            vec![(
-                item1.index,
-                &Item {
-                    data: 10,
-                    previous: None,
-                    next: None
-                }
-            ),]
-        );
+        list.set_max_len(Some(3));
+        let first = list.push_back(1);
+        let second = list.push_back(2);
+        list.unlink(second);
+        assert_eq!(list.len(), 1);
+        assert!(!list.is_full());
 
-        list.remove(item1);
+        assert_eq!(list.try_push_back_many(vec![3, 4, 5]), Err(vec![3, 4, 5]));
+        let tokens = list.try_push_back_many(vec![3, 4]).unwrap();
+        assert!(list.is_full());
+        assert_eq!(list.try_insert_after(first, 5), Err(5));
+        assert_eq!(list.try_push_front_many(vec![5]), Err(vec![5]));
+        assert_eq_contents!(list, &[1, 3, 4]);
 
-        assert_eq!(list.head, None);
-        assert_eq!(list.tail, None);
-        assert_eq!(list.arena.into_iter().collect::<Vec<_>>(), vec![]);
+        // Infallible insertion ignores the limit
+        list.insert_before(tokens[0], 2);
+        list.push_front_many(vec![-1, 0]);
+        assert_eq_contents!(list, &[-1, 0, 1, 2, 3, 4]);
+        assert_eq!(list.try_push_back(5), Err(5));
+        list.assert_invariants();
     }
 
     #[test]
-    fn remove_head() {
+    fn token_string_rejects_malformed_input() {
         let mut list = GenerationalTokenList::new();
-        let item1 = list.push_back(10);
-        let item2 = list.push_back(20);
-
-        assert_eq!(list.head, Some(item1));
-        assert_eq!(list.tail, Some(item2));
+        list.push_back(1);
+        list.pop_back();
+        let token = list.push_back(2);
         assert_eq!(
-            list.arena.iter().collect::<Vec<_>>(),
-            vec![
-                (
-                    item1.index,
-                    &Item {
-                        data: 10,
-                        previous: None,
-                        next: Some(item2)
-                    }
-                ),
-                (
-                    item2.index,
-                    &Item {
-                        data: 20,
-                        previous: Some(item1),
-                        next: None,
-                    }
-                )
-            ]
+            token.to_token_string().parse::<crate::ItemToken>(),
+            Ok(token)
         );
 
-        list.remove(item1);
+        for text in [
+            "", "-", "0", "0-", "-0", "0-0-0", "00-1", "A-1", "+1-1", "1 -1", "g-1",
+        ] {
+            assert!(text.parse::<crate::ItemToken>().is_err(), "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn chunk_by() {
+        let mut list = GenerationalTokenList::<i32>::new();
+        assert_eq!(list.chunk_by(|a, b| a == b).next(), None);
+
+        let item1 = list.push_back(10);
+        let item2 = list.push_back(10);
+        let item3 = list.push_back(20);
 
-        assert_eq!(list.head, Some(item2));
-        assert_eq!(list.tail, Some(item2));
-        assert_eq!(
-            list.arena.iter().collect::<Vec<_>>(),
-            vec![(
-                item2.index,
-                &Item {
-                    data: 20,
-                    previous: None,
-                    next: None,
-                }
-            )]
-        );
+        let mut chunks = list.chunk_by(|a, b| a == b);
+        assert_eq!(chunks.next(), Some(vec![(item1, &10), (item2, &10)]));
+        assert_eq!(chunks.next(), Some(vec![(item3, &20)]));
+        assert_eq!(chunks.next(), None);
     }
 
     #[test]
-    fn remove_tail() {
+    fn saved_position_falls_back_to_other_neighbor() {
         let mut list = GenerationalTokenList::new();
-        let item1 = list.push_back(10);
-        let item2 = list.push_back(20);
+        let item1 = list.push_back(1);
+        let item2 = list.push_back(2);
+        let item3 = list.push_back(3);
 
-        assert_eq!(list.head, Some(item1));
-        assert_eq!(list.tail, Some(item2));
-        assert_eq!(
-            list.arena.iter().collect::<Vec<_>>(),
-            vec![
-                (
-                    item1.index,
-                    &Item {
-                        data: 10,
-                        previous: None,
-                        next: Some(item2)
-                    }
-                ),
-                (
-                    item2.index,
-                    &Item {
-                        data: 20,
-                        previous: Some(item1),
-                        next: None,
-                    }
-                )
-            ]
-        );
+        let before = list.save_position(item1, crate::Bias::Before);
+        let after = list.save_position(item3, crate::Bias::After);
+        list.remove(item1);
+        list.remove(item3);
+        assert_eq!(before.token(), Some(item2));
+        assert_eq!(after.token(), Some(item2));
 
+        drop(before);
         list.remove(item2);
+        assert_eq!(after.token(), None);
+        assert_eq!(list.positions.len(), 1);
+    }
 
-        assert_eq!(list.head, Some(item1));
-        assert_eq!(list.tail, Some(item1));
+    #[test]
+    fn read_write_copies_stay_in_sync() {
+        let (reader, mut writer) = GenerationalTokenList::new().into_read_write();
+        let mut tokens = Vec::new();
+        for round in 0..4 {
+            tokens.push(writer.push_back(round));
+            let first = writer.push_front(round * 10);
+            writer.insert_after(first, round * 100);
+            if round % 2 == 1 {
+                writer.remove(tokens[0]);
+            }
+            writer.refresh();
+
+            let snapshot = reader.snapshot();
+            assert_eq!(
+                snapshot.iter_with_tokens().collect::<Vec<_>>(),
+                writer.list().iter_with_tokens().collect::<Vec<_>>()
+            );
+        }
+        assert!(!writer.has_pending());
+    }
+
+    #[test]
+    fn assert_list_eq_shows_positional_diff() {
+        let list = GenerationalTokenList::from(vec![1, 2, 4]);
+        let message = std::panic::catch_unwind(|| crate::assert_list_eq!(list, [1, 3]))
+            .unwrap_err()
+            .downcast::<String>()
+            .unwrap();
         assert_eq!(
-            list.arena.iter().collect::<Vec<_>>(),
-            vec![(
-                item1.index,
-                &Item {
-                    data: 10,
-                    previous: None,
-                    next: None,
-                }
-            )]
+            *message,
+            "list contents differ (-expected +list):\n    0: 1\n-   1: 3\n+   1: 2\n+   2: 4\n"
         );
     }
 
+    #[cfg(feature = "quickcheck")]
     #[test]
-    fn remove_middle() {
+    fn arbitrary_lists_shrink_by_removal() {
+        use ::quickcheck::{Arbitrary, Gen};
+
+        let mut g = Gen::new(20);
+        for _ in 0..50 {
+            let list = GenerationalTokenList::<u8>::arbitrary(&mut g);
+            list.assert_invariants();
+
+            for shrunk in list.shrink() {
+                shrunk.assert_invariants();
+                assert!(shrunk.len() < list.len());
+                for (token, data) in shrunk.iter_with_tokens() {
+                    assert_eq!(list.get(token), Some(data));
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn random_token_in_sparse_list() {
+        use rand::SeedableRng;
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+        let mut list = GenerationalTokenList::with_capacity(1000);
+        let tokens = (0..1000).map(|i| list.push_back(i)).collect::<Vec<_>>();
+        for token in &tokens[1..] {
+            list.remove(*token);
+        }
+        let unlinked = list.push_back(1);
+        list.unlink(unlinked);
+
+        for _ in 0..10 {
+            assert_eq!(list.random_token(&mut rng), Some(tokens[0]));
+        }
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn shuffle_keeps_links_consistent() {
+        use rand::SeedableRng;
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+        let mut list = (0..50).collect::<GenerationalTokenList<_>>();
+        list.shuffle(&mut rng);
+
+        let forward = list.iter().copied().collect::<Vec<_>>();
+        let mut backward = Vec::new();
+        let mut current = list.tail_token();
+        while let Some(token) = current {
+            backward.push(list[token]);
+            current = list.prev_token(token);
+        }
+        backward.reverse();
+        assert_eq!(forward, backward);
+
+        let mut sorted = forward.clone();
+        sorted.sort();
+        assert_eq!(sorted, (0..50).collect::<Vec<_>>());
+        assert_ne!(forward, sorted);
+    }
+
+    #[cfg(feature = "futures")]
+    #[test]
+    fn into_stream_skips_unlinked() {
+        use futures_core::Stream;
+        use std::pin::Pin;
+        use std::task::{Context, Poll, Waker};
+
         let mut list = GenerationalTokenList::new();
-        let item1 = list.push_back(10);
-        let item2 = list.push_back(20);
-        let item3 = list.push_back(30);
-        let item4 = list.push_back(40);
+        list.push_back(1);
+        let item2 = list.push_back(2);
+        list.push_back(3);
+        list.unlink(item2);
 
-        assert_eq!(list.head, Some(item1));
-        assert_eq!(list.tail, Some(item4));
-        assert_eq!(
-            list.arena.iter().collect::<Vec<_>>(),
-            vec![
-                (
-                    item1.index,
-                    &Item {
-                        data: 10,
-                        previous: None,
-                        next: Some(item2)
-                    }
-                ),
-                (
-                    item2.index,
-                    &Item {
-                        data: 20,
-                        previous: Some(item1),
-                        next: Some(item3),
-                    }
-                ),
-                (
-                    item3.index,
-                    &Item {
-                        data: 30,
-                        previous: Some(item2),
-                        next: Some(item4),
-                    }
-                ),
-                (
-                    item4.index,
-                    &Item {
-                        data: 40,
-                        previous: Some(item3),
-                        next: None,
-                    }
-                )
-            ]
-        );
+        let mut stream = list.into_stream();
+        let mut cx = Context::from_waker(Waker::noop());
+        let mut items = Vec::new();
+        while let Poll::Ready(Some(item)) = Pin::new(&mut stream).poll_next(&mut cx) {
+            items.push(item);
+        }
+        assert_eq!(items, vec![1, 3]);
+    }
 
-        list.remove(item2);
+    #[cfg(feature = "futures")]
+    #[test]
+    fn stream_pop_front_wakes_on_insertion() {
+        use futures_core::Stream;
+        use std::pin::Pin;
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use std::sync::{Arc, Mutex};
+        use std::task::{Context, Poll, Wake, Waker};
 
-        assert_eq!(list.head, Some(item1));
-        assert_eq!(list.tail, Some(item4));
-        assert_eq!(
-            list.arena.iter().collect::<Vec<_>>(),
-            vec![
-                (
-                    item1.index,
-                    &Item {
-                        data: 10,
-                        previous: None,
-                        next: Some(item3),
-                    }
-                ),
-                (
-                    item3.index,
-                    &Item {
-                        data: 30,
-                        previous: Some(item1),
-                        next: Some(item4),
-                    }
-                ),
-                (
-                    item4.index,
-                    &Item {
-                        data: 40,
-                        previous: Some(item3),
-                        next: None,
-                    }
-                )
-            ]
-        );
+        struct Flag(AtomicBool);
+        impl Wake for Flag {
+            fn wake(self: Arc<Self>) {
+                self.0.store(true, Ordering::SeqCst);
+            }
+        }
 
-        list.remove(item3);
-        assert_eq!(list.head, Some(item1));
-        assert_eq!(list.tail, Some(item4));
+        let queue = Arc::new(Mutex::new(GenerationalTokenList::new()));
+        let mut stream = GenerationalTokenList::stream_pop_front(&queue);
+        let flag = Arc::new(Flag(AtomicBool::new(false)));
+        let waker = Waker::from(flag.clone());
+        let mut cx = Context::from_waker(&waker);
+
+        assert_eq!(Pin::new(&mut stream).poll_next(&mut cx), Poll::Pending);
+        assert!(!flag.0.load(Ordering::SeqCst));
+        queue.lock().unwrap().push_back(1);
+        assert!(flag.0.load(Ordering::SeqCst));
         assert_eq!(
-            list.arena.iter().collect::<Vec<_>>(),
-            vec![
-                (
-                    item1.index,
-                    &Item {
-                        data: 10,
-                        previous: None,
-                        next: Some(item4),
-                    }
-                ),
-                (
-                    item4.index,
-                    &Item {
-                        data: 40,
-                        previous: Some(item1),
-                        next: None,
-                    }
-                )
-            ]
+            Pin::new(&mut stream).poll_next(&mut cx),
+            Poll::Ready(Some(1))
         );
     }
 
     #[test]
-    fn pop_front() {
+    fn playback_shuffle_plays_each_item_once_per_round() {
         let mut list = GenerationalTokenList::new();
-        list.push_back(10);
-        list.push_back(20);
-        list.push_back(30);
-        assert_eq_contents!(list, &[10, 20, 30]);
-        assert_eq!(list.pop_front(), Some(10));
-        assert_eq_contents!(list, &[20, 30]);
-        assert_eq!(list.pop_front(), Some(20));
-        assert_eq_contents!(list, &[30]);
-        assert_eq!(list.pop_front(), Some(30));
-        assert_eq_contents!(list, &[]);
+        let tokens: Vec<_> = (0..5).map(|i| list.push_back(i)).collect();
+        let mut cursor = crate::PlaybackCursor::new(crate::PlaybackMode::Shuffle);
+        cursor.set_seed(7);
+
+        let round: Vec<_> = (0..5).map(|_| cursor.next(&mut list).unwrap()).collect();
+        let last = *round.last().unwrap();
+        assert!(tokens.iter().all(|token| round.contains(token)));
+
+        // Going back retraces the history, skipping removed items
+        let before_last = cursor.prev(&mut list).unwrap();
+        list.remove(before_last);
+        assert_eq!(cursor.next(&mut list), Some(last));
+        assert_ne!(cursor.prev(&mut list), Some(before_last));
+
+        // A new round doesn't start with the item played last
+        let mut cursor = crate::PlaybackCursor::new(crate::PlaybackMode::Shuffle);
+        for _ in 0..4 {
+            cursor.next(&mut list);
+        }
+        let last = cursor.current(&list);
+        assert_ne!(cursor.next(&mut list), last);
     }
 
     #[test]
-    fn into_iter() {
-        let mut list = GenerationalTokenList::<i32>::new();
-        list.push_back(10);
-        list.push_back(20);
-        list.push_back(30);
+    fn replace_with_list_at_ends() {
+        let mut list = GenerationalTokenList::new();
+        let first = list.push_back(0);
+        let last = list.push_back(9);
 
-        let data = list.into_iter().collect::<Vec<_>>();
-        assert_eq!(data, vec![10, 20, 30]);
+        let front = list.replace_with_list(first, (1..3).collect());
+        crate::assert_list_eq!(list, [1, 2, 9]);
+        assert_eq!(list.head_token(), Some(front[0]));
+
+        assert!(list
+            .replace_with_list(last, GenerationalTokenList::new())
+            .is_empty());
+        crate::assert_list_eq!(list, [1, 2]);
+
+        list.remove(front[0]);
+        let only = list.replace_with_list(front[1], (3..5).collect());
+        crate::assert_list_eq!(list, [3, 4]);
+        assert_eq!(list.tail_token(), Some(only[1]));
     }
 
     #[test]
-    fn index() {
-        let mut list = GenerationalTokenList::<i32>::new();
-        let item1 = list.push_back(10);
-        let item2 = list.push_back(20);
-        let item3 = list.push_back(30);
-        assert_eq!(list[item1], 10);
-        assert_eq!(list[item2], 20);
-        assert_eq!(list[item3], 30);
+    fn owning_handles_can_be_nested() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+        use std::sync::{Arc, Mutex};
+
+        use crate::{ArcToken, RcToken};
+
+        struct RcNode {
+            _child: Option<RcToken<RcNode>>,
+        }
+        let list = Rc::new(RefCell::new(GenerationalTokenList::new()));
+        let leaf = RcToken::push_back(&list, RcNode { _child: None });
+        let middle = RcToken::push_back(&list, RcNode { _child: Some(leaf) });
+        let root = RcToken::push_back(
+            &list,
+            RcNode {
+                _child: Some(middle),
+            },
+        );
+        assert_eq!(list.borrow().len(), 3);
+        drop(root);
+        assert!(list.borrow().is_empty());
+
+        struct ArcNode {
+            _child: Option<ArcToken<ArcNode>>,
+        }
+        let list = Arc::new(Mutex::new(GenerationalTokenList::new()));
+        let leaf = ArcToken::push_back(&list, ArcNode { _child: None });
+        let middle = ArcToken::push_back(&list, ArcNode { _child: Some(leaf) });
+        let root = ArcToken::push_back(
+            &list,
+            ArcNode {
+                _child: Some(middle),
+            },
+        );
+        assert_eq!(list.lock().unwrap().len(), 3);
+        drop(root);
+        assert!(list.lock().unwrap().is_empty());
     }
 
+    #[cfg(feature = "debug-checks")]
     #[test]
-    fn index_mut() {
-        let mut list = GenerationalTokenList::<i32>::new();
-        let item1 = list.push_back(10);
-        let item2 = list.push_back(20);
-        let item3 = list.push_back(30);
+    #[should_panic(expected = "item was removed at op #3")]
+    fn index_reports_removal() {
+        let mut list = GenerationalTokenList::new();
+        list.push_back(1);
+        let item = list.push_back(2);
+        list.remove(item);
+        let _ = list[item];
+    }
 
-        list[item1] = 500;
-        list[item3] *= 9;
+    #[cfg(feature = "debug-checks")]
+    #[test]
+    #[should_panic(expected = "list was structurally changed during a token walk")]
+    fn token_walk_detects_removal() {
+        let mut list = GenerationalTokenList::new();
+        list.push_back(1);
+        let item = list.push_back(2);
 
-        assert_eq!(list[item1], 500);
-        assert_eq!(list[item2], 20);
-        assert_eq!(list[item3], 270);
+        let mut walk = list.walk_tokens();
+        walk.next(&list);
+        walk.resync(&list);
+        list.remove(item);
+        walk.next(&list);
     }
 
     #[test]
-    fn iter_with_tokens() {
-        let mut list = GenerationalTokenList::<i32>::new();
-        let item1 = list.push_back(10);
-        let item2 = list.push_back(20);
-        let item3 = list.push_back(30);
+    fn secondary_map_hides_removed_items() {
+        let mut list = GenerationalTokenList::new();
+        let item1 = list.push_back(1);
+        let item2 = list.push_back(2);
 
-        let mut iter = list.iter_with_tokens();
-        assert_eq!(iter.next(), Some((item1, &10)));
-        assert_eq!(iter.next(), Some((item2, &20)));
-        assert_eq!(iter.next(), Some((item3, &30)));
-        assert_eq!(iter.next(), None);
+        let mut map = crate::SecondaryMap::new();
+        map.insert(item1, "one");
+        map.insert(item2, "two");
+
+        list.remove(item1);
+        assert_eq!(map.get(&list, item1), None);
+        assert_eq!(map.get_mut(&list, item1), None);
+        assert!(!map.contains_key(&list, item1));
+        assert_eq!(map.iter(&list).collect::<Vec<_>>(), vec![(item2, &"two")]);
+        assert_eq!(map.get(&list, item2), Some(&"two"));
     }
 
     #[cfg(feature = "iter-mut")]
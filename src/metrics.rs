@@ -0,0 +1,121 @@
+// SPDX-License-Identifier: MIT
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::GenerationalTokenList;
+
+/// Operation counts of a [`GenerationalTokenList`], as returned by
+/// [`GenerationalTokenList::metrics`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ListMetrics {
+    /// Number of items inserted, by any push or insert method.
+    pub insertions: u64,
+    /// Number of items removed, not counting [`clear`](GenerationalTokenList::clear).
+    pub removals: u64,
+    /// Number of times the list's storage was reallocated to grow.
+    pub reallocations: u64,
+    /// Number of iterators created with [`iter`](GenerationalTokenList::iter) or
+    /// [`iter_with_tokens`](GenerationalTokenList::iter_with_tokens), including the ones used
+    /// internally by other methods.
+    pub traversals: u64,
+    /// Total number of items yielded by those iterators.
+    pub traversal_steps: u64,
+}
+
+/// The live counters. Atomics, so that traversals can be counted through `&self` while the list
+/// stays `Sync`.
+#[derive(Debug, Default)]
+pub(crate) struct Counters {
+    insertions: AtomicU64,
+    removals: AtomicU64,
+    reallocations: AtomicU64,
+    traversals: AtomicU64,
+    traversal_steps: AtomicU64,
+}
+
+impl Counters {
+    pub(crate) fn record_insertion(&self, reallocated: bool) {
+        self.insertions.fetch_add(1, Ordering::Relaxed);
+        if reallocated {
+            self.record_reallocation();
+        }
+    }
+
+    pub(crate) fn record_removal(&self) {
+        self.removals.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_reallocation(&self) {
+        self.reallocations.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_traversal(&self) {
+        self.traversals.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_traversal_step(&self) {
+        self.traversal_steps.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> ListMetrics {
+        ListMetrics {
+            insertions: self.insertions.load(Ordering::Relaxed),
+            removals: self.removals.load(Ordering::Relaxed),
+            reallocations: self.reallocations.load(Ordering::Relaxed),
+            traversals: self.traversals.load(Ordering::Relaxed),
+            traversal_steps: self.traversal_steps.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl<T> GenerationalTokenList<T> {
+    /// Returns the operation counts since the list was created (or cloned, or the counts were
+    /// reset).
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::new();
+    /// let item = list.push_back(1);
+    /// list.push_back(2);
+    /// list.remove(item);
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), vec![&2]);
+    ///
+    /// let metrics = list.metrics();
+    /// assert_eq!(metrics.insertions, 2);
+    /// assert_eq!(metrics.removals, 1);
+    /// assert_eq!(metrics.traversals, 1);
+    /// assert_eq!(metrics.traversal_steps, 1);
+    /// ```
+    pub fn metrics(&self) -> ListMetrics {
+        self.metrics.snapshot()
+    }
+
+    /// Reset all operation counts to zero.
+    pub fn reset_metrics(&mut self) {
+        self.metrics = Counters::default();
+    }
+
+    /// Publish the operation counts through the [metrics](https://crates.io/crates/metrics)
+    /// facade, as counters named `generational_token_list.<field>` with a `list` label set to
+    /// `name`. Call this periodically, e.g. when your exporter scrapes.
+    pub fn export_metrics(&self, name: &str) {
+        let metrics = self.metrics();
+        let counts = [
+            ("generational_token_list.insertions", metrics.insertions),
+            ("generational_token_list.removals", metrics.removals),
+            (
+                "generational_token_list.reallocations",
+                metrics.reallocations,
+            ),
+            ("generational_token_list.traversals", metrics.traversals),
+            (
+                "generational_token_list.traversal_steps",
+                metrics.traversal_steps,
+            ),
+        ];
+        for (key, value) in counts {
+            ::metrics::counter!(key, "list" => name.to_owned()).absolute(value);
+        }
+    }
+}
@@ -0,0 +1,183 @@
+// SPDX-License-Identifier: MIT
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::task::{Context, Poll, Waker};
+
+use crate::GenerationalTokenList;
+
+#[derive(Debug, Default)]
+struct State {
+    /// Number of structural changes so far.
+    changes: u64,
+    /// Set when the list is dropped.
+    closed: bool,
+    /// Tasks waiting in [`Changed`].
+    wakers: Vec<Waker>,
+}
+
+impl State {
+    fn add_waker(&mut self, waker: &Waker) {
+        if !self.wakers.iter().any(|other| other.will_wake(waker)) {
+            self.wakers.push(waker.clone());
+        }
+    }
+}
+
+/// The list's end of its subscriptions. Wakes everyone up when dropped together with the list.
+#[derive(Debug, Default)]
+pub(crate) struct Notifier {
+    state: Arc<Mutex<State>>,
+}
+
+impl Notifier {
+    pub(crate) fn notify(&self) {
+        let wakers = {
+            let mut state = lock(&self.state);
+            state.changes += 1;
+            std::mem::take(&mut state.wakers)
+        };
+        wakers.into_iter().for_each(Waker::wake);
+    }
+}
+
+impl Drop for Notifier {
+    fn drop(&mut self) {
+        let wakers = {
+            let mut state = lock(&self.state);
+            state.closed = true;
+            std::mem::take(&mut state.wakers)
+        };
+        wakers.into_iter().for_each(Waker::wake);
+    }
+}
+
+/// A handle for finding out about structural changes of a list: insertions, removals, unlinking and
+/// relinking, reordering and clearing. Changing the value of an item in place is not a structural
+/// change. Created by [`GenerationalTokenList::subscribe`].
+///
+/// The subscription does not borrow the list, so it can be moved into another task. Changes are
+/// coalesced: however many happened since the subscription last looked, it sees them as one.
+#[derive(Clone, Debug)]
+pub struct ChangeSubscription {
+    state: Arc<Mutex<State>>,
+    seen: u64,
+}
+
+impl ChangeSubscription {
+    /// Returns `true` if the list has changed since the subscription was created or last saw a
+    /// change.
+    pub fn has_changed(&self) -> bool {
+        lock(&self.state).changes != self.seen
+    }
+
+    /// Returns `true` if the list has been dropped, so that no more changes will happen.
+    pub fn is_closed(&self) -> bool {
+        lock(&self.state).closed
+    }
+
+    /// Mark all changes so far as seen.
+    pub fn mark_seen(&mut self) {
+        self.seen = lock(&self.state).changes;
+    }
+
+    /// Wait for the list to change. The returned future resolves to `true` (and marks the change
+    /// as seen) as soon as the list has changed since the subscription last saw a change, or to
+    /// `false` once the list has been dropped without changing.
+    pub fn changed(&mut self) -> Changed<'_> {
+        Changed { subscription: self }
+    }
+
+    /// Mark all changes so far as seen, and wake `waker` on the next one.
+    #[cfg(feature = "futures")]
+    pub(crate) fn wake_on_change(&mut self, waker: &Waker) {
+        let mut state = lock(&self.state);
+        self.seen = state.changes;
+        state.add_waker(waker);
+    }
+}
+
+/// Future returned by [`ChangeSubscription::changed`].
+#[derive(Debug)]
+#[must_use = "futures do nothing unless polled"]
+pub struct Changed<'a> {
+    subscription: &'a mut ChangeSubscription,
+}
+
+impl Future for Changed<'_> {
+    type Output = bool;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<bool> {
+        let subscription = &mut *self.subscription;
+        let mut state = lock(&subscription.state);
+        if state.changes != subscription.seen {
+            subscription.seen = state.changes;
+            return Poll::Ready(true);
+        }
+        if state.closed {
+            return Poll::Ready(false);
+        }
+
+        state.add_waker(cx.waker());
+        Poll::Pending
+    }
+}
+
+impl<T> GenerationalTokenList<T> {
+    /// Subscribe to structural changes of the list, e.g. to re-render a view only when items were
+    /// inserted, removed or moved.
+    ///
+    /// Subscriptions are not carried over to clones of the list.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::new();
+    /// let item = list.push_back(1);
+    ///
+    /// let mut changes = list.subscribe();
+    /// assert!(!changes.has_changed());
+    ///
+    /// list[item] = 2;
+    /// assert!(!changes.has_changed());
+    ///
+    /// list.push_back(3);
+    /// list.remove(item);
+    /// assert!(changes.has_changed());
+    /// changes.mark_seen();
+    /// assert!(!changes.has_changed());
+    /// ```
+    ///
+    /// Awaiting a change:
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// # use std::future::Future;
+    /// # use std::pin::pin;
+    /// # use std::task::{Context, Poll, Waker};
+    /// let mut list = GenerationalTokenList::new();
+    /// let mut changes = list.subscribe();
+    /// let mut cx = Context::from_waker(Waker::noop());
+    ///
+    /// assert_eq!(pin!(changes.changed()).poll(&mut cx), Poll::Pending);
+    /// list.push_back(1);
+    /// assert_eq!(pin!(changes.changed()).poll(&mut cx), Poll::Ready(true));
+    /// drop(list);
+    /// assert_eq!(pin!(changes.changed()).poll(&mut cx), Poll::Ready(false));
+    /// ```
+    pub fn subscribe(&mut self) -> ChangeSubscription {
+        let state = self
+            .notifier
+            .get_or_insert_with(Default::default)
+            .state
+            .clone();
+        let seen = lock(&state).changes;
+        ChangeSubscription { state, seen }
+    }
+}
+
+fn lock(state: &Mutex<State>) -> MutexGuard<'_, State> {
+    state
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
@@ -0,0 +1,269 @@
+// SPDX-License-Identifier: MIT
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+use crate::{Bias, GenerationalTokenList, ItemToken, SavedPosition, TokenSet};
+
+/// How a [`PlaybackCursor`] moves through its list.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PlaybackMode {
+    /// Play the items in list order and stop after the last one.
+    StopAtEnd,
+    /// Play the items in list order, starting over from the first one after the last one.
+    RepeatAll,
+    /// Play the current item over and over. Once it is removed, move on in list order.
+    RepeatOne,
+    /// Play the items in random order, each one once before any is repeated. Going back retraces
+    /// the items played so far.
+    Shuffle,
+}
+
+/// A cursor for playing the items of a list one after the other, like the tracks of a playlist.
+///
+/// The cursor doesn't borrow the list: items can be added, removed and reordered in between calls
+/// to [`next`](Self::next) and [`prev`](Self::prev), which pick up from where the current item is
+/// (or was, if it has been removed) and skip items that have been removed since they were played.
+/// A cursor must only ever be used with one list.
+///
+/// # Examples
+/// ```
+/// # use generational_token_list::{GenerationalTokenList, PlaybackCursor, PlaybackMode};
+/// let mut playlist = GenerationalTokenList::new();
+/// let intro = playlist.push_back("intro");
+/// let verse = playlist.push_back("verse");
+/// let outro = playlist.push_back("outro");
+///
+/// let mut cursor = PlaybackCursor::new(PlaybackMode::RepeatAll);
+/// assert_eq!(cursor.next(&mut playlist), Some(intro));
+/// assert_eq!(cursor.next(&mut playlist), Some(verse));
+///
+/// // The current track is removed while playing
+/// playlist.remove(verse);
+/// assert_eq!(cursor.next(&mut playlist), Some(outro));
+/// assert_eq!(cursor.next(&mut playlist), Some(intro));
+/// ```
+#[derive(Debug)]
+pub struct PlaybackCursor {
+    mode: PlaybackMode,
+    current: Option<ItemToken>,
+    /// Where the current item is, kept up to date when it is removed.
+    position: Option<SavedPosition>,
+    /// Items played in the current pass over the list.
+    played: TokenSet,
+    /// Items played in shuffle mode, in order.
+    history: Vec<ItemToken>,
+    /// Number of entries of `history` up to and including the current item.
+    history_position: usize,
+    /// State of the random number generator for shuffle mode.
+    seed: u64,
+}
+
+impl PlaybackCursor {
+    /// Creates a new cursor, before the first item. Shuffle mode is seeded randomly; use
+    /// [`set_seed`](Self::set_seed) for a reproducible order.
+    pub fn new(mode: PlaybackMode) -> Self {
+        PlaybackCursor {
+            mode,
+            current: None,
+            position: None,
+            played: TokenSet::new(),
+            history: Vec::new(),
+            history_position: 0,
+            seed: RandomState::new().build_hasher().finish(),
+        }
+    }
+
+    /// Returns the playback mode.
+    pub fn mode(&self) -> PlaybackMode {
+        self.mode
+    }
+
+    /// Change the playback mode. This forgets which items have been played, including the shuffle
+    /// history, but keeps the current item.
+    pub fn set_mode(&mut self, mode: PlaybackMode) {
+        self.mode = mode;
+        self.played.clear();
+        self.history.clear();
+        self.history_position = 0;
+        if let Some(current) = self.current {
+            self.played.insert(current);
+            self.history.push(current);
+            self.history_position = 1;
+        }
+    }
+
+    /// Seed the random number generator used by shuffle mode.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+    }
+
+    /// Returns the token of the current item, or `None` before the first call to
+    /// [`next`](Self::next) or if the current item has been removed.
+    pub fn current<T>(&self, list: &GenerationalTokenList<T>) -> Option<ItemToken> {
+        self.current.filter(|&token| is_live(list, token))
+    }
+
+    /// Make the item identified by given token the current one, e.g. when the user picks an item
+    /// to play.
+    ///
+    /// # Panics
+    /// Panics if `token` is invalid.
+    pub fn jump_to<T>(&mut self, list: &mut GenerationalTokenList<T>, token: ItemToken) {
+        self.history.truncate(self.history_position);
+        self.history.push(token);
+        self.history_position = self.history.len();
+        self.played.insert(token);
+        self.set_current(list, token);
+    }
+
+    /// Move to the next item according to the playback mode and return its token, or `None` if
+    /// there is nothing left to play. In [`StopAtEnd`](PlaybackMode::StopAtEnd) mode, the cursor
+    /// stays on the last item.
+    ///
+    /// In shuffle mode, picking a new item takes time proportional to the length of the list.
+    pub fn next<T>(&mut self, list: &mut GenerationalTokenList<T>) -> Option<ItemToken> {
+        match self.mode {
+            PlaybackMode::RepeatOne if self.current(list).is_some() => self.current,
+            PlaybackMode::Shuffle => self.shuffle_next(list),
+            _ => self.step_in_order(list, true),
+        }
+    }
+
+    /// Move to the previous item and return its token, or `None` if there is none. In shuffle
+    /// mode, this goes back through the items played so far.
+    pub fn prev<T>(&mut self, list: &mut GenerationalTokenList<T>) -> Option<ItemToken> {
+        match self.mode {
+            PlaybackMode::RepeatOne if self.current(list).is_some() => self.current,
+            PlaybackMode::Shuffle => self.shuffle_prev(list),
+            _ => self.step_in_order(list, false),
+        }
+    }
+
+    fn step_in_order<T>(
+        &mut self,
+        list: &mut GenerationalTokenList<T>,
+        forward: bool,
+    ) -> Option<ItemToken> {
+        let first = |list: &GenerationalTokenList<T>| {
+            if forward {
+                list.head_token()
+            } else {
+                list.tail_token()
+            }
+        };
+        let step = |list: &GenerationalTokenList<T>, token| {
+            if forward {
+                list.next_token(token)
+            } else {
+                list.prev_token(token)
+            }
+        };
+
+        let candidate = match self.current {
+            None => first(list),
+            Some(current) if is_live(list, current) => step(list, current),
+            Some(_) => {
+                // The current item is gone, and its position moved on to the item after it, or
+                // to the one before it if there was none. Items before the current one have
+                // been played in this pass, which tells the two apart.
+                let anchor = self.position.as_ref().and_then(SavedPosition::token);
+                match anchor.filter(|&anchor| is_live(list, anchor)) {
+                    Some(anchor) if self.played.contains(anchor) == forward => step(list, anchor),
+                    Some(anchor) => Some(anchor),
+                    None => first(list),
+                }
+            }
+        };
+
+        let token = match candidate {
+            Some(token) => token,
+            None if self.mode == PlaybackMode::StopAtEnd => return None,
+            None => {
+                self.played.clear();
+                first(list)?
+            }
+        };
+        if forward {
+            self.played.insert(token);
+        } else if let Some(current) = self.current {
+            self.played.remove(current);
+        }
+        self.set_current(list, token);
+        Some(token)
+    }
+
+    fn shuffle_next<T>(&mut self, list: &mut GenerationalTokenList<T>) -> Option<ItemToken> {
+        while self.history_position < self.history.len() {
+            let token = self.history[self.history_position];
+            self.history_position += 1;
+            if is_live(list, token) {
+                self.set_current(list, token);
+                return Some(token);
+            }
+        }
+
+        let unplayed = |played: &TokenSet| {
+            list.iter_with_tokens()
+                .map(|(token, _)| token)
+                .filter(|&token| !played.contains(token))
+                .collect::<Vec<_>>()
+        };
+        let mut candidates = unplayed(&self.played);
+        if candidates.is_empty() {
+            // Everything has been played, start a new round without repeating the current item
+            // right away
+            self.played.clear();
+            if let Some(current) = self.current(list) {
+                if list.len() > 1 {
+                    self.played.insert(current);
+                }
+            }
+            candidates = unplayed(&self.played);
+            self.played.clear();
+        }
+        if candidates.is_empty() {
+            return None;
+        }
+
+        let token = candidates[(self.random() % candidates.len() as u64) as usize];
+        self.played.insert(token);
+        self.history.push(token);
+        self.history_position = self.history.len();
+        self.set_current(list, token);
+        Some(token)
+    }
+
+    fn shuffle_prev<T>(&mut self, list: &mut GenerationalTokenList<T>) -> Option<ItemToken> {
+        let mut position = self.history_position;
+        while position > 1 {
+            position -= 1;
+            let token = self.history[position - 1];
+            if is_live(list, token) {
+                self.history_position = position;
+                self.set_current(list, token);
+                return Some(token);
+            }
+        }
+        None
+    }
+
+    fn set_current<T>(&mut self, list: &mut GenerationalTokenList<T>, token: ItemToken) {
+        self.current = Some(token);
+        self.position = Some(list.save_position(token, Bias::After));
+    }
+
+    /// Returns the next number from a splitmix64 generator.
+    fn random(&mut self) -> u64 {
+        self.seed = self.seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.seed;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+/// Returns `true` if the token is valid and its item is linked into the list.
+fn is_live<T>(list: &GenerationalTokenList<T>, token: ItemToken) -> bool {
+    list.get(token).is_some() && !list.is_unlinked(token)
+}
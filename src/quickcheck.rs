@@ -0,0 +1,58 @@
+// SPDX-License-Identifier: MIT
+
+use ::quickcheck::{Arbitrary, Gen};
+
+use crate::GenerationalTokenList;
+
+/// Generates lists of arbitrary items, built with a random mix of insertions at either end and
+/// temporary items that are removed again, so that lists don't always have their items in arena
+/// order and start out with a generation of zero.
+///
+/// Shrinking removes items through [`remove`](GenerationalTokenList::remove), first halves of the
+/// list and then single items, so the tokens of the remaining items stay valid in each shrunk
+/// list.
+impl<T> Arbitrary for GenerationalTokenList<T>
+where
+    T: Arbitrary,
+{
+    fn arbitrary(g: &mut Gen) -> Self {
+        let items = Vec::<T>::arbitrary(g);
+        let mut list = Self::with_capacity(items.len());
+
+        for item in items {
+            if bool::arbitrary(g) {
+                let temporary = list.push_back(T::arbitrary(g));
+                list.remove(temporary);
+            }
+            if bool::arbitrary(g) {
+                list.push_back(item);
+            } else {
+                list.push_front(item);
+            }
+        }
+        list
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        let list = self.clone();
+        let tokens = self
+            .iter_with_tokens()
+            .map(|(token, _)| token)
+            .collect::<Vec<_>>();
+        let half = tokens.len() / 2;
+
+        let halves = match half {
+            0 => vec![],
+            _ => vec![tokens[..half].to_vec(), tokens[half..].to_vec()],
+        };
+        let singles = tokens.into_iter().map(|token| vec![token]);
+
+        Box::new(halves.into_iter().chain(singles).map(move |removed| {
+            let mut shrunk = list.clone();
+            for token in removed {
+                shrunk.remove(token);
+            }
+            shrunk
+        }))
+    }
+}
@@ -0,0 +1,200 @@
+// SPDX-License-Identifier: MIT
+
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::{GenerationalTokenList, ItemToken};
+
+/// How many random slots to probe before falling back to picking a position and walking the list.
+const MAX_PROBES: usize = 32;
+
+impl<T> GenerationalTokenList<T> {
+    /// Returns the token of a uniformly random item in the list, or `None` if the list is empty.
+    ///
+    /// This probes random arena slots until it hits an item, which takes a handful of tries unless
+    /// most of the list's capacity is unused; only then does it fall back to walking the list.
+    ///
+    /// # Examples
+    /// ```
+    /// # use rand::SeedableRng;
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+    /// let mut list = GenerationalTokenList::new();
+    /// assert_eq!(list.random_token(&mut rng), None);
+    ///
+    /// let backends = [list.push_back("a"), list.push_back("b"), list.push_back("c")];
+    /// let backend = list.random_token(&mut rng).unwrap();
+    /// assert!(backends.contains(&backend));
+    /// ```
+    pub fn random_token<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<ItemToken> {
+        if self.is_empty() {
+            return None;
+        }
+
+        for _ in 0..MAX_PROBES {
+            let slot = rng.gen_range(0..self.arena.capacity());
+            if let Some((_, index)) = self.arena.get_unknown_gen(slot) {
+                let token = ItemToken { index };
+                if !self.is_unlinked(token) {
+                    return Some(token);
+                }
+            }
+        }
+
+        self.token_at(rng.gen_range(0..self.len()))
+    }
+
+    /// Returns a reference to a uniformly random item in the list, or `None` if the list is empty.
+    /// See [`random_token`](Self::random_token).
+    ///
+    /// # Examples
+    /// ```
+    /// # use rand::SeedableRng;
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+    /// let list = vec![1, 2, 3].into_iter().collect::<GenerationalTokenList<_>>();
+    /// assert!((1..=3).contains(list.choose(&mut rng).unwrap()));
+    /// ```
+    pub fn choose<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<&T> {
+        self.random_token(rng).map(|token| &self[token])
+    }
+
+    /// Randomly reorder the list. Items are relinked rather than moved, so tokens stay valid and
+    /// keep referring to the same items.
+    ///
+    /// # Examples
+    /// ```
+    /// # use rand::SeedableRng;
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+    /// let mut playlist = GenerationalTokenList::new();
+    /// let favorite = playlist.push_back("favorite");
+    /// for _ in 0..10 {
+    ///     playlist.push_back("other");
+    /// }
+    ///
+    /// playlist.shuffle(&mut rng);
+    /// assert_eq!(playlist.len(), 11);
+    /// assert_eq!(playlist.get(favorite), Some(&"favorite"));
+    /// ```
+    pub fn shuffle<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        let mut tokens: Vec<_> = self.iter_with_tokens().map(|(token, _)| token).collect();
+        tokens.shuffle(rng);
+        self.relink_in_order(&tokens);
+    }
+
+    /// Pick `k` distinct items uniformly at random (or all of them, if the list has fewer than `k`
+    /// items) in a single pass, without collecting the tokens first. The result is in list order.
+    ///
+    /// # Examples
+    /// ```
+    /// # use rand::SeedableRng;
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+    /// let list = (0..100).collect::<GenerationalTokenList<_>>();
+    ///
+    /// let sample = list.sample(&mut rng, 5);
+    /// assert_eq!(sample.len(), 5);
+    /// assert!(sample.windows(2).all(|pair| pair[0].1 < pair[1].1));
+    /// ```
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R, k: usize) -> Vec<(ItemToken, &T)> {
+        let mut reservoir = Vec::with_capacity(k.min(self.len()));
+        for (position, token, data) in self.enumerate_with_tokens() {
+            if position < k {
+                reservoir.push((position, token, data));
+            } else {
+                let replace = rng.gen_range(0..=position);
+                if replace < k {
+                    reservoir[replace] = (position, token, data);
+                }
+            }
+        }
+
+        reservoir.sort_by_key(|(position, _, _)| *position);
+        reservoir
+            .into_iter()
+            .map(|(_, token, data)| (token, data))
+            .collect()
+    }
+
+    /// Like [`sample`](Self::sample), but the chance of each item to be picked is proportional to
+    /// `weight(item)`. Items with a weight that is not positive (or is NaN) are never picked.
+    ///
+    /// # Examples
+    /// ```
+    /// # use rand::SeedableRng;
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+    /// let servers = vec![("big", 10.0), ("small", 1.0), ("down", 0.0)]
+    ///     .into_iter()
+    ///     .collect::<GenerationalTokenList<_>>();
+    ///
+    /// let picked = servers.sample_weighted(&mut rng, 2, |server| server.1);
+    /// let names = picked.iter().map(|(_, server)| server.0).collect::<Vec<_>>();
+    /// assert_eq!(names, vec!["big", "small"]);
+    /// ```
+    pub fn sample_weighted<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        k: usize,
+        mut weight: impl FnMut(&T) -> f64,
+    ) -> Vec<(ItemToken, &T)> {
+        if k == 0 {
+            return Vec::new();
+        }
+
+        // Efraimidis-Spirakis: give each item the key u^(1/weight) and keep the k largest keys
+        let mut heap = BinaryHeap::with_capacity(k + 1);
+        for (position, token, data) in self.enumerate_with_tokens() {
+            let weight = weight(data);
+            if weight.is_nan() || weight <= 0.0 {
+                continue;
+            }
+
+            let key = rng.gen::<f64>().powf(1.0 / weight);
+            if heap.len() < k {
+                heap.push(Reverse(SampleKey(key, position, token, data)));
+            } else if heap
+                .peek()
+                .is_some_and(|Reverse(smallest)| key > smallest.0)
+            {
+                heap.pop();
+                heap.push(Reverse(SampleKey(key, position, token, data)));
+            }
+        }
+
+        let mut picked: Vec<_> = heap.into_iter().map(|Reverse(key)| key).collect();
+        picked.sort_by_key(|key| key.1);
+        picked
+            .into_iter()
+            .map(|SampleKey(_, _, token, data)| (token, data))
+            .collect()
+    }
+}
+
+/// Entry of the heap in [`GenerationalTokenList::sample_weighted`]: (key, position, token, data),
+/// ordered by key.
+struct SampleKey<'a, T>(f64, usize, ItemToken, &'a T);
+
+impl<T> PartialEq for SampleKey<'_, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T> Eq for SampleKey<'_, T> {}
+
+impl<T> PartialOrd for SampleKey<'_, T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for SampleKey<'_, T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
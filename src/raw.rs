@@ -0,0 +1,100 @@
+// SPDX-License-Identifier: MIT
+
+use std::collections::HashMap;
+
+use crate::{GenerationalTokenList, ItemToken};
+
+impl<T> GenerationalTokenList<T> {
+    /// Returns a plain number identifying the item of `token`, or `None` if the token is invalid.
+    ///
+    /// The id is unique among the items currently in the list (it is the item's arena slot), but
+    /// may be reused for a later item once this one is removed. It is meant for writing references
+    /// to items alongside [`export_raw`](Self::export_raw), so they can be resolved again after
+    /// [`import_raw`](Self::import_raw).
+    pub fn raw_id(&self, token: ItemToken) -> Option<u64> {
+        self.get(token).map(|_| token.slot() as u64)
+    }
+
+    /// Returns the items in list order, each with its [`raw_id`](Self::raw_id). Unlinked items are
+    /// not included.
+    ///
+    /// Together with [`import_raw`](Self::import_raw) this allows saving and restoring a list in a
+    /// custom format, including references to its items, without any serialization framework.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::new();
+    /// let first = list.push_back("first");
+    /// list.push_back("second");
+    /// let bookmark = list.raw_id(first).unwrap();
+    ///
+    /// let saved = list
+    ///     .export_raw()
+    ///     .into_iter()
+    ///     .map(|(id, data)| (id, data.to_string()))
+    ///     .collect::<Vec<_>>();
+    ///
+    /// let (restored, tokens) = GenerationalTokenList::import_raw(saved);
+    /// assert_eq!(restored.iter().collect::<Vec<_>>(), vec!["first", "second"]);
+    /// assert_eq!(restored[tokens[&bookmark]], "first");
+    /// ```
+    pub fn export_raw(&self) -> Vec<(u64, &T)> {
+        self.iter_with_tokens()
+            .map(|(token, data)| (token.slot() as u64, data))
+            .collect()
+    }
+
+    /// Build a list from items in list order, each with an id as returned by
+    /// [`export_raw`](Self::export_raw). Returns the list along with a map from those ids to the
+    /// tokens of the new items.
+    ///
+    /// Tokens of the exported list are not valid for the imported one (the arena can't recreate
+    /// an item at a given slot and generation), which is why references have to go through ids.
+    ///
+    /// # Panics
+    /// Panics if an id occurs more than once.
+    pub fn import_raw(
+        items: impl IntoIterator<Item = (u64, T)>,
+    ) -> (Self, HashMap<u64, ItemToken>) {
+        let items = items.into_iter();
+        let mut list = Self::with_capacity(items.size_hint().0);
+        let mut tokens = HashMap::with_capacity(items.size_hint().0);
+
+        for (id, data) in items {
+            let token = list.push_back(data);
+            assert!(tokens.insert(id, token).is_none(), "duplicate id {}", id);
+        }
+
+        (list, tokens)
+    }
+    /// Returns an iterator over all slots of the underlying arena, in slot order: `Some` with the
+    /// token and item for occupied slots, `None` for vacant ones. The position of a slot in the
+    /// iteration is its [`raw_id`](Self::raw_id), and the iteration is
+    /// [`capacity`](Self::capacity) slots long.
+    ///
+    /// This exposes the storage layout (e.g. for analyzing fragmentation or writing out the arena
+    /// as is) without exposing the arena itself. Unlinked items are included; use
+    /// [`is_unlinked`](Self::is_unlinked) to tell them apart.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::with_capacity(3);
+    /// let a = list.push_back("a");
+    /// let b = list.push_back("b");
+    /// list.push_front("c");
+    /// list.remove(b);
+    ///
+    /// let slots = list.iter_slots().map(|slot| slot.map(|(_, data)| *data)).collect::<Vec<_>>();
+    /// assert_eq!(slots, vec![Some("a"), None, Some("c")]);
+    /// assert_eq!(list.iter_slots().next(), Some(Some((a, &"a"))));
+    /// ```
+    pub fn iter_slots(&self) -> impl Iterator<Item = Option<(ItemToken, &T)>> + '_ {
+        (0..self.arena.capacity()).map(move |slot| {
+            self.arena
+                .get_unknown_gen(slot)
+                .map(|(item, index)| (ItemToken { index }, &item.data))
+        })
+    }
+}
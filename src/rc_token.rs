@@ -0,0 +1,188 @@
+// SPDX-License-Identifier: MIT
+
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+use std::sync::{Arc, Mutex};
+
+use crate::{GenerationalTokenList, ItemToken};
+
+/// A reference-counted handle to an item in a list shared as `Rc<RefCell<GenerationalTokenList<T>>>`.
+/// When the last clone of a handle is dropped, the item is removed from the list.
+///
+/// The handle only holds a weak reference to the list, so it does not keep the list alive. If the
+/// item was already removed by other means, dropping the last handle does nothing.
+///
+/// # Panics
+/// Dropping the last handle panics if the list is currently borrowed (e.g. while iterating it).
+///
+/// # Examples
+/// ```
+/// # use std::cell::RefCell;
+/// # use std::rc::Rc;
+/// # use generational_token_list::{GenerationalTokenList, RcToken};
+/// let list = Rc::new(RefCell::new(GenerationalTokenList::new()));
+/// let a = RcToken::push_back(&list, "a");
+/// let b = RcToken::push_back(&list, "b");
+///
+/// let b2 = b.clone();
+/// drop(b);
+/// assert_eq!(list.borrow().len(), 2);
+///
+/// drop(b2);
+/// assert_eq!(list.borrow().iter().collect::<Vec<_>>(), vec![&"a"]);
+/// assert_eq!(list.borrow().get(a.token()), Some(&"a"));
+/// ```
+pub struct RcToken<T> {
+    inner: Rc<RcTokenInner<T>>,
+}
+
+struct RcTokenInner<T> {
+    list: Weak<RefCell<GenerationalTokenList<T>>>,
+    token: ItemToken,
+}
+
+impl<T> RcToken<T> {
+    /// Take ownership of the item identified by `token` in `list`.
+    pub fn new(list: &Rc<RefCell<GenerationalTokenList<T>>>, token: ItemToken) -> Self {
+        RcToken {
+            inner: Rc::new(RcTokenInner {
+                list: Rc::downgrade(list),
+                token,
+            }),
+        }
+    }
+
+    /// Append an item to the back of `list` and return a handle that owns it.
+    pub fn push_back(list: &Rc<RefCell<GenerationalTokenList<T>>>, data: T) -> Self {
+        let token = list.borrow_mut().push_back(data);
+        Self::new(list, token)
+    }
+
+    /// Prepend an item to the front of `list` and return a handle that owns it.
+    pub fn push_front(list: &Rc<RefCell<GenerationalTokenList<T>>>, data: T) -> Self {
+        let token = list.borrow_mut().push_front(data);
+        Self::new(list, token)
+    }
+
+    /// Returns the token of the owned item.
+    pub fn token(&self) -> ItemToken {
+        self.inner.token
+    }
+}
+
+impl<T> Clone for RcToken<T> {
+    fn clone(&self) -> Self {
+        RcToken {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for RcToken<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("RcToken").field(&self.inner.token).finish()
+    }
+}
+
+impl<T> Drop for RcTokenInner<T> {
+    fn drop(&mut self) {
+        if let Some(list) = self.list.upgrade() {
+            // Drop the item only once the list is no longer borrowed, as it may own handles to
+            // other items
+            let removed = list.borrow_mut().remove(self.token);
+            drop(removed);
+        }
+    }
+}
+
+/// The thread-safe counterpart of [`RcToken`], for lists shared as
+/// `Arc<Mutex<GenerationalTokenList<T>>>`.
+///
+/// # Panics
+/// Dropping the last handle locks the list, so it deadlocks or panics if the dropping thread holds
+/// the lock already. A poisoned lock is ignored and the item is removed anyway.
+///
+/// # Examples
+/// ```
+/// # use std::sync::{Arc, Mutex};
+/// # use generational_token_list::{ArcToken, GenerationalTokenList};
+/// let list = Arc::new(Mutex::new(GenerationalTokenList::new()));
+/// let job = ArcToken::push_back(&list, "job");
+///
+/// let worker = std::thread::spawn({
+///     let job = job.clone();
+///     move || drop(job)
+/// });
+/// worker.join().unwrap();
+/// assert_eq!(list.lock().unwrap().len(), 1);
+///
+/// drop(job);
+/// assert!(list.lock().unwrap().is_empty());
+/// ```
+pub struct ArcToken<T> {
+    inner: Arc<ArcTokenInner<T>>,
+}
+
+struct ArcTokenInner<T> {
+    list: std::sync::Weak<Mutex<GenerationalTokenList<T>>>,
+    token: ItemToken,
+}
+
+impl<T> ArcToken<T> {
+    /// Take ownership of the item identified by `token` in `list`.
+    pub fn new(list: &Arc<Mutex<GenerationalTokenList<T>>>, token: ItemToken) -> Self {
+        ArcToken {
+            inner: Arc::new(ArcTokenInner {
+                list: Arc::downgrade(list),
+                token,
+            }),
+        }
+    }
+
+    /// Append an item to the back of `list` and return a handle that owns it.
+    pub fn push_back(list: &Arc<Mutex<GenerationalTokenList<T>>>, data: T) -> Self {
+        let token = lock(list).push_back(data);
+        Self::new(list, token)
+    }
+
+    /// Prepend an item to the front of `list` and return a handle that owns it.
+    pub fn push_front(list: &Arc<Mutex<GenerationalTokenList<T>>>, data: T) -> Self {
+        let token = lock(list).push_front(data);
+        Self::new(list, token)
+    }
+
+    /// Returns the token of the owned item.
+    pub fn token(&self) -> ItemToken {
+        self.inner.token
+    }
+}
+
+impl<T> Clone for ArcToken<T> {
+    fn clone(&self) -> Self {
+        ArcToken {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for ArcToken<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("ArcToken").field(&self.inner.token).finish()
+    }
+}
+
+impl<T> Drop for ArcTokenInner<T> {
+    fn drop(&mut self) {
+        if let Some(list) = self.list.upgrade() {
+            // Drop the item only once the list is unlocked, as it may own handles to other items
+            let removed = lock(&list).remove(self.token);
+            drop(removed);
+        }
+    }
+}
+
+fn lock<T>(
+    list: &Mutex<GenerationalTokenList<T>>,
+) -> std::sync::MutexGuard<'_, GenerationalTokenList<T>> {
+    list.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
@@ -0,0 +1,78 @@
+// SPDX-License-Identifier: MIT
+
+use crate::{GenerationalTokenList, ItemToken};
+
+impl<T> GenerationalTokenList<T> {
+    /// Remove the item identified by given token like [`remove`](Self::remove), but instead of
+    /// returning or dropping the value, keep it for reuse by
+    /// [`push_back_recycled`](Self::push_back_recycled) or
+    /// [`push_front_recycled`](Self::push_front_recycled). Returns `false` if the token is invalid
+    /// or the item is protected by a [`RemovalGuard`](crate::RemovalGuard).
+    ///
+    /// This saves freeing and reallocating whatever the value owns (e.g. large buffers) when items
+    /// are removed and inserted over and over. Parked values stay around until they are reused or
+    /// dropped with [`clear_recycled`](Self::clear_recycled), and are not carried over to clones.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::<Vec<u8>>::new();
+    /// let item = list.push_back(Vec::with_capacity(4096));
+    /// assert!(list.remove_recycled(item));
+    /// assert_eq!(list.recycled_len(), 1);
+    ///
+    /// let item = list.push_back_recycled(|buffer| buffer.clear());
+    /// assert!(list[item].capacity() >= 4096);
+    /// assert_eq!(list.recycled_len(), 0);
+    /// ```
+    pub fn remove_recycled(&mut self, token: ItemToken) -> bool {
+        match self.remove(token) {
+            Some(data) => {
+                self.recycled.push(data);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Append an item to the end of the list, reusing a value parked by
+    /// [`remove_recycled`](Self::remove_recycled) (or `T::default()` if there is none) after
+    /// passing it to `reset`. Returns a token which corresponds to the new item.
+    pub fn push_back_recycled(&mut self, reset: impl FnOnce(&mut T)) -> ItemToken
+    where
+        T: Default,
+    {
+        let data = self.take_recycled(reset);
+        self.push_back(data)
+    }
+
+    /// Prepend an item to the beginning of the list, reusing a value parked by
+    /// [`remove_recycled`](Self::remove_recycled) (or `T::default()` if there is none) after
+    /// passing it to `reset`. Returns a token which corresponds to the new item.
+    pub fn push_front_recycled(&mut self, reset: impl FnOnce(&mut T)) -> ItemToken
+    where
+        T: Default,
+    {
+        let data = self.take_recycled(reset);
+        self.push_front(data)
+    }
+
+    /// Returns the number of values parked for reuse.
+    pub fn recycled_len(&self) -> usize {
+        self.recycled.len()
+    }
+
+    /// Drop all values parked for reuse.
+    pub fn clear_recycled(&mut self) {
+        self.recycled.clear();
+    }
+
+    fn take_recycled(&mut self, reset: impl FnOnce(&mut T)) -> T
+    where
+        T: Default,
+    {
+        let mut data = self.recycled.pop().unwrap_or_default();
+        reset(&mut data);
+        data
+    }
+}
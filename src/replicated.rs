@@ -0,0 +1,228 @@
+// SPDX-License-Identifier: MIT
+
+use std::collections::HashMap;
+
+use crate::{GenerationalTokenList, ItemToken};
+
+/// Globally unique identifier of an element of a [`ReplicatedList`]: a Lamport timestamp plus the
+/// id of the replica that created the element. Ordered by timestamp first, then by replica.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct OpId {
+    /// Lamport timestamp.
+    pub counter: u64,
+    /// Id of the replica that created the element.
+    pub replica: u64,
+}
+
+/// An operation produced by a [`ReplicatedList`], to be sent to the other replicas and applied
+/// there with [`ReplicatedList::apply`]. All fields are plain data, so ops can be serialized in any
+/// format.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReplicatedOp<T> {
+    /// Insert `value` as a new element `id`, directly after element `after` (or at the beginning
+    /// if `after` is `None`).
+    Insert {
+        id: OpId,
+        after: Option<OpId>,
+        value: T,
+    },
+    /// Delete element `id`.
+    Delete { id: OpId },
+}
+
+#[derive(Clone, Debug)]
+struct Element<T> {
+    id: OpId,
+    value: T,
+    deleted: bool,
+}
+
+/// A list that can be edited concurrently on several replicas and converges to the same contents
+/// on all of them, using the RGA (replicated growable array) algorithm.
+///
+/// Every local edit returns a [`ReplicatedOp`]; once every replica has applied every other
+/// replica's ops, all replicas have the same contents, no matter in which order concurrent ops
+/// arrived. Ops must be delivered in causal order, i.e. an op must be applied after the ops it
+/// depends on (in practice: after all ops its replica had seen when it was created).
+///
+/// Deleted elements are kept as tombstones, so that concurrent inserts next to them can still be
+/// placed correctly.
+///
+/// # Examples
+/// ```
+/// # use generational_token_list::ReplicatedList;
+/// let mut alice = ReplicatedList::new(1);
+/// let mut bob = ReplicatedList::new(2);
+///
+/// let op = alice.insert(0, 'a');
+/// bob.apply(op);
+///
+/// // Concurrent edits
+/// let from_alice = alice.insert(1, 'b');
+/// let from_bob = bob.insert(1, 'c');
+/// alice.apply(from_bob);
+/// bob.apply(from_alice);
+///
+/// assert_eq!(alice.iter().collect::<String>(), bob.iter().collect::<String>());
+/// assert_eq!(alice.len(), 3);
+/// ```
+#[derive(Clone, Debug)]
+pub struct ReplicatedList<T> {
+    replica: u64,
+    clock: u64,
+    elements: GenerationalTokenList<Element<T>>,
+    tokens: HashMap<OpId, ItemToken>,
+    len: usize,
+}
+
+impl<T> ReplicatedList<T> {
+    /// Creates a new, empty `ReplicatedList<T>` for the replica with the given id. Every replica
+    /// must have a distinct id.
+    pub fn new(replica: u64) -> Self {
+        ReplicatedList {
+            replica,
+            clock: 0,
+            elements: GenerationalTokenList::new(),
+            tokens: HashMap::new(),
+            len: 0,
+        }
+    }
+
+    /// Returns the id of this replica.
+    pub fn replica(&self) -> u64 {
+        self.replica
+    }
+
+    /// Returns the number of (non-deleted) elements.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether there are no (non-deleted) elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns an iterator of references to the (non-deleted) elements, in order.
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        self.elements
+            .iter()
+            .filter(|element| !element.deleted)
+            .map(|element| &element.value)
+    }
+
+    /// Returns the id of the (non-deleted) element at position `index`, or `None` if `index` is out
+    /// of bounds.
+    pub fn id_at(&self, index: usize) -> Option<OpId> {
+        self.elements
+            .iter()
+            .filter(|element| !element.deleted)
+            .nth(index)
+            .map(|element| element.id)
+    }
+
+    /// Insert `value` so that it ends up at position `index`, and return the op to send to the other
+    /// replicas.
+    ///
+    /// # Panics
+    /// Panics if `index > len`.
+    pub fn insert(&mut self, index: usize, value: T) -> ReplicatedOp<T>
+    where
+        T: Clone,
+    {
+        assert!(index <= self.len, "index out of bounds");
+
+        let after = match index {
+            0 => None,
+            _ => self.id_at(index - 1),
+        };
+        self.clock += 1;
+        let id = OpId {
+            counter: self.clock,
+            replica: self.replica,
+        };
+
+        let op = ReplicatedOp::Insert {
+            id,
+            after,
+            value: value.clone(),
+        };
+        self.integrate_insert(id, after, value);
+        op
+    }
+
+    /// Delete the element at position `index`, and return the op to send to the other replicas, or
+    /// `None` if `index` is out of bounds.
+    pub fn remove(&mut self, index: usize) -> Option<ReplicatedOp<T>> {
+        let id = self.id_at(index)?;
+        self.integrate_delete(id);
+        Some(ReplicatedOp::Delete { id })
+    }
+
+    /// Apply an op received from another replica. Applying an op more than once has no further
+    /// effect.
+    ///
+    /// Returns `false` (and does nothing) if the op refers to an element this replica doesn't know
+    /// yet, i.e. if it was delivered out of causal order; apply it again once its dependencies
+    /// have been applied.
+    pub fn apply(&mut self, op: ReplicatedOp<T>) -> bool {
+        match op {
+            ReplicatedOp::Insert { id, after, value } => {
+                if self.tokens.contains_key(&id) {
+                    return true;
+                }
+                if after.is_some_and(|after| !self.tokens.contains_key(&after)) {
+                    return false;
+                }
+
+                self.clock = self.clock.max(id.counter);
+                self.integrate_insert(id, after, value);
+                true
+            }
+            ReplicatedOp::Delete { id } => {
+                if !self.tokens.contains_key(&id) {
+                    return false;
+                }
+
+                self.integrate_delete(id);
+                true
+            }
+        }
+    }
+
+    fn integrate_insert(&mut self, id: OpId, after: Option<OpId>, value: T) {
+        // Skip over elements that were inserted at the same spot concurrently (or later, after
+        // those), and that win over the new one because of their higher id
+        let mut previous = after.map(|after| self.tokens[&after]);
+        loop {
+            let next = match previous {
+                Some(previous) => self.elements.next_token(previous),
+                None => self.elements.head_token(),
+            };
+            match next {
+                Some(next) if self.elements[next].id > id => previous = Some(next),
+                _ => break,
+            }
+        }
+
+        let element = Element {
+            id,
+            value,
+            deleted: false,
+        };
+        let token = match previous {
+            Some(previous) => self.elements.insert_after(previous, element),
+            None => self.elements.push_front(element),
+        };
+        self.tokens.insert(id, token);
+        self.len += 1;
+    }
+
+    fn integrate_delete(&mut self, id: OpId) {
+        let element = &mut self.elements[self.tokens[&id]];
+        if !element.deleted {
+            element.deleted = true;
+            self.len -= 1;
+        }
+    }
+}
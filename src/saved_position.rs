@@ -0,0 +1,125 @@
+// SPDX-License-Identifier: MIT
+
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
+
+use crate::{GenerationalTokenList, ItemToken};
+
+/// Which neighbor a [`SavedPosition`] moves to when its item is removed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Bias {
+    /// Move to the previous item, or to the next one if the removed item was the head.
+    Before,
+    /// Move to the next item, or to the previous one if the removed item was the tail.
+    After,
+}
+
+#[derive(Debug)]
+pub(crate) struct Anchor {
+    token: Option<ItemToken>,
+    bias: Bias,
+}
+
+/// The list's end of its saved positions. Positions that have been dropped are pruned lazily.
+pub(crate) type PositionTable = Vec<Weak<Mutex<Anchor>>>;
+
+/// A position in a list that survives the removal of its item, created by
+/// [`GenerationalTokenList::save_position`].
+///
+/// A saved position refers to an item like a token does, but when that item is removed from the
+/// list, the position moves to one of its neighbors (chosen by its [`Bias`]) instead of becoming
+/// invalid. This gives cursors and scroll anchors "approximately here" semantics. The position
+/// only becomes empty once there is no neighbor left to move to.
+///
+/// The position does not borrow the list. Unlinking an item doesn't move positions pointing to it,
+/// and positions are not carried over to clones of the list.
+#[derive(Debug)]
+pub struct SavedPosition {
+    anchor: Arc<Mutex<Anchor>>,
+}
+
+impl SavedPosition {
+    /// Returns the token of the item the position currently refers to, or `None` if the list ran
+    /// out of items to move to.
+    pub fn token(&self) -> Option<ItemToken> {
+        lock(&self.anchor).token
+    }
+
+    /// Returns which neighbor the position moves to when its item is removed.
+    pub fn bias(&self) -> Bias {
+        lock(&self.anchor).bias
+    }
+}
+
+impl<T> GenerationalTokenList<T> {
+    /// Save the position of the item identified by given token. When that item is removed, the
+    /// returned position moves to its previous or next neighbor, depending on `bias`; see
+    /// [`SavedPosition`].
+    ///
+    /// # Panics
+    /// Panics if `token` is invalid.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::{Bias, GenerationalTokenList};
+    /// let mut list = GenerationalTokenList::new();
+    /// let a = list.push_back("a");
+    /// let b = list.push_back("b");
+    /// let c = list.push_back("c");
+    ///
+    /// let cursor = list.save_position(b, Bias::After);
+    /// list.remove(b);
+    /// assert_eq!(cursor.token(), Some(c));
+    /// list.remove(c);
+    /// assert_eq!(cursor.token(), Some(a));
+    /// list.clear();
+    /// assert_eq!(cursor.token(), None);
+    /// ```
+    pub fn save_position(&mut self, token: ItemToken, bias: Bias) -> SavedPosition {
+        assert!(self.arena.contains(token.index), "invalid token");
+
+        let anchor = Arc::new(Mutex::new(Anchor {
+            token: Some(token),
+            bias,
+        }));
+        self.positions.retain(|anchor| anchor.strong_count() > 0);
+        self.positions.push(Arc::downgrade(&anchor));
+        SavedPosition { anchor }
+    }
+
+    /// Move the saved positions of a removed item to one of its former neighbors.
+    pub(crate) fn move_positions(
+        &mut self,
+        removed: ItemToken,
+        previous: Option<ItemToken>,
+        next: Option<ItemToken>,
+    ) {
+        self.positions.retain(|anchor| match anchor.upgrade() {
+            Some(anchor) => {
+                let mut anchor = lock(&anchor);
+                if anchor.token == Some(removed) {
+                    anchor.token = match anchor.bias {
+                        Bias::Before => previous.or(next),
+                        Bias::After => next.or(previous),
+                    };
+                }
+                true
+            }
+            None => false,
+        });
+    }
+
+    /// Empty all saved positions, for when all items are removed at once.
+    pub(crate) fn clear_positions(&mut self) {
+        for anchor in self.positions.drain(..) {
+            if let Some(anchor) = anchor.upgrade() {
+                lock(&anchor).token = None;
+            }
+        }
+    }
+}
+
+fn lock(anchor: &Mutex<Anchor>) -> MutexGuard<'_, Anchor> {
+    anchor
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
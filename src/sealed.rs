@@ -0,0 +1,146 @@
+// SPDX-License-Identifier: MIT
+
+use std::collections::HashMap;
+
+use crate::secondary_map::SlotMap;
+use crate::{GenerationalTokenList, ItemToken};
+
+/// A read-optimized, immutable-structure form of a [`GenerationalTokenList`], created by
+/// [`GenerationalTokenList::seal`].
+///
+/// The items are stored contiguously in list order, so iteration is a plain slice iteration and
+/// positional access is O(1). Tokens of the list it was sealed from keep working for lookups, which
+/// go through a slot-indexed table. Items can be modified in place, but not inserted, removed or
+/// moved; [`unseal`](Self::unseal) turns it back into an editable list.
+///
+/// # Examples
+/// ```
+/// # use generational_token_list::GenerationalTokenList;
+/// let mut list = GenerationalTokenList::new();
+/// let b = list.push_back("b");
+/// let a = list.push_front("a");
+///
+/// let sealed = list.seal();
+/// assert_eq!(sealed.as_slice(), &["a", "b"]);
+/// assert_eq!(sealed[b], "b");
+/// assert_eq!(sealed.position(b), Some(1));
+/// assert_eq!(sealed.token_at(0), Some(a));
+/// ```
+#[derive(Clone, Debug)]
+pub struct SealedTokenList<T> {
+    items: Vec<T>,
+    tokens: Vec<ItemToken>,
+    positions: SlotMap<usize>,
+}
+
+impl<T> GenerationalTokenList<T> {
+    /// Copy the items into a contiguous [`SealedTokenList`], in list order, for fast read-only
+    /// access. Tokens remain valid for the sealed list. Unlinked items are dropped.
+    pub fn seal(self) -> SealedTokenList<T> {
+        let tokens: Vec<_> = self.iter_with_tokens().map(|(token, _)| token).collect();
+        let mut positions = SlotMap::new();
+        for (position, token) in tokens.iter().enumerate() {
+            positions.insert(*token, position);
+        }
+
+        SealedTokenList {
+            items: self.into_iter().collect(),
+            tokens,
+            positions,
+        }
+    }
+}
+
+impl<T> SealedTokenList<T> {
+    /// Returns the number of items.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Returns whether there are no items.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Returns the items, in list order.
+    pub fn as_slice(&self) -> &[T] {
+        &self.items
+    }
+
+    /// Returns the items, in list order, for modification in place.
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        &mut self.items
+    }
+
+    /// Returns an iterator of references to the items, in list order.
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.items.iter()
+    }
+
+    /// Returns an iterator of pairs of (item tokens, references to items), in list order.
+    pub fn iter_with_tokens(&self) -> impl Iterator<Item = (ItemToken, &T)> + '_ {
+        self.tokens.iter().copied().zip(&self.items)
+    }
+
+    /// Returns a reference to the item identified by given token, or `None` if the token is invalid.
+    pub fn get(&self, token: ItemToken) -> Option<&T> {
+        self.position(token).map(|position| &self.items[position])
+    }
+
+    /// Returns a mutable reference to the item identified by given token, or `None` if the token is
+    /// invalid.
+    pub fn get_mut(&mut self, token: ItemToken) -> Option<&mut T> {
+        let position = self.position(token)?;
+        Some(&mut self.items[position])
+    }
+
+    /// Returns the position of the item identified by given token, or `None` if the token is
+    /// invalid.
+    pub fn position(&self, token: ItemToken) -> Option<usize> {
+        self.positions.get(token).copied()
+    }
+
+    /// Returns the token of the item at position `pos`, or `None` if `pos` is out of bounds.
+    pub fn token_at(&self, pos: usize) -> Option<ItemToken> {
+        self.tokens.get(pos).copied()
+    }
+
+    /// Turn the items back into an editable list, in the same order.
+    ///
+    /// The list's storage can't be recreated exactly, so the items get new tokens. Along with the
+    /// list, this returns a map from the old tokens to the new ones.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::new();
+    /// let a = list.push_back("a");
+    ///
+    /// let (mut list, new_tokens) = list.seal().unseal();
+    /// let a = new_tokens[&a];
+    /// list.insert_after(a, "b");
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), vec![&"a", &"b"]);
+    /// ```
+    pub fn unseal(self) -> (GenerationalTokenList<T>, HashMap<ItemToken, ItemToken>) {
+        let mut list = GenerationalTokenList::with_capacity(self.items.len());
+        let mut new_tokens = HashMap::with_capacity(self.items.len());
+        for (token, data) in self.tokens.into_iter().zip(self.items) {
+            new_tokens.insert(token, list.push_back(data));
+        }
+        (list, new_tokens)
+    }
+}
+
+impl<T> std::ops::Index<ItemToken> for SealedTokenList<T> {
+    type Output = T;
+
+    fn index(&self, token: ItemToken) -> &Self::Output {
+        self.get(token).expect("invalid token")
+    }
+}
+
+impl<T> std::ops::IndexMut<ItemToken> for SealedTokenList<T> {
+    fn index_mut(&mut self, token: ItemToken) -> &mut Self::Output {
+        self.get_mut(token).expect("invalid token")
+    }
+}
@@ -0,0 +1,268 @@
+// SPDX-License-Identifier: MIT
+
+use crate::{GenerationalTokenList, ItemToken};
+
+/// Values keyed by token, stored in a vector indexed by the token's arena slot. Each entry
+/// remembers the generation of the token it was inserted with, and lookups compare generations
+/// only. Backs [`SecondaryMap`], and is used directly by containers that remove entries along with
+/// their items.
+#[derive(Clone, Debug)]
+pub(crate) struct SlotMap<V> {
+    slots: Vec<Option<(u64, V)>>,
+    len: usize,
+}
+
+impl<V> SlotMap<V> {
+    pub(crate) fn new() -> Self {
+        SlotMap {
+            slots: Vec::new(),
+            len: 0,
+        }
+    }
+
+    pub(crate) fn insert(&mut self, token: ItemToken, value: V) -> Option<V> {
+        let slot = token.slot();
+        if slot >= self.slots.len() {
+            self.slots.resize_with(slot + 1, || None);
+        }
+
+        let entry = &mut self.slots[slot];
+        match entry.replace((token.generation(), value)) {
+            Some((generation, old)) if generation == token.generation() => Some(old),
+            Some(_) => None,
+            None => {
+                self.len += 1;
+                None
+            }
+        }
+    }
+
+    pub(crate) fn get(&self, token: ItemToken) -> Option<&V> {
+        match self.slots.get(token.slot()) {
+            Some(Some((generation, value))) if *generation == token.generation() => Some(value),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn get_mut(&mut self, token: ItemToken) -> Option<&mut V> {
+        match self.slots.get_mut(token.slot()) {
+            Some(Some((generation, value))) if *generation == token.generation() => Some(value),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn contains_key(&self, token: ItemToken) -> bool {
+        self.get(token).is_some()
+    }
+
+    pub(crate) fn remove(&mut self, token: ItemToken) -> Option<V> {
+        if !self.contains_key(token) {
+            return None;
+        }
+
+        self.len -= 1;
+        self.slots[token.slot()].take().map(|(_, value)| value)
+    }
+
+    /// Remove all entries for which `keep` returns `false`.
+    fn retain(&mut self, mut keep: impl FnMut(ItemToken) -> bool) {
+        for (slot, entry) in self.slots.iter_mut().enumerate() {
+            if let Some((generation, _)) = entry {
+                if !keep(ItemToken::from_slot_and_generation(slot, *generation)) {
+                    *entry = None;
+                    self.len -= 1;
+                }
+            }
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.slots.clear();
+        self.len = 0;
+    }
+
+    fn iter(&self) -> impl Iterator<Item = (ItemToken, &V)> + '_ {
+        self.slots.iter().enumerate().filter_map(|(slot, entry)| {
+            entry.as_ref().map(|(generation, value)| {
+                (
+                    ItemToken::from_slot_and_generation(slot, *generation),
+                    value,
+                )
+            })
+        })
+    }
+}
+
+impl<V> std::ops::Index<ItemToken> for SlotMap<V> {
+    type Output = V;
+
+    fn index(&self, token: ItemToken) -> &Self::Output {
+        self.get(token).unwrap()
+    }
+}
+
+impl<V> std::ops::IndexMut<ItemToken> for SlotMap<V> {
+    fn index_mut(&mut self, token: ItemToken) -> &mut Self::Output {
+        self.get_mut(token).unwrap()
+    }
+}
+
+/// A map that associates auxiliary data with the items of a [`GenerationalTokenList`], keyed by
+/// [`ItemToken`].
+///
+/// Storage is a vector indexed by the token's arena slot, so lookups are O(1) and there is no
+/// hashing involved. Each entry remembers the generation of the token it was inserted with, so once
+/// a slot is reused for a new item, a lookup with the new item's token will not see the old item's
+/// entry (and inserting for the new token replaces it).
+///
+/// Lookups take the list the tokens belong to, and treat entries for items that have been removed
+/// from it as absent. Such entries still take up room, and count towards [`len`](Self::len), until
+/// their slot is reused or [`retain_valid`](Self::retain_valid) is called.
+///
+/// # Examples
+/// ```
+/// # use generational_token_list::{GenerationalTokenList, SecondaryMap};
+/// let mut list = GenerationalTokenList::new();
+/// let apple = list.push_back("apple");
+/// let pear = list.push_back("pear");
+///
+/// let mut prices = SecondaryMap::new();
+/// prices.insert(apple, 3);
+/// prices.insert(pear, 5);
+/// assert_eq!(prices.get(&list, apple), Some(&3));
+///
+/// list.remove(pear);
+/// assert_eq!(prices.get(&list, pear), None);
+///
+/// // The slot of `pear` gets reused by `plum`, which doesn't inherit the price
+/// let plum = list.push_back("plum");
+/// assert_eq!(prices.get(&list, plum), None);
+/// ```
+#[derive(Clone, Debug)]
+pub struct SecondaryMap<V> {
+    entries: SlotMap<V>,
+}
+
+impl<V> Default for SecondaryMap<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V> SecondaryMap<V> {
+    /// Creates a new, empty `SecondaryMap<V>`.
+    pub fn new() -> Self {
+        SecondaryMap {
+            entries: SlotMap::new(),
+        }
+    }
+
+    /// Insert `value` for `token`. Returns the previous value for the same token, if any.
+    ///
+    /// An entry left behind by an older item that used the same slot is dropped.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::{GenerationalTokenList, SecondaryMap};
+    /// let mut list = GenerationalTokenList::new();
+    /// let item = list.push_back(());
+    ///
+    /// let mut map = SecondaryMap::new();
+    /// assert_eq!(map.insert(item, "a"), None);
+    /// assert_eq!(map.insert(item, "b"), Some("a"));
+    /// ```
+    pub fn insert(&mut self, token: ItemToken, value: V) -> Option<V> {
+        self.entries.insert(token, value)
+    }
+
+    /// Returns a reference to the value for `token`, or `None` if there is none or the item has
+    /// been removed from `list`.
+    pub fn get<T>(&self, list: &GenerationalTokenList<T>, token: ItemToken) -> Option<&V> {
+        list.get(token)?;
+        self.entries.get(token)
+    }
+
+    /// Returns a mutable reference to the value for `token`, or `None` if there is none or the item
+    /// has been removed from `list`.
+    pub fn get_mut<T>(
+        &mut self,
+        list: &GenerationalTokenList<T>,
+        token: ItemToken,
+    ) -> Option<&mut V> {
+        list.get(token)?;
+        self.entries.get_mut(token)
+    }
+
+    /// Returns whether there is a value for `token` and its item is still in `list`.
+    pub fn contains_key<T>(&self, list: &GenerationalTokenList<T>, token: ItemToken) -> bool {
+        self.get(list, token).is_some()
+    }
+
+    /// Remove and return the value for `token`, if any. This also returns the value for a removed
+    /// item, as long as its slot hasn't been reused.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::{GenerationalTokenList, SecondaryMap};
+    /// let mut list = GenerationalTokenList::new();
+    /// let item = list.push_back(());
+    ///
+    /// let mut map = SecondaryMap::new();
+    /// map.insert(item, 1);
+    /// assert_eq!(map.remove(item), Some(1));
+    /// assert_eq!(map.remove(item), None);
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn remove(&mut self, token: ItemToken) -> Option<V> {
+        self.entries.remove(token)
+    }
+
+    /// Remove all entries whose token is not valid for `list`, e.g. because the item was removed.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::{GenerationalTokenList, SecondaryMap};
+    /// let mut list = GenerationalTokenList::new();
+    /// let item1 = list.push_back(());
+    /// let item2 = list.push_back(());
+    ///
+    /// let mut map = SecondaryMap::new();
+    /// map.insert(item1, 1);
+    /// map.insert(item2, 2);
+    ///
+    /// list.remove(item1);
+    /// map.retain_valid(&list);
+    /// assert_eq!(map.len(), 1);
+    /// assert_eq!(map.remove(item1), None);
+    /// ```
+    pub fn retain_valid<T>(&mut self, list: &GenerationalTokenList<T>) {
+        self.entries.retain(|token| list.get(token).is_some());
+    }
+
+    /// Returns the number of entries in the map, including those for removed items that
+    /// [`retain_valid`](Self::retain_valid) hasn't dropped yet.
+    pub fn len(&self) -> usize {
+        self.entries.len
+    }
+
+    /// Returns whether the map is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.len == 0
+    }
+
+    /// Remove all entries.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Returns an iterator of pairs of (tokens, references to values) for the items still in
+    /// `list`, in arena slot order (which is unrelated to list order).
+    pub fn iter<'a, T>(
+        &'a self,
+        list: &'a GenerationalTokenList<T>,
+    ) -> impl Iterator<Item = (ItemToken, &'a V)> + 'a {
+        self.entries
+            .iter()
+            .filter(move |&(token, _)| list.get(token).is_some())
+    }
+}
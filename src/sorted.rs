@@ -0,0 +1,279 @@
+// SPDX-License-Identifier: MIT
+
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+use std::fmt;
+use std::iter::FromIterator;
+
+use crate::{GenerationalTokenList, ItemToken, Iter, IterWithTokens};
+
+/// A wrapper around [`GenerationalTokenList`] that keeps its items sorted.
+///
+/// Only order-preserving operations are exposed: items are placed by [`insert`](Self::insert) and
+/// can't be mutated in place. Alongside the list, the wrapper keeps the tokens in list order, so
+/// lookups by value use binary search.
+///
+/// # Complexity
+/// [`find`](Self::find), [`lower_bound`](Self::lower_bound) and [`upper_bound`](Self::upper_bound)
+/// take O(log n) comparisons. [`insert`](Self::insert) and [`remove`](Self::remove) take O(log n)
+/// comparisons too, plus shifting the tokens after the affected position in the index, which is a
+/// single `memmove` of at most n / 2 tokens and much cheaper than a linear scan of the list.
+///
+/// Items that compare equal are kept in insertion order.
+///
+/// The comparator is a type parameter `F`, so it can be any closure and gets inlined. It defaults
+/// to a function pointer, which is what [`new`](SortedTokenList::new) uses.
+///
+/// # Examples
+/// ```
+/// # use generational_token_list::SortedTokenList;
+/// let mut list = SortedTokenList::new();
+/// list.insert(3);
+/// let one = list.insert(1);
+/// list.insert(2);
+/// assert_eq!(list.iter().collect::<Vec<_>>(), vec![&1, &2, &3]);
+/// assert_eq!(list.find(&1), Some(one));
+/// ```
+#[derive(Clone)]
+pub struct SortedTokenList<T, F = fn(&T, &T) -> Ordering> {
+    list: GenerationalTokenList<T>,
+    order: VecDeque<ItemToken>,
+    compare: F,
+}
+
+impl<T, F> fmt::Debug for SortedTokenList<T, F>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SortedTokenList")
+            .field("list", &self.list)
+            .field("order", &self.order)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> Default for SortedTokenList<T>
+where
+    T: Ord,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> SortedTokenList<T>
+where
+    T: Ord,
+{
+    /// Creates a new `SortedTokenList<T>`, ordered by `T`'s [`Ord`] implementation.
+    pub fn new() -> Self {
+        Self::new_by(T::cmp)
+    }
+}
+
+impl<T, F> SortedTokenList<T, F>
+where
+    F: Fn(&T, &T) -> Ordering,
+{
+    /// Creates a new `SortedTokenList<T, F>`, ordered by `compare`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::SortedTokenList;
+    /// let mut list = SortedTokenList::new_by(|a: &i32, b: &i32| b.cmp(a));
+    /// list.insert(1);
+    /// list.insert(3);
+    /// list.insert(2);
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), vec![&3, &2, &1]);
+    /// ```
+    pub fn new_by(compare: F) -> Self {
+        SortedTokenList {
+            list: GenerationalTokenList::new(),
+            order: VecDeque::new(),
+            compare,
+        }
+    }
+
+    /// Insert `value` at its sorted position, after any items that compare equal to it. Returns the
+    /// token of the new item.
+    pub fn insert(&mut self, value: T) -> ItemToken {
+        let position = self.upper_bound_position(&value);
+        let token = match position.checked_sub(1) {
+            Some(before) => self.list.insert_after(self.order[before], value),
+            None => self.list.push_front(value),
+        };
+        self.order.insert(position, token);
+        token
+    }
+
+    /// Remove the item identified by given token and return it, or `None` if the token is invalid.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::SortedTokenList;
+    /// let mut list = SortedTokenList::new();
+    /// let a = list.insert("a");
+    /// list.insert("b");
+    /// assert_eq!(list.remove(a), Some("a"));
+    /// assert_eq!(list.remove(a), None);
+    /// assert_eq!(list.len(), 1);
+    /// ```
+    pub fn remove(&mut self, token: ItemToken) -> Option<T> {
+        let position = self.position(token)?;
+        self.order.remove(position);
+        self.list.remove(token)
+    }
+
+    /// Remove and return the first (smallest) item, if any.
+    pub fn pop_first(&mut self) -> Option<T> {
+        self.order.pop_front();
+        self.list.pop_front()
+    }
+
+    /// Remove and return the last (largest) item, if any.
+    pub fn pop_last(&mut self) -> Option<T> {
+        self.order.pop_back();
+        self.list.pop_back()
+    }
+
+    /// Returns a reference to the first (smallest) item, or `None` if the list is empty.
+    pub fn first(&self) -> Option<&T> {
+        self.list.head()
+    }
+
+    /// Returns a reference to the last (largest) item, or `None` if the list is empty.
+    pub fn last(&self) -> Option<&T> {
+        self.list.tail()
+    }
+
+    /// Returns a reference to the item identified by given token, or `None` if the token is
+    /// invalid.
+    pub fn get(&self, token: ItemToken) -> Option<&T> {
+        self.list.get(token)
+    }
+
+    /// Returns the token of the first item that compares equal to `value`, if any.
+    pub fn find(&self, value: &T) -> Option<ItemToken> {
+        let token = *self.order.get(self.lower_bound_position(value))?;
+        match (self.compare)(&self.list[token], value) {
+            Ordering::Equal => Some(token),
+            _ => None,
+        }
+    }
+
+    /// Returns the token of the first item that does not compare less than `value`, or `None` if
+    /// there is no such item.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::SortedTokenList;
+    /// let list = vec![10, 20, 30].into_iter().collect::<SortedTokenList<_>>();
+    /// let token = list.lower_bound(&15).unwrap();
+    /// assert_eq!(list.get(token), Some(&20));
+    /// assert_eq!(list.lower_bound(&31), None);
+    /// ```
+    pub fn lower_bound(&self, value: &T) -> Option<ItemToken> {
+        self.order.get(self.lower_bound_position(value)).copied()
+    }
+
+    /// Returns the token of the first item that compares greater than `value`, or `None` if there
+    /// is no such item.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::SortedTokenList;
+    /// let list = vec![10, 20, 20, 30].into_iter().collect::<SortedTokenList<_>>();
+    /// let token = list.upper_bound(&20).unwrap();
+    /// assert_eq!(list.get(token), Some(&30));
+    /// assert_eq!(list.upper_bound(&30), None);
+    /// ```
+    pub fn upper_bound(&self, value: &T) -> Option<ItemToken> {
+        self.order.get(self.upper_bound_position(value)).copied()
+    }
+
+    /// Returns the number of items in the list.
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    /// Returns whether the list is empty.
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// Remove all items. Invalidates all tokens.
+    pub fn clear(&mut self) {
+        self.list.clear();
+        self.order.clear();
+    }
+
+    /// Returns an iterator of references to the items, in sorted order.
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.list.iter()
+    }
+
+    /// Returns an iterator of pairs of (tokens, references to items), in sorted order.
+    pub fn iter_with_tokens(&self) -> IterWithTokens<'_, T> {
+        self.list.iter_with_tokens()
+    }
+
+    /// Returns a reference to the underlying list.
+    pub fn as_list(&self) -> &GenerationalTokenList<T> {
+        &self.list
+    }
+
+    /// Consume the wrapper and return the underlying list. Tokens remain valid.
+    pub fn into_list(self) -> GenerationalTokenList<T> {
+        self.list
+    }
+
+    fn lower_bound_position(&self, value: &T) -> usize {
+        self.order
+            .partition_point(|&token| (self.compare)(&self.list[token], value) == Ordering::Less)
+    }
+
+    fn upper_bound_position(&self, value: &T) -> usize {
+        self.order
+            .partition_point(|&token| (self.compare)(&self.list[token], value) != Ordering::Greater)
+    }
+
+    fn position(&self, token: ItemToken) -> Option<usize> {
+        let value = self.list.get(token)?;
+        let start = self.lower_bound_position(value);
+        self.order
+            .range(start..)
+            .position(|&other| other == token)
+            .map(|offset| start + offset)
+    }
+}
+
+impl<T> FromIterator<T> for SortedTokenList<T>
+where
+    T: Ord,
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T, F> Extend<T> for SortedTokenList<T, F>
+where
+    F: Fn(&T, &T) -> Ordering,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+impl<T, F> std::ops::Index<ItemToken> for SortedTokenList<T, F> {
+    type Output = T;
+
+    fn index(&self, token: ItemToken) -> &Self::Output {
+        &self.list[token]
+    }
+}
@@ -0,0 +1,128 @@
+// SPDX-License-Identifier: MIT
+
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+use futures_core::Stream;
+
+use crate::{ChangeSubscription, GenerationalTokenList, IntoIter};
+
+/// A stream of the items of a list, in order. Created by [`GenerationalTokenList::into_stream`].
+pub struct IntoStream<T> {
+    items: IntoIter<T>,
+}
+
+// The items are never pinned, only moved out
+impl<T> Unpin for IntoStream<T> {}
+
+impl<T> Stream for IntoStream<T> {
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Option<T>> {
+        Poll::Ready(self.items.next())
+    }
+}
+
+/// A stream that pops items off the front of a shared list. Created by
+/// [`GenerationalTokenList::stream_pop_front`].
+#[derive(Debug)]
+pub struct PopFrontStream<T> {
+    list: Arc<Mutex<GenerationalTokenList<T>>>,
+    /// Wakes the stream when items are inserted, created the first time it has to wait.
+    changes: Option<ChangeSubscription>,
+}
+
+impl<T> Stream for PopFrontStream<T> {
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let this = &mut *self;
+        let mut list = this
+            .list
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(data) = list.pop_front() {
+            return Poll::Ready(Some(data));
+        }
+        if list.is_empty() && Arc::strong_count(&this.list) == 1 {
+            // Nobody else can insert any more items
+            return Poll::Ready(None);
+        }
+
+        // The list can't change while it is locked, so no insertion can slip in between the
+        // check above and registering the waker
+        this.changes
+            .get_or_insert_with(|| list.subscribe())
+            .wake_on_change(cx.waker());
+        Poll::Pending
+    }
+}
+
+impl<T> GenerationalTokenList<T> {
+    /// Consume the list and return a stream of its items, in order.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// # use futures_core::Stream;
+    /// # use std::pin::Pin;
+    /// # use std::task::{Context, Poll, Waker};
+    /// let list = GenerationalTokenList::from(vec![1, 2]);
+    /// let mut stream = list.into_stream();
+    ///
+    /// let mut cx = Context::from_waker(Waker::noop());
+    /// assert_eq!(Pin::new(&mut stream).poll_next(&mut cx), Poll::Ready(Some(1)));
+    /// assert_eq!(Pin::new(&mut stream).poll_next(&mut cx), Poll::Ready(Some(2)));
+    /// assert_eq!(Pin::new(&mut stream).poll_next(&mut cx), Poll::Ready(None));
+    /// ```
+    pub fn into_stream(self) -> IntoStream<T> {
+        IntoStream {
+            items: self.into_iter(),
+        }
+    }
+
+    /// Returns a stream that removes and yields the first item of the shared list each time it is
+    /// polled. When the list is empty, the stream waits for the next insertion; it only ends once
+    /// the list is empty and the stream holds the last reference to it.
+    ///
+    /// The lock is only held while popping a single item, so producers can keep appending to the
+    /// list in between, and items are only taken off the list as fast as the consumer asks for
+    /// them.
+    ///
+    /// While the first item is protected by a [`RemovalGuard`](crate::RemovalGuard), the stream
+    /// waits as well. Dropping the guard doesn't change the list, so the stream only tries again
+    /// on the next structural change.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// # use futures_core::Stream;
+    /// # use std::pin::Pin;
+    /// # use std::sync::{Arc, Mutex};
+    /// # use std::task::{Context, Poll, Waker};
+    /// let queue = Arc::new(Mutex::new(GenerationalTokenList::new()));
+    /// queue.lock().unwrap().push_back("a");
+    /// let mut stream = GenerationalTokenList::stream_pop_front(&queue);
+    ///
+    /// let mut cx = Context::from_waker(Waker::noop());
+    /// assert_eq!(Pin::new(&mut stream).poll_next(&mut cx), Poll::Ready(Some("a")));
+    /// queue.lock().unwrap().push_back("b");
+    /// assert_eq!(Pin::new(&mut stream).poll_next(&mut cx), Poll::Ready(Some("b")));
+    ///
+    /// // Wait for the producer
+    /// assert_eq!(Pin::new(&mut stream).poll_next(&mut cx), Poll::Pending);
+    /// queue.lock().unwrap().push_back("c");
+    /// assert_eq!(Pin::new(&mut stream).poll_next(&mut cx), Poll::Ready(Some("c")));
+    ///
+    /// // The producer is gone
+    /// drop(queue);
+    /// assert_eq!(Pin::new(&mut stream).poll_next(&mut cx), Poll::Ready(None));
+    /// ```
+    pub fn stream_pop_front(list: &Arc<Mutex<Self>>) -> PopFrontStream<T> {
+        PopFrontStream {
+            list: list.clone(),
+            changes: None,
+        }
+    }
+}
@@ -0,0 +1,135 @@
+// SPDX-License-Identifier: MIT
+
+use std::time::Instant;
+
+use crate::{ItemToken, SortedTokenList};
+
+/// A list of values ordered by deadline, for implementing timers.
+///
+/// Scheduling a value returns a token that can be used to [`cancel`](Self::cancel) it later.
+/// Values with equal deadlines expire in the order they were scheduled. The deadline type defaults
+/// to [`Instant`], but anything [`Ord`] works, e.g. a tick count.
+///
+/// # Examples
+/// ```
+/// # use generational_token_list::TimerList;
+/// let mut timers = TimerList::new();
+/// timers.schedule(30, "c");
+/// let b = timers.schedule(20, "b");
+/// timers.schedule(10, "a");
+///
+/// assert_eq!(timers.cancel(b), Some("b"));
+/// assert_eq!(timers.next_deadline(), Some(&10));
+/// assert_eq!(timers.pop_expired(25).collect::<Vec<_>>(), vec![(10, "a")]);
+/// assert_eq!(timers.len(), 1);
+/// ```
+#[derive(Clone, Debug)]
+pub struct TimerList<T, D = Instant> {
+    timers: SortedTokenList<(D, T)>,
+}
+
+impl<T, D> Default for TimerList<T, D>
+where
+    D: Ord,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, D> TimerList<T, D>
+where
+    D: Ord,
+{
+    /// Creates a new, empty `TimerList`.
+    pub fn new() -> Self {
+        TimerList {
+            timers: SortedTokenList::new_by(|a, b| a.0.cmp(&b.0)),
+        }
+    }
+
+    /// Schedule `value` to expire at `deadline`. Returns a token that identifies the timer.
+    pub fn schedule(&mut self, deadline: D, value: T) -> ItemToken {
+        self.timers.insert((deadline, value))
+    }
+
+    /// Cancel the timer identified by given token and return its value. Returns `None` if the token
+    /// is invalid, e.g. because the timer already expired.
+    pub fn cancel(&mut self, token: ItemToken) -> Option<T> {
+        self.timers.remove(token).map(|(_, value)| value)
+    }
+
+    /// Remove and return, in deadline order, all timers whose deadline is at or before `now`.
+    ///
+    /// Timers are removed as the iterator is advanced; timers that have not been yielded when the
+    /// iterator is dropped stay in the list.
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::time::{Duration, Instant};
+    /// # use generational_token_list::TimerList;
+    /// let start = Instant::now();
+    /// let mut timers = TimerList::new();
+    /// timers.schedule(start + Duration::from_secs(1), "soon");
+    /// timers.schedule(start + Duration::from_secs(60), "later");
+    ///
+    /// let now = start + Duration::from_secs(2);
+    /// let expired = timers.pop_expired(now).map(|(_, value)| value).collect::<Vec<_>>();
+    /// assert_eq!(expired, vec!["soon"]);
+    /// ```
+    pub fn pop_expired(&mut self, now: D) -> PopExpired<'_, T, D> {
+        PopExpired { list: self, now }
+    }
+
+    /// Returns the earliest deadline, or `None` if there are no timers.
+    pub fn next_deadline(&self) -> Option<&D> {
+        self.timers.first().map(|(deadline, _)| deadline)
+    }
+
+    /// Returns the deadline of the timer identified by given token, or `None` if the token is
+    /// invalid.
+    pub fn deadline(&self, token: ItemToken) -> Option<&D> {
+        self.timers.get(token).map(|(deadline, _)| deadline)
+    }
+
+    /// Returns a reference to the value of the timer identified by given token, or `None` if the
+    /// token is invalid.
+    pub fn get(&self, token: ItemToken) -> Option<&T> {
+        self.timers.get(token).map(|(_, value)| value)
+    }
+
+    /// Returns the number of scheduled timers.
+    pub fn len(&self) -> usize {
+        self.timers.len()
+    }
+
+    /// Returns whether there are no scheduled timers.
+    pub fn is_empty(&self) -> bool {
+        self.timers.is_empty()
+    }
+
+    /// Cancel all timers. Invalidates all tokens.
+    pub fn clear(&mut self) {
+        self.timers.clear();
+    }
+}
+
+/// Iterator returned by [`TimerList::pop_expired`].
+pub struct PopExpired<'a, T, D> {
+    list: &'a mut TimerList<T, D>,
+    now: D,
+}
+
+impl<'a, T, D> Iterator for PopExpired<'a, T, D>
+where
+    D: Ord,
+{
+    type Item = (D, T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.list.next_deadline() {
+            Some(deadline) if *deadline <= self.now => self.list.timers.pop_first(),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,159 @@
+// SPDX-License-Identifier: MIT
+
+use crate::ItemToken;
+
+/// A set of [`ItemToken`]s, backed by a bitset over arena slots.
+///
+/// Insertion, removal and lookup are O(1) without any hashing, which makes this much cheaper than a
+/// `HashSet<ItemToken>` for things like selection sets or marking visited items during a traversal.
+/// Like [`SecondaryMap`](crate::SecondaryMap), the set remembers the generation of each token, so a
+/// token for a newer item that reuses a slot is not considered a member.
+///
+/// # Examples
+/// ```
+/// # use generational_token_list::{GenerationalTokenList, TokenSet};
+/// let mut list = GenerationalTokenList::new();
+/// let a = list.push_back("a");
+/// let b = list.push_back("b");
+///
+/// let mut selection = TokenSet::new();
+/// assert!(selection.insert(b));
+/// assert!(!selection.insert(b));
+/// assert!(selection.contains(b));
+/// assert!(!selection.contains(a));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct TokenSet {
+    bits: Vec<u64>,
+    generations: Vec<u64>,
+    len: usize,
+}
+
+impl TokenSet {
+    /// Creates a new, empty `TokenSet`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `token` to the set. Returns `true` if it was not already a member.
+    ///
+    /// A token for an older item that used the same slot is replaced.
+    pub fn insert(&mut self, token: ItemToken) -> bool {
+        let slot = token.slot();
+        if slot >= self.generations.len() {
+            self.generations.resize(slot + 1, 0);
+            self.bits.resize(slot / 64 + 1, 0);
+        }
+
+        let was_set = self.bit(slot);
+        let was_member = was_set && self.generations[slot] == token.generation();
+
+        self.bits[slot / 64] |= 1 << (slot % 64);
+        self.generations[slot] = token.generation();
+        if !was_set {
+            self.len += 1;
+        }
+
+        !was_member
+    }
+
+    /// Returns whether `token` is a member of the set.
+    pub fn contains(&self, token: ItemToken) -> bool {
+        let slot = token.slot();
+        slot < self.generations.len()
+            && self.bit(slot)
+            && self.generations[slot] == token.generation()
+    }
+
+    /// Remove `token` from the set. Returns `true` if it was a member.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::{GenerationalTokenList, TokenSet};
+    /// let mut list = GenerationalTokenList::new();
+    /// let a = list.push_back("a");
+    ///
+    /// let mut set = TokenSet::new();
+    /// set.insert(a);
+    /// assert!(set.remove(a));
+    /// assert!(!set.remove(a));
+    /// assert!(set.is_empty());
+    /// ```
+    pub fn remove(&mut self, token: ItemToken) -> bool {
+        if !self.contains(token) {
+            return false;
+        }
+
+        let slot = token.slot();
+        self.bits[slot / 64] &= !(1 << (slot % 64));
+        self.len -= 1;
+        true
+    }
+
+    /// Returns the number of tokens in the set.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether the set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Remove all tokens from the set, keeping the allocated storage.
+    pub fn clear(&mut self) {
+        for word in &mut self.bits {
+            *word = 0;
+        }
+        self.len = 0;
+    }
+
+    /// Returns an iterator over the tokens in the set, in arena slot order (which is unrelated to
+    /// list order).
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::{GenerationalTokenList, TokenSet};
+    /// let mut list = GenerationalTokenList::new();
+    /// let a = list.push_back("a");
+    /// let b = list.push_back("b");
+    ///
+    /// let set = vec![b, a].into_iter().collect::<TokenSet>();
+    /// assert_eq!(set.iter().count(), 2);
+    /// assert!(set.iter().all(|token| token == a || token == b));
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = ItemToken> + '_ {
+        self.bits
+            .iter()
+            .enumerate()
+            .filter(|(_, word)| **word != 0)
+            .flat_map(move |(word_index, word)| {
+                (0..64)
+                    .filter(move |bit| word & (1 << bit) != 0)
+                    .map(move |bit| {
+                        let slot = word_index * 64 + bit;
+                        ItemToken::from_slot_and_generation(slot, self.generations[slot])
+                    })
+            })
+    }
+
+    fn bit(&self, slot: usize) -> bool {
+        self.bits[slot / 64] & (1 << (slot % 64)) != 0
+    }
+}
+
+impl std::iter::FromIterator<ItemToken> for TokenSet {
+    fn from_iter<I: IntoIterator<Item = ItemToken>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl Extend<ItemToken> for TokenSet {
+    fn extend<I: IntoIterator<Item = ItemToken>>(&mut self, iter: I) {
+        for token in iter {
+            self.insert(token);
+        }
+    }
+}
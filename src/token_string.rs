@@ -0,0 +1,72 @@
+// SPDX-License-Identifier: MIT
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::ItemToken;
+
+impl ItemToken {
+    /// Returns a compact textual form of the token, made of its slot and generation in lowercase
+    /// hexadecimal, separated by a dash (e.g. `"1f-3"`). It only contains URL-safe characters, is
+    /// stable across versions of this crate, and can be turned back into the same token with
+    /// [`str::parse`].
+    ///
+    /// The string carries no information about which list the token belongs to.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::{GenerationalTokenList, ItemToken};
+    /// let mut list = GenerationalTokenList::new();
+    /// let item = list.push_back("a");
+    ///
+    /// let text = item.to_token_string();
+    /// assert_eq!(text, "0-0");
+    /// assert_eq!(text.parse::<ItemToken>(), Ok(item));
+    /// assert!("0_0".parse::<ItemToken>().is_err());
+    /// ```
+    pub fn to_token_string(&self) -> String {
+        format!("{:x}-{:x}", self.slot(), self.generation())
+    }
+}
+
+/// The error returned when parsing a string that wasn't produced by
+/// [`ItemToken::to_token_string`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseTokenError {
+    _private: (),
+}
+
+impl fmt::Display for ParseTokenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid token string")
+    }
+}
+
+impl std::error::Error for ParseTokenError {}
+
+impl FromStr for ItemToken {
+    type Err = ParseTokenError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = ParseTokenError { _private: () };
+        let (slot, generation) = s.split_once('-').ok_or_else(|| error.clone())?;
+        let slot = usize::try_from(parse_hex(slot).ok_or_else(|| error.clone())?)
+            .map_err(|_| error.clone())?;
+        let generation = parse_hex(generation).ok_or(error)?;
+        Ok(ItemToken::from_slot_and_generation(slot, generation))
+    }
+}
+
+/// Parses lowercase hexadecimal digits only, so that each token has exactly one string form.
+fn parse_hex(digits: &str) -> Option<u64> {
+    let canonical = !digits.is_empty()
+        && (digits == "0" || !digits.starts_with('0'))
+        && digits
+            .bytes()
+            .all(|digit| matches!(digit, b'0'..=b'9' | b'a'..=b'f'));
+    if !canonical {
+        return None;
+    }
+    u64::from_str_radix(digits, 16).ok()
+}
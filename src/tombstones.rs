@@ -0,0 +1,94 @@
+// SPDX-License-Identifier: MIT
+
+use std::collections::VecDeque;
+
+use crate::{GenerationalTokenList, ItemToken};
+
+/// How many removed tokens are remembered.
+const HISTORY_LEN: usize = 1024;
+
+/// What is known about a token, as returned by [`GenerationalTokenList::token_status`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TokenStatus {
+    /// The token identifies an item in the list.
+    Live,
+    /// The item was removed at the given operation number (see
+    /// [`GenerationalTokenList::op_count`]).
+    Removed {
+        /// Operation number of the removal.
+        op: u64,
+    },
+    /// The token is not valid and not among the recently removed ones: it belongs to a different
+    /// list, or it was removed too long ago to be remembered.
+    Unknown,
+}
+
+/// Bounded history of removals, kept with the `debug-checks` feature.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Tombstones {
+    /// Number of insertions and removals so far.
+    ops: u64,
+    removed: VecDeque<(ItemToken, u64)>,
+}
+
+impl Tombstones {
+    pub(crate) fn record_insertion(&mut self) {
+        self.ops += 1;
+    }
+
+    pub(crate) fn record_removal(&mut self, token: ItemToken) {
+        self.ops += 1;
+        if self.removed.len() == HISTORY_LEN {
+            self.removed.pop_front();
+        }
+        self.removed.push_back((token, self.ops));
+    }
+
+    /// Forget all removals, e.g. because their tokens may be handed out again.
+    pub(crate) fn forget_removals(&mut self) {
+        self.removed.clear();
+    }
+
+    fn removed_at(&self, token: ItemToken) -> Option<u64> {
+        self.removed
+            .iter()
+            .rev()
+            .find(|(removed, _)| *removed == token)
+            .map(|(_, op)| *op)
+    }
+}
+
+impl<T> GenerationalTokenList<T> {
+    /// Returns the number of insertions and removals performed on the list so far. Every item
+    /// removed by [`clear`](Self::clear) counts as a removal.
+    pub fn op_count(&self) -> u64 {
+        self.tombstones.ops
+    }
+
+    /// Tell whether `token` is valid and, if not, whether it is one of the last 1024 tokens removed
+    /// from this list.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::{GenerationalTokenList, TokenStatus};
+    /// let mut list = GenerationalTokenList::new();
+    /// let item = list.push_back(1);
+    /// assert_eq!(list.token_status(item), TokenStatus::Live);
+    ///
+    /// list.remove(item);
+    /// assert_eq!(list.token_status(item), TokenStatus::Removed { op: 2 });
+    ///
+    /// let other = GenerationalTokenList::<i32>::new();
+    /// assert_eq!(other.token_status(item), TokenStatus::Unknown);
+    /// ```
+    pub fn token_status(&self, token: ItemToken) -> TokenStatus {
+        if self.arena.contains(token.index) {
+            return TokenStatus::Live;
+        }
+
+        match self.tombstones.removed_at(token) {
+            Some(op) => TokenStatus::Removed { op },
+            None => TokenStatus::Unknown,
+        }
+    }
+}
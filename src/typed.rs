@@ -0,0 +1,268 @@
+// SPDX-License-Identifier: MIT
+
+use std::iter::FromIterator;
+use std::marker::PhantomData;
+
+use crate::{GenerationalTokenList, ItemToken};
+
+/// Defines a newtype wrapper around [`ItemToken`], for use with [`TypedTokenList`], so that tokens
+/// of different lists can't be mixed up.
+///
+/// `define_token!(pub InstrToken)` defines `pub struct InstrToken`, which derives `Clone`, `Copy`,
+/// `Debug`, `PartialEq`, `Eq` and `Hash`, and converts from and to [`ItemToken`] with [`From`].
+/// Adding `, pub type InstrList` also defines `pub type InstrList<T> = TypedTokenList<T,
+/// InstrToken>`. Attributes (e.g. doc comments) in front of the name are applied to the token
+/// type.
+///
+/// # Examples
+/// ```
+/// # use generational_token_list::define_token;
+/// define_token!(
+///     /// Identifies an instruction.
+///     pub InstrToken, pub type InstrList
+/// );
+/// define_token!(pub BlockToken, pub type BlockList);
+///
+/// let mut instructions = InstrList::new();
+/// let nop = instructions.push_back("nop");
+/// let mut blocks = BlockList::new();
+/// blocks.push_back(vec![nop]);
+///
+/// assert_eq!(instructions[nop], "nop");
+/// ```
+///
+/// Using a token with the wrong list doesn't compile:
+/// ```compile_fail
+/// # use generational_token_list::define_token;
+/// # define_token!(pub InstrToken, pub type InstrList);
+/// # define_token!(pub BlockToken, pub type BlockList);
+/// let mut instructions = InstrList::new();
+/// let nop = instructions.push_back("nop");
+/// let blocks = BlockList::<Vec<InstrToken>>::new();
+/// blocks.get(nop);
+/// ```
+#[macro_export]
+macro_rules! define_token {
+    ($(#[$meta:meta])* $vis:vis $name:ident) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        $vis struct $name($crate::ItemToken);
+
+        impl ::std::convert::From<$crate::ItemToken> for $name {
+            fn from(token: $crate::ItemToken) -> Self {
+                $name(token)
+            }
+        }
+
+        impl ::std::convert::From<$name> for $crate::ItemToken {
+            fn from(token: $name) -> Self {
+                token.0
+            }
+        }
+    };
+    ($(#[$meta:meta])* $vis:vis $name:ident, $list_vis:vis type $list:ident) => {
+        $crate::define_token!($(#[$meta])* $vis $name);
+
+        $list_vis type $list<T> = $crate::TypedTokenList<T, $name>;
+    };
+}
+
+/// A [`GenerationalTokenList`] whose tokens have their own type `K`, usually defined with
+/// [`define_token!`](crate::define_token).
+///
+/// This only adds type safety: `K` is converted from and to [`ItemToken`] at the boundary, and the
+/// underlying list is available through [`as_list`](Self::as_list) for everything not exposed
+/// here.
+#[derive(Clone, Debug)]
+pub struct TypedTokenList<T, K> {
+    list: GenerationalTokenList<T>,
+    token: PhantomData<fn(K) -> K>,
+}
+
+impl<T, K> Default for TypedTokenList<T, K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, K> TypedTokenList<T, K> {
+    /// Creates a new, empty `TypedTokenList<T, K>`.
+    pub fn new() -> Self {
+        Self::from_list(GenerationalTokenList::new())
+    }
+
+    /// Creates a new, empty `TypedTokenList<T, K>` with given capacity.
+    pub fn with_capacity(n: usize) -> Self {
+        Self::from_list(GenerationalTokenList::with_capacity(n))
+    }
+
+    /// Wrap an existing list. Tokens it handed out before can be converted with `K::from`.
+    pub fn from_list(list: GenerationalTokenList<T>) -> Self {
+        TypedTokenList {
+            list,
+            token: PhantomData,
+        }
+    }
+
+    /// Returns a reference to the underlying list.
+    pub fn as_list(&self) -> &GenerationalTokenList<T> {
+        &self.list
+    }
+
+    /// Unwrap the underlying list. Tokens remain valid after converting them into [`ItemToken`]s.
+    pub fn into_list(self) -> GenerationalTokenList<T> {
+        self.list
+    }
+
+    /// Returns the number of items in the list.
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    /// Returns whether the list is empty.
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// Returns a reference to the first item in the list, or `None` if list is empty.
+    pub fn head(&self) -> Option<&T> {
+        self.list.head()
+    }
+
+    /// Returns a reference to the last item in the list, or `None` if list is empty.
+    pub fn tail(&self) -> Option<&T> {
+        self.list.tail()
+    }
+
+    /// Returns an iterator of references to the items, in list order.
+    pub fn iter(&self) -> crate::Iter<'_, T> {
+        self.list.iter()
+    }
+
+    /// Remove all items from the list. Invalidates all tokens.
+    pub fn clear(&mut self) {
+        self.list.clear();
+    }
+
+    /// Append an item to the end of the list. Returns a token which corresponds to the new item.
+    pub fn push_back(&mut self, data: T) -> K
+    where
+        K: From<ItemToken>,
+    {
+        self.list.push_back(data).into()
+    }
+
+    /// Prepend an item to the beginning of the list. Returns a token which corresponds to the new
+    /// item.
+    pub fn push_front(&mut self, data: T) -> K
+    where
+        K: From<ItemToken>,
+    {
+        self.list.push_front(data).into()
+    }
+
+    /// Remove the first item from the list and return it, or `None` if list is empty.
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.list.pop_front()
+    }
+
+    /// Remove the last item from the list and return it, or `None` if list is empty.
+    pub fn pop_back(&mut self) -> Option<T> {
+        self.list.pop_back()
+    }
+}
+
+impl<T, K> TypedTokenList<T, K>
+where
+    K: Copy + From<ItemToken> + Into<ItemToken>,
+{
+    /// Returns the token of the first item in the list, or `None` if list is empty.
+    pub fn head_token(&self) -> Option<K> {
+        self.list.head_token().map(K::from)
+    }
+
+    /// Returns the token of the last item in the list, or `None` if list is empty.
+    pub fn tail_token(&self) -> Option<K> {
+        self.list.tail_token().map(K::from)
+    }
+
+    /// Returns a reference to the item identified by given token, or `None` if the token is invalid.
+    pub fn get(&self, token: K) -> Option<&T> {
+        self.list.get(token.into())
+    }
+
+    /// Returns a mutable reference to the item identified by given token, or `None` if the token is
+    /// invalid.
+    pub fn get_mut(&mut self, token: K) -> Option<&mut T> {
+        self.list.get_mut(token.into())
+    }
+
+    /// Returns the token of the item after the one identified by given token, or `None` if the
+    /// token is invalid or identifies the last item.
+    pub fn next_token(&self, token: K) -> Option<K> {
+        self.list.next_token(token.into()).map(K::from)
+    }
+
+    /// Returns the token of the item before the one identified by given token, or `None` if the
+    /// token is invalid or identifies the first item.
+    pub fn prev_token(&self, token: K) -> Option<K> {
+        self.list.prev_token(token.into()).map(K::from)
+    }
+
+    /// Insert an item after the item identified by given token. Returns a token which corresponds to
+    /// the new item.
+    ///
+    /// # Panics
+    /// Panics if `after` is an invalid token.
+    pub fn insert_after(&mut self, after: K, data: T) -> K {
+        self.list.insert_after(after.into(), data).into()
+    }
+
+    /// Insert an item before the item identified by given token. Returns a token which corresponds
+    /// to the new item.
+    ///
+    /// # Panics
+    /// Panics if `before` is an invalid token.
+    pub fn insert_before(&mut self, before: K, data: T) -> K {
+        self.list.insert_before(before.into(), data).into()
+    }
+
+    /// Remove the item identified by given token from the list and return the item. Invalidates the
+    /// token. Returns `None` if token is invalid.
+    pub fn remove(&mut self, token: K) -> Option<T> {
+        self.list.remove(token.into())
+    }
+
+    /// Returns an iterator of pairs of (tokens, references to items), in list order.
+    pub fn iter_with_tokens(&self) -> impl Iterator<Item = (K, &T)> + '_ {
+        self.list
+            .iter_with_tokens()
+            .map(|(token, data)| (K::from(token), data))
+    }
+}
+
+impl<T, K> FromIterator<T> for TypedTokenList<T, K> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from_list(iter.into_iter().collect())
+    }
+}
+
+impl<T, K> std::ops::Index<K> for TypedTokenList<T, K>
+where
+    K: Into<ItemToken>,
+{
+    type Output = T;
+
+    fn index(&self, token: K) -> &Self::Output {
+        &self.list[token.into()]
+    }
+}
+
+impl<T, K> std::ops::IndexMut<K> for TypedTokenList<T, K>
+where
+    K: Into<ItemToken>,
+{
+    fn index_mut(&mut self, token: K) -> &mut Self::Output {
+        &mut self.list[token.into()]
+    }
+}
@@ -0,0 +1,87 @@
+// SPDX-License-Identifier: MIT
+
+use crate::{GenerationalTokenList, ItemToken};
+
+/// A detached walk over the tokens of a list, created by
+/// [`GenerationalTokenList::walk_tokens`].
+///
+/// Unlike an iterator, a walk doesn't borrow the list, so the list can be used (and mutated) in
+/// between steps. Mutating the list in the middle of a walk is usually a logic error though, e.g.
+/// removing the item the walk is about to step from. With the `debug-checks` feature, the walk
+/// remembers the list's [`version`](GenerationalTokenList::version) and [`next`](Self::next)
+/// panics if the list has been structurally changed since the walk was created or last
+/// [`resync`](Self::resync)ed. Without the feature, no checks are made.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TokenWalk {
+    /// The token returned last, or `None` before the first step.
+    current: Option<ItemToken>,
+    done: bool,
+    #[cfg(feature = "debug-checks")]
+    version: u64,
+}
+
+impl TokenWalk {
+    /// Returns the token of the next item, or `None` once the end of the list has been reached.
+    ///
+    /// # Panics
+    /// With the `debug-checks` feature, panics if `list` has been structurally changed since the
+    /// walk was created or last resynced.
+    pub fn next<T>(&mut self, list: &GenerationalTokenList<T>) -> Option<ItemToken> {
+        #[cfg(feature = "debug-checks")]
+        assert_eq!(
+            self.version,
+            list.version(),
+            "list was structurally changed during a token walk (at {:?})",
+            self.current
+        );
+
+        if self.done {
+            return None;
+        }
+        let next = match self.current {
+            Some(token) => list.next_token(token),
+            None => list.head_token(),
+        };
+        self.done = next.is_none();
+        self.current = next;
+        next
+    }
+
+    /// Accept the structural changes made to `list` so far, and carry on from the token returned
+    /// last. That token must still be valid and linked.
+    pub fn resync<T>(&mut self, list: &GenerationalTokenList<T>) {
+        #[cfg(feature = "debug-checks")]
+        {
+            self.version = list.version();
+        }
+        #[cfg(not(feature = "debug-checks"))]
+        let _ = list;
+    }
+}
+
+impl<T> GenerationalTokenList<T> {
+    /// Start a walk over the tokens of the list, in order, that doesn't borrow the list. See
+    /// [`TokenWalk`].
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::GenerationalTokenList;
+    /// let mut list = GenerationalTokenList::new();
+    /// list.push_back(1);
+    /// list.push_back(2);
+    ///
+    /// let mut walk = list.walk_tokens();
+    /// while let Some(token) = walk.next(&list) {
+    ///     list[token] *= 10;
+    /// }
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), vec![&10, &20]);
+    /// ```
+    pub fn walk_tokens(&self) -> TokenWalk {
+        TokenWalk {
+            current: None,
+            done: false,
+            #[cfg(feature = "debug-checks")]
+            version: self.version(),
+        }
+    }
+}
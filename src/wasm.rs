@@ -0,0 +1,236 @@
+// SPDX-License-Identifier: MIT
+
+use std::convert::TryFrom;
+
+use ::wasm_bindgen::prelude::*;
+
+use crate::{GenerationalTokenList, ItemToken};
+
+/// A list of JavaScript values, exported to JavaScript as `TokenList`.
+///
+/// It has the same semantics as [`GenerationalTokenList`]: tokens stay valid for as long as their
+/// item is in the list, and are never valid for an item inserted later. Tokens are handed to
+/// JavaScript as `bigint`s packing the slot (low 64 bits) and generation (high 64 bits), so they
+/// can be compared with `===` and used as `Map` keys, and never run out however many items are
+/// removed over the lifetime of the list.
+///
+/// Methods that take a token return `undefined` (or `false`) instead of throwing when the token is
+/// invalid, except for the insertions, which throw.
+#[wasm_bindgen(js_name = TokenList)]
+#[derive(Clone, Debug, Default)]
+pub struct JsTokenList {
+    list: GenerationalTokenList<JsValue>,
+}
+
+#[wasm_bindgen(js_class = TokenList)]
+impl JsTokenList {
+    /// Creates a new, empty list.
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of items in the list.
+    #[wasm_bindgen(getter)]
+    pub fn length(&self) -> usize {
+        self.list.len()
+    }
+
+    /// Returns the first item in the list.
+    pub fn head(&self) -> JsValue {
+        self.list.head().cloned().unwrap_or(JsValue::UNDEFINED)
+    }
+
+    /// Returns the last item in the list.
+    pub fn tail(&self) -> JsValue {
+        self.list.tail().cloned().unwrap_or(JsValue::UNDEFINED)
+    }
+
+    /// Returns the token of the first item in the list.
+    #[wasm_bindgen(js_name = headToken)]
+    pub fn head_token(&self) -> Option<u128> {
+        self.list.head_token().map(to_js)
+    }
+
+    /// Returns the token of the last item in the list.
+    #[wasm_bindgen(js_name = tailToken)]
+    pub fn tail_token(&self) -> Option<u128> {
+        self.list.tail_token().map(to_js)
+    }
+
+    /// Returns the token of the item after the one identified by given token.
+    #[wasm_bindgen(js_name = nextToken)]
+    pub fn next_token(&self, token: u128) -> Option<u128> {
+        self.list.next_token(from_js(token)).map(to_js)
+    }
+
+    /// Returns the token of the item before the one identified by given token.
+    #[wasm_bindgen(js_name = prevToken)]
+    pub fn prev_token(&self, token: u128) -> Option<u128> {
+        self.list.prev_token(from_js(token)).map(to_js)
+    }
+
+    /// Returns the item identified by given token.
+    pub fn get(&self, token: u128) -> JsValue {
+        self.list
+            .get(from_js(token))
+            .cloned()
+            .unwrap_or(JsValue::UNDEFINED)
+    }
+
+    /// Replaces the item identified by given token. Returns `false` if the token is invalid.
+    pub fn set(&mut self, token: u128, value: JsValue) -> bool {
+        match self.list.get_mut(from_js(token)) {
+            Some(item) => {
+                *item = value;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns whether the token identifies an item of the list.
+    pub fn contains(&self, token: u128) -> bool {
+        self.list.get(from_js(token)).is_some()
+    }
+
+    /// Appends an item to the end of the list and returns its token.
+    #[wasm_bindgen(js_name = pushBack)]
+    pub fn push_back(&mut self, value: JsValue) -> u128 {
+        to_js(self.list.push_back(value))
+    }
+
+    /// Prepends an item to the beginning of the list and returns its token.
+    #[wasm_bindgen(js_name = pushFront)]
+    pub fn push_front(&mut self, value: JsValue) -> u128 {
+        to_js(self.list.push_front(value))
+    }
+
+    /// Inserts an item after the one identified by given token and returns its token. Throws if
+    /// the token is invalid.
+    #[wasm_bindgen(js_name = insertAfter)]
+    pub fn insert_after(&mut self, after: u128, value: JsValue) -> Result<u128, JsError> {
+        let after = self.valid(after)?;
+        Ok(to_js(self.list.insert_after(after, value)))
+    }
+
+    /// Inserts an item before the one identified by given token and returns its token. Throws if
+    /// the token is invalid.
+    #[wasm_bindgen(js_name = insertBefore)]
+    pub fn insert_before(&mut self, before: u128, value: JsValue) -> Result<u128, JsError> {
+        let before = self.valid(before)?;
+        Ok(to_js(self.list.insert_before(before, value)))
+    }
+
+    /// Removes the item identified by given token and returns it.
+    pub fn remove(&mut self, token: u128) -> JsValue {
+        self.list
+            .remove(from_js(token))
+            .unwrap_or(JsValue::UNDEFINED)
+    }
+
+    /// Removes the first item from the list and returns it.
+    #[wasm_bindgen(js_name = popFront)]
+    pub fn pop_front(&mut self) -> JsValue {
+        self.list.pop_front().unwrap_or(JsValue::UNDEFINED)
+    }
+
+    /// Removes the last item from the list and returns it.
+    #[wasm_bindgen(js_name = popBack)]
+    pub fn pop_back(&mut self) -> JsValue {
+        self.list.pop_back().unwrap_or(JsValue::UNDEFINED)
+    }
+
+    /// Removes all items from the list. Invalidates all tokens.
+    pub fn clear(&mut self) {
+        self.list.clear();
+    }
+
+    /// Returns the items as an array, in list order.
+    #[wasm_bindgen(js_name = toArray)]
+    pub fn to_array(&self) -> Vec<JsValue> {
+        self.list.iter().cloned().collect()
+    }
+
+    /// Returns the tokens as an array, in list order.
+    pub fn tokens(&self) -> Vec<JsValue> {
+        self.list
+            .iter_with_tokens()
+            .map(|(token, _)| JsValue::from(to_js(token)))
+            .collect()
+    }
+
+    fn valid(&self, token: u128) -> Result<ItemToken, JsError> {
+        let token = from_js(token);
+        match self.list.get(token) {
+            Some(_) => Ok(token),
+            None => Err(JsError::new("invalid token")),
+        }
+    }
+}
+
+impl JsTokenList {
+    /// Returns the underlying list.
+    pub fn as_list(&self) -> &GenerationalTokenList<JsValue> {
+        &self.list
+    }
+
+    /// Returns the underlying list mutably.
+    pub fn as_list_mut(&mut self) -> &mut GenerationalTokenList<JsValue> {
+        &mut self.list
+    }
+
+    /// Returns the underlying list.
+    pub fn into_list(self) -> GenerationalTokenList<JsValue> {
+        self.list
+    }
+
+    /// Converts a token of the underlying list to its JavaScript representation.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::{GenerationalTokenList, JsTokenList};
+    /// let mut list = GenerationalTokenList::new();
+    /// list.push_back(());
+    /// list.pop_back();
+    /// let token = list.push_back(());
+    ///
+    /// let number = JsTokenList::token_to_js(token);
+    /// assert_eq!(number, 1 << 64);
+    /// assert_eq!(JsTokenList::token_from_js(number), token);
+    /// ```
+    pub fn token_to_js(token: ItemToken) -> u128 {
+        to_js(token)
+    }
+
+    /// Converts a token received from JavaScript to a token of the underlying list.
+    ///
+    /// # Examples
+    /// ```
+    /// # use generational_token_list::JsTokenList;
+    /// // Generations beyond 32 bits, after billions of removals, round-trip as well
+    /// let number = (1 << 40) << 64 | 7;
+    /// let token = JsTokenList::token_from_js(number);
+    /// assert_eq!(JsTokenList::token_to_js(token), number);
+    /// ```
+    pub fn token_from_js(token: u128) -> ItemToken {
+        from_js(token)
+    }
+}
+
+impl From<GenerationalTokenList<JsValue>> for JsTokenList {
+    fn from(list: GenerationalTokenList<JsValue>) -> Self {
+        JsTokenList { list }
+    }
+}
+
+fn to_js(token: ItemToken) -> u128 {
+    u128::from(token.generation()) << 64 | token.slot() as u128
+}
+
+fn from_js(token: u128) -> ItemToken {
+    // A slot that doesn't fit in `usize` can't be in the arena, so make sure it doesn't wrap
+    // around to one that is
+    let slot = usize::try_from(token as u64).unwrap_or(usize::MAX);
+    ItemToken::from_slot_and_generation(slot, (token >> 64) as u64)
+}